@@ -0,0 +1,58 @@
+use std::collections::VecDeque;
+
+/// Telegram rejects buttons with more callback data than that, in bytes
+const MAX_CALLBACK_DATA: usize = 64;
+
+const CAPACITY: usize = 50;
+
+/// Values too long to go in the callback data of a button (tag filters,
+/// tags), kept here with a short `~key` put in the button instead.
+/// Only the latest ones are kept, older buttons simply expire.
+pub(super) struct ButtonValues {
+    values: VecDeque<(u64, String)>,
+    next_key: u64
+}
+
+impl ButtonValues {
+
+    pub(super) fn new() -> Self {
+        ButtonValues { values: VecDeque::new(), next_key: 0 }
+    }
+
+    /// Callback data made of `prefix` and `value`, with the value swapped
+    /// for a key when it does not fit or could be mistaken for one
+    pub(super) fn data(&mut self, prefix: &str, value: &str) -> String {
+
+        if prefix.len() + value.len() <= MAX_CALLBACK_DATA && !value.starts_with('~') {
+            return format!("{}{}", prefix, value);
+        }
+
+        let key = match self.values.iter().find(|(_key, v)| v == value) {
+            Some((key, _value)) => *key,
+            None => {
+                let key = self.next_key;
+                self.next_key += 1;
+                if self.values.len() == CAPACITY {
+                    self.values.pop_front();
+                }
+                self.values.push_back((key, value.to_owned()));
+                key
+            }
+        };
+
+        format!("{}~{}", prefix, key)
+    }
+
+    /// Value from the callback data of a button, `None` if it expired
+    pub(super) fn resolve(&self, data: &str) -> Option<String> {
+        match data.strip_prefix('~') {
+            None => Some(data.to_owned()),
+            Some(key) => {
+                let key: u64 = key.parse().ok()?;
+                self.values.iter()
+                    .find(|(k, _value)| *k == key)
+                    .map(|(_key, value)| value.clone())
+            }
+        }
+    }
+}
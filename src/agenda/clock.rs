@@ -0,0 +1,18 @@
+use std::sync::Arc;
+use super::Instant;
+
+/// Source of the current time, so that the tests can simulate it
+pub(crate) trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+pub(crate) type SharedClock = Arc<dyn Clock>;
+
+/// The system's wall clock
+pub(crate) struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        chrono::Local::now()
+    }
+}
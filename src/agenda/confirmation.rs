@@ -0,0 +1,46 @@
+use super::event::AgendaEvent;
+
+/// Where a confirmed event goes
+#[derive(Debug, Clone, Copy)]
+pub(super) enum EventTarget {
+    New,
+    Existing(u64)
+}
+
+/// Event waiting for the user to pick one of its possible readings.
+/// Only the latest one is kept, older buttons simply expire.
+pub(super) struct PendingEvent {
+    pub(super) seq: u64,
+    pub(super) target: EventTarget,
+    pub(super) candidates: Vec<AgendaEvent>
+}
+
+pub(super) struct Confirmations {
+    pending: Option<PendingEvent>,
+    next_seq: u64
+}
+
+impl Confirmations {
+
+    pub(super) fn new() -> Self {
+        Confirmations { pending: None, next_seq: 0 }
+    }
+
+    /// Returns the sequence number to put in the buttons
+    pub(super) fn push(&mut self, target: EventTarget, candidates: Vec<AgendaEvent>) -> u64 {
+
+        let seq = self.next_seq;
+        self.next_seq += 1;
+
+        self.pending = Some(PendingEvent { seq, target, candidates });
+
+        seq
+    }
+
+    pub(super) fn take(&mut self, seq: u64) -> Option<PendingEvent> {
+        match &self.pending {
+            Some(pending) if pending.seq == seq => self.pending.take(),
+            _ => None
+        }
+    }
+}
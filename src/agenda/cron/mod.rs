@@ -0,0 +1,180 @@
+use serde::{Deserialize, Serialize};
+use chrono::{Datelike, Timelike};
+use crate::{Opts, DateFormat};
+use crate::locale::{Msg, fill};
+use super::Instant;
+
+#[cfg(test)]
+mod tests;
+
+
+pub const CRON_COLUMNS: [CronColumn; 5] = [
+    CronColumn::Minute,
+    CronColumn::Hour,
+    CronColumn::Day,
+    CronColumn::Month,
+    CronColumn::Year
+];
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[repr(usize)]
+pub enum CronColumn {
+    Minute = 0,
+    Hour = 1,
+    Day = 2,
+    Month = 3,
+    Year = 4
+}
+
+impl CronColumn {
+
+    pub fn rank(&self) -> usize {
+        *self as usize
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum CronValue {
+    Every,
+    On(u64)
+}
+
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Cronline {
+    line: [CronValue; 5],
+    /// Second of the minute at which it fires, for "in 30 seconds"
+    #[serde(default)]
+    second: u64
+}
+
+impl Cronline {
+
+    pub fn from_values(line: [CronValue; 5]) -> Self {
+        Cronline {
+            line,
+            second: 0
+        }
+    }
+
+    pub fn with_second(mut self, second: u64) -> Self {
+        self.second = second;
+        self
+    }
+
+    pub fn second(&self) -> u64 {
+        self.second
+    }
+
+    pub fn from_time(t: &Instant) -> Self {
+        Cronline {
+            line: [
+                CronValue::On(t.minute() as u64),
+                CronValue::On(t.hour() as u64),
+                CronValue::On(t.day() as u64),
+                CronValue::On(t.month() as u64),
+                CronValue::On(t.year() as u64)
+            ],
+            second: 0
+        }
+    }
+
+    pub fn get(&self, col: CronColumn) -> CronValue {
+        self.line[col.rank()]
+    }
+
+    pub fn is_recurring(&self) -> bool {
+        self.line.contains(&CronValue::Every)
+    }
+
+    pub fn msg_format(&self, opts: &Opts) -> String {
+
+        let format_val = |cronval, width| match cronval {
+            CronValue::Every => "_".repeat(width),
+            CronValue::On(val) => format!("{:0>1$}", val, width)
+        };
+
+        let (c1, c2) = match opts.date_format {
+            DateFormat::DMY => (CronColumn::Day, CronColumn::Month),
+            DateFormat::MDY => (CronColumn::Month, CronColumn::Day),
+        };
+
+        let seconds = match self.second {
+            0 => String::new(),
+            second => format!(":{:02}", second)
+        };
+
+        format!(
+            "{}/{}/{} {}:{}{}",
+            format_val(self.get(c1), 2),
+            format_val(self.get(c2), 2),
+            format_val(self.get(CronColumn::Year), 4),
+            format_val(self.get(CronColumn::Hour), 2),
+            format_val(self.get(CronColumn::Minute), 2),
+            seconds
+        )
+    }
+
+    /// In words, like "every day at 17:00" or "once on 21 August 2024 at 9:00"
+    pub fn describe(&self, opts: &Opts) -> String {
+
+        let locale = opts.locale;
+
+        let value = |col| match self.get(col) {
+            CronValue::Every => None,
+            CronValue::On(val) => Some(val)
+        };
+        let (minute, hour, day, month, year) = (
+            value(CronColumn::Minute),
+            value(CronColumn::Hour),
+            value(CronColumn::Day),
+            value(CronColumn::Month),
+            value(CronColumn::Year)
+        );
+
+        let time = || opts.time_format.clock(hour.unwrap_or(0), minute.unwrap_or(0), self.second);
+        let date = |year| locale.written_date(day.unwrap_or(1), month.unwrap_or(1), year, &opts.date_format);
+
+        // Repeats at the smallest wildcard, the fixed columns below it
+        // are part of the phrase
+        let frequency = CRON_COLUMNS.iter().copied().find(|col| self.get(*col) == CronValue::Every);
+
+        let mut parts = vec![match frequency {
+            None => fill(locale.tr(Msg::RecurOnce), &[&date(year), &time()]),
+            Some(CronColumn::Minute) => locale.tr(Msg::RecurMinute).to_owned(),
+            Some(CronColumn::Hour) => fill(locale.tr(Msg::RecurHour), &[&minute.unwrap_or(0)]),
+            Some(CronColumn::Day) => fill(locale.tr(Msg::RecurDay), &[&time()]),
+            Some(CronColumn::Month) => fill(
+                locale.tr(Msg::RecurMonth),
+                &[&locale.ordinal_day(day.unwrap_or(1)), &time()]
+            ),
+            Some(CronColumn::Year) => fill(locale.tr(Msg::RecurYear), &[&date(None), &time()])
+        }];
+
+        // Fixed columns above it narrow it down ("every day in March")
+        let rank = frequency.map_or(CRON_COLUMNS.len(), |col| col.rank());
+        if let Some(hour) = hour.filter(|_| rank < CronColumn::Hour.rank()) {
+            parts.push(fill(locale.tr(Msg::DuringHour), &[
+                &opts.time_format.clock(hour, 0, 0),
+                &opts.time_format.clock(hour, 59, 0)
+            ]));
+        }
+        if let Some(day) = day.filter(|_| rank < CronColumn::Day.rank()) {
+            parts.push(fill(locale.tr(Msg::OnDay), &[&locale.ordinal_day(day)]));
+        }
+        let month = month.filter(|_| rank < CronColumn::Month.rank());
+        let year = year.filter(|_| rank < CronColumn::Year.rank());
+        match (month, year) {
+            (Some(month), Some(year)) => parts.push(fill(
+                locale.tr(Msg::InMonth),
+                &[&format!("{} {}", locale.month_name(month), year)]
+            )),
+            (Some(month), None) => parts.push(fill(locale.tr(Msg::InMonth), &[&locale.month_name(month)])),
+            (None, Some(year)) => parts.push(fill(locale.tr(Msg::InYear), &[&year])),
+            (None, None) => ()
+        }
+
+        parts.join(" ")
+    }
+}
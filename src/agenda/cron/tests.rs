@@ -0,0 +1,68 @@
+#![allow(clippy::zero_prefixed_literal)]
+
+use clap::Clap;
+use crate::Opts;
+use super::{Cronline, CronValue};
+
+fn check_descriptions(args: &[&str], cases: &[(Cronline, &str)]) {
+
+    let opts = Opts::parse_from([&["placeholder", "placeholder"], args].concat());
+
+    for (cronline, expected) in cases.iter() {
+        assert_eq!(cronline.describe(&opts), *expected);
+    }
+}
+
+#[test]
+fn descriptions() {
+
+    use CronValue::{Every, On};
+
+    check_descriptions(&[], &[
+        (Cronline::from_values([On(00), On(17), Every, Every, Every]), "every day at 17:00"),
+        (Cronline::from_values([On(00), On(09), On(21), On(08), On(2024)]), "once on 21 August 2024 at 9:00"),
+        (Cronline::from_values([On(30), On(09), On(22), Every, Every]), "every month on the 22nd at 9:30"),
+        (Cronline::from_values([On(00), On(10), On(01), On(08), Every]), "every year on 1 August at 10:00"),
+        (Cronline::from_values([On(15), Every, Every, Every, Every]), "every hour at minute 15"),
+        (Cronline::from_values([Every, Every, Every, Every, Every]), "every minute"),
+        (Cronline::from_values([On(00), On(09), Every, On(03), Every]), "every day at 9:00 in March"),
+        (Cronline::from_values([On(00), On(09), Every, On(03), On(2024)]), "every day at 9:00 in March 2024"),
+        (Cronline::from_values([On(00), Every, On(13), Every, Every]), "every hour at minute 0 on the 13th"),
+        (Cronline::from_values([Every, On(09), Every, Every, Every]), "every minute from 9:00 to 9:59"),
+        (Cronline::from_values([Every, On(17), On(13), Every, Every]), "every minute from 17:00 to 17:59 on the 13th"),
+        (
+            Cronline::from_values([On(01), On(10), On(01), On(01), On(2000)]).with_second(15),
+            "once on 1 January 2000 at 10:01:15"
+        )
+    ]);
+
+    check_descriptions(&["--date-format", "mdy", "--time-format", "h12"], &[
+        (Cronline::from_values([On(00), On(17), Every, Every, Every]), "every day at 5:00pm"),
+        (Cronline::from_values([On(00), On(10), On(01), On(08), Every]), "every year on August 1st at 10:00am"),
+        (Cronline::from_values([On(30), On(00), On(21), On(08), On(2024)]), "once on August 21st, 2024 at 12:30am"),
+        (Cronline::from_values([On(00), On(12), On(02), Every, Every]), "every month on the 2nd at 12:00pm"),
+        (Cronline::from_values([Every, On(09), Every, Every, Every]), "every minute from 9:00am to 9:59am")
+    ]);
+}
+
+#[test]
+fn locale_descriptions() {
+
+    use CronValue::{Every, On};
+
+    let may_day = Cronline::from_values([On(00), On(09), On(01), On(05), Every]);
+    let daily = Cronline::from_values([On(30), On(18), Every, Every, Every]);
+
+    check_descriptions(&["--locale", "fr"], &[
+        (may_day.clone(), "tous les ans le 1er mai à 9:00"),
+        (daily.clone(), "tous les jours à 18:30")
+    ]);
+    check_descriptions(&["--locale", "de"], &[
+        (may_day.clone(), "jedes Jahr am 1. Mai um 9:00"),
+        (daily.clone(), "jeden Tag um 18:30")
+    ]);
+    check_descriptions(&["--locale", "es"], &[
+        (may_day, "cada año el 1 de mayo a las 9:00"),
+        (daily, "cada día a las 18:30")
+    ]);
+}
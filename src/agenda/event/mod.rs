@@ -0,0 +1,285 @@
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, Timelike};
+use chrono::offset::{TimeZone, LocalResult};
+use serde::{Deserialize, Serialize};
+use super::cron::{Cronline, CronValue, CronColumn};
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "SavedEvent")]
+pub struct AgendaEvent {
+    pub cronline: Cronline,
+    pub text: String,
+    pub tags: BTreeSet<String>,
+    pub bounds: Bounds,
+    /// Set while the event is paused
+    pub pause: Option<Pause>
+}
+
+/// An event as saved, including by the versions where it had at most one tag
+#[derive(Deserialize)]
+struct SavedEvent {
+    cronline: Cronline,
+    text: String,
+    #[serde(default)]
+    tag: Option<String>,
+    #[serde(default)]
+    tags: BTreeSet<String>,
+    #[serde(default)]
+    bounds: Bounds,
+    #[serde(default)]
+    pause: Option<Pause>
+}
+
+impl From<SavedEvent> for AgendaEvent {
+    fn from(saved: SavedEvent) -> Self {
+        let tags = saved.tags.into_iter()
+            .chain(saved.tag)
+            .map(|tag| normalize_tag(&tag))
+            .collect();
+        AgendaEvent {
+            cronline: saved.cronline,
+            text: saved.text,
+            tags,
+            bounds: saved.bounds,
+            pause: saved.pause
+        }
+    }
+}
+
+/// Occurrences are skipped, without being counted, while an event is paused
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Pause {
+    /// Occurrences fire again from that time on, None until resumed by hand
+    pub until: Option<Instant>
+}
+
+impl Pause {
+
+    pub fn covers(&self, t: &Instant) -> bool {
+        self.until.is_none_or(|until| *t < until)
+    }
+}
+
+/// Tags are single lowercase words, written with or without a leading '#'.
+/// Spaces, which used to be allowed, become dashes.
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim_start_matches('#')
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join("-")
+}
+
+/// Limits of a recurring event ("starting monday", "until march 10",
+/// "for 2 weeks", "5 times")
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Bounds {
+    /// Occurrences happen at or after that time
+    pub start: Option<Instant>,
+    /// Occurrences happen strictly before that time
+    pub end: Option<Instant>,
+    /// Number of occurrences left
+    pub remaining: Option<u64>
+}
+
+impl Bounds {
+
+    fn allows(&self, t: &Instant) -> bool {
+        self.remaining != Some(0)
+            && self.start.is_none_or(|start| *t >= start)
+            && self.end.is_none_or(|end| *t < end)
+    }
+}
+
+type Instant = DateTime<chrono::Local>;
+
+
+// TODO: move all that stuff to Cronline
+impl AgendaEvent {
+
+    pub fn new(cronline: Cronline, text: String) -> Self {
+        AgendaEvent { cronline, text, tags: BTreeSet::new(), bounds: Bounds::default(), pause: None }
+    }
+
+    pub fn get_next_occurence(&self, now: &Instant) -> Option<Instant> {
+
+        // Nothing before the start, which may be an occurrence itself
+        let search_t = match self.bounds.start {
+            Some(start) if start > *now => start - chrono::Duration::seconds(1),
+            _ => *now
+        };
+
+        next_cron_occurence(&self.cronline, &search_t)
+            .filter(|t| self.bounds.allows(t))
+    }
+
+    /// Next occurrence that is not skipped by a pause
+    pub fn get_next_active_occurence(&self, now: &Instant) -> Option<Instant> {
+        match &self.pause {
+            None => self.get_next_occurence(now),
+            Some(Pause { until: None }) => None,
+            Some(Pause { until: Some(until) }) => {
+                let search_t = (*until - chrono::Duration::seconds(1)).max(*now);
+                self.get_next_occurence(&search_t)
+            }
+        }
+    }
+
+    /// End of a pause that leaves no occurrence after it, when the event is over
+    pub fn pause_end_without_occurence(&self, now: &Instant) -> Option<Instant> {
+        match (&self.pause, self.get_next_active_occurence(now)) {
+            (Some(Pause { until: Some(until) }), None) => Some(*until),
+            _ => None
+        }
+    }
+
+    pub fn is_paused(&self, now: &Instant) -> bool {
+        self.pause.as_ref().is_some_and(|pause| pause.covers(now))
+    }
+
+    /// Counts an occurrence against the number of occurrences left
+    pub fn record_occurence(&mut self) {
+        if let Some(remaining) = &mut self.bounds.remaining {
+            *remaining = remaining.saturating_sub(1);
+        }
+    }
+
+    /// The occurrences that will fire, the ones skipped by a pause left out
+    pub fn upcoming_occurences<'a>(&'a self, now: &Instant) -> impl Iterator<Item = Instant> + 'a {
+        let nb_left = self.bounds.remaining.map_or(usize::MAX, |nb| nb as usize);
+        std::iter::successors(
+            self.get_next_active_occurence(now),
+            move |t| self.get_next_active_occurence(t)
+        )
+        .take(nb_left)
+    }
+}
+
+/// Next time matching the cronline, ignoring any bounds
+///
+/// Works like cron: starting from the first minute that can still fire after
+/// `now`, the first column that does not match is moved to its next allowed
+/// value and all the columns below it are reset, until every column matches.
+pub(super) fn next_cron_occurence(cronline: &Cronline, now: &Instant) -> Option<Instant> {
+
+    let value = |col| match cronline.get(col) {
+        CronValue::Every => None,
+        CronValue::On(val) => Some(val)
+    };
+    let (year, month, day, hour, minute) = (
+        value(CronColumn::Year),
+        value(CronColumn::Month),
+        value(CronColumn::Day),
+        value(CronColumn::Hour),
+        value(CronColumn::Minute)
+    );
+
+    let second = cronline.second();
+
+    // Outside of those, the cronline can never match
+    if second > 59
+        || month.is_some_and(|m| !(1..=12).contains(&m))
+        || day.is_some_and(|d| !(1..=31).contains(&d))
+        || hour.is_some_and(|h| h > 23)
+        || minute.is_some_and(|m| m > 59) {
+        return None;
+    }
+
+    // The calendar repeats itself every 400 years
+    let max_year = match year {
+        Some(y) => {
+            let y = i32::try_from(y).ok()?;
+            NaiveDate::from_ymd_opt(y, 12, 31)?;
+            y
+        },
+        None => now.year() + 400
+    };
+
+    let second = chrono::Duration::seconds(second as i64);
+    let now = now.naive_local();
+
+    // First minute where firing would be after now
+    let minute_start = now.date().and_hms(now.hour(), now.minute(), 0);
+    let mut t = match minute_start + second > now {
+        true => minute_start,
+        false => minute_start + chrono::Duration::minutes(1)
+    };
+
+    while t.year() <= max_year {
+
+        let (t_year, t_month, t_day) = (t.year(), t.month(), t.day());
+
+        if let Some(y) = year {
+            let y = y as i32;
+            if t_year < y {
+                t = NaiveDate::from_ymd(y, 1, 1).and_hms(0, 0, 0);
+                continue;
+            }
+        }
+
+        if let Some(m) = month {
+            let m = m as u32;
+            if t_month != m {
+                let next_year = if t_month < m { t_year } else { t_year + 1 };
+                t = NaiveDate::from_ymd(next_year, m, 1).and_hms(0, 0, 0);
+                continue;
+            }
+        }
+
+        if let Some(d) = day {
+            let d = d as u32;
+            if t_day != d {
+                t = match NaiveDate::from_ymd_opt(t_year, t_month, d) {
+                    Some(date) if t_day < d => date.and_hms(0, 0, 0),
+                    _ => first_of_next_month(t_year, t_month)
+                };
+                continue;
+            }
+        }
+
+        if let Some(h) = hour {
+            let h = h as u32;
+            if t.hour() != h {
+                t = if t.hour() < h {
+                    t.date().and_hms(h, 0, 0)
+                } else {
+                    t.date().succ().and_hms(0, 0, 0)
+                };
+                continue;
+            }
+        }
+
+        if let Some(m) = minute {
+            let m = m as u32;
+            if t.minute() != m {
+                t = if t.minute() < m {
+                    t.date().and_hms(t.hour(), m, 0)
+                } else {
+                    t.date().and_hms(t.hour(), 0, 0) + chrono::Duration::hours(1)
+                };
+                continue;
+            }
+        }
+
+        match chrono::Local.from_local_datetime(&t) {
+            LocalResult::Single(instant) => return Some(instant + second),
+            // Skipped or repeated by a DST change
+            _ => t += chrono::Duration::minutes(1)
+        }
+    }
+
+    None
+}
+
+fn first_of_next_month(year: i32, month: u32) -> NaiveDateTime {
+    match month {
+        12 => NaiveDate::from_ymd(year + 1, 1, 1),
+        _ => NaiveDate::from_ymd(year, month + 1, 1)
+    }
+    .and_hms(0, 0, 0)
+}
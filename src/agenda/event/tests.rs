@@ -0,0 +1,206 @@
+#![allow(clippy::zero_prefixed_literal)]
+
+use chrono::{Datelike, TimeZone};
+use chrono::offset::LocalResult;
+use proptest::prelude::*;
+use super::super::cron::{Cronline, CronValue, CronColumn};
+use super::{next_cron_occurence, AgendaEvent, Bounds, Instant};
+
+/// The former implementation, which tries every combination of values
+/// within 4 years, kept as a reference
+fn brute_force_next_occurence(cronline: &Cronline, now: &Instant) -> Option<Instant> {
+
+    const COLUMNS: [CronColumn; 5] = [
+        CronColumn::Year,
+        CronColumn::Month,
+        CronColumn::Day,
+        CronColumn::Hour,
+        CronColumn::Minute
+    ];
+
+    fn recursion_func(now: &Instant, cronline: &Cronline, acc: &[u64]) -> Option<Instant> {
+
+        let level = acc.len();
+
+        if level < 5 {
+
+            let col = COLUMNS[level];
+            let curr_year = now.year() as u64;
+
+            let vals_to_try = match cronline.get(col) {
+                CronValue::On(val) => vec![val],
+                CronValue::Every => match col {
+                    CronColumn::Year => (curr_year..=curr_year+4).collect(),
+                    CronColumn::Month => (0..=12).collect(),
+                    CronColumn::Day => (0..=31).collect(),
+                    CronColumn::Hour => (0..=24).collect(),
+                    CronColumn::Minute => (0..=60).collect()
+                }
+            };
+
+            vals_to_try.into_iter().find_map(|val| {
+                let new_acc = &[acc, &[val]].concat();
+                recursion_func(now, cronline, new_acc)
+            })
+
+        } else {
+            let res = chrono::Local
+                .ymd_opt(acc[0] as i32, acc[1] as u32, acc[2] as u32)
+                .and_hms_opt(acc[3] as u32, acc[4] as u32, 0);
+            match res {
+                LocalResult::Single(t) => Some(t).filter(|t| t > now),
+                _ => None
+            }
+        }
+    }
+
+    recursion_func(now, cronline, &[])
+}
+
+fn cron_value(max: u64) -> impl Strategy<Value = CronValue> {
+    prop_oneof![
+        Just(CronValue::Every),
+        (0..=max).prop_map(CronValue::On)
+    ]
+}
+
+fn cronline() -> impl Strategy<Value = Cronline> {
+    (cron_value(59), cron_value(23), cron_value(31), cron_value(12), prop_oneof![
+        Just(CronValue::Every),
+        (1999u64..=2010).prop_map(CronValue::On)
+    ])
+    .prop_map(|(minute, hour, day, month, year)| {
+        Cronline::from_values([minute, hour, day, month, year])
+    })
+}
+
+fn instant() -> impl Strategy<Value = Instant> {
+    (2000i32..=2005, 1u32..=12, 1u32..=28, 0u32..=23, 0u32..=59, 0u32..=59)
+        .prop_map(|(year, month, day, hour, minute, second)| {
+            chrono::Local.ymd(year, month, day).and_hms(hour, minute, second)
+        })
+}
+
+proptest! {
+
+    // The brute force search is slow
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn matches_brute_force(cronline in cronline(), now in instant()) {
+
+        let expected = brute_force_next_occurence(&cronline, &now);
+        let actual = next_cron_occurence(&cronline, &now);
+
+        match expected {
+            Some(_) => prop_assert_eq!(actual, expected),
+            // The brute force search gives up after 4 years
+            None => prop_assert!(actual.is_none_or(|t| t.year() > now.year() + 4))
+        }
+    }
+}
+
+#[test]
+fn far_occurences() {
+
+    use CronValue::{Every, On};
+
+    let now = chrono::Local.ymd(2000, 03, 01).and_hms(08, 00, 00);
+
+    let leap_day = Cronline::from_values([On(00), On(09), On(29), On(02), Every]);
+    assert_eq!(
+        next_cron_occurence(&leap_day, &now),
+        Some(chrono::Local.ymd(2004, 02, 29).and_hms(09, 00, 00))
+    );
+
+    let now = chrono::Local.ymd(2097, 03, 01).and_hms(08, 00, 00);
+    assert_eq!(
+        next_cron_occurence(&leap_day, &now),
+        Some(chrono::Local.ymd(2104, 02, 29).and_hms(09, 00, 00))
+    );
+
+    let in_ten_years = Cronline::from_values([On(00), On(09), On(01), On(01), On(2010)]);
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+    assert_eq!(
+        next_cron_occurence(&in_ten_years, &now),
+        Some(chrono::Local.ymd(2010, 01, 01).and_hms(09, 00, 00))
+    );
+
+    let never = Cronline::from_values([Every, Every, On(31), On(02), Every]);
+    assert_eq!(next_cron_occurence(&never, &now), None);
+
+    let invalid = Cronline::from_values([Every, Every, On(00), Every, Every]);
+    assert_eq!(next_cron_occurence(&invalid, &now), None);
+}
+
+#[test]
+fn second_precision() {
+
+    use CronValue::{Every, On};
+
+    let in_30_seconds = Cronline::from_values([On(01), On(10), On(01), On(01), On(2000)])
+        .with_second(15);
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(10, 00, 45);
+    assert_eq!(
+        next_cron_occurence(&in_30_seconds, &now),
+        Some(chrono::Local.ymd(2000, 01, 01).and_hms(10, 01, 15))
+    );
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(10, 01, 14);
+    assert_eq!(
+        next_cron_occurence(&in_30_seconds, &now),
+        Some(chrono::Local.ymd(2000, 01, 01).and_hms(10, 01, 15))
+    );
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(10, 01, 15);
+    assert_eq!(next_cron_occurence(&in_30_seconds, &now), None);
+
+    let mut event = AgendaEvent::new(
+        Cronline::from_values([Every, Every, Every, Every, Every]).with_second(30),
+        "Stretch".to_owned()
+    );
+    event.bounds = Bounds { remaining: Some(2), ..Bounds::default() };
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(10, 00, 45);
+    let occurences: Vec<Instant> = event.upcoming_occurences(&now).collect();
+    assert_eq!(occurences, vec![
+        chrono::Local.ymd(2000, 01, 01).and_hms(10, 01, 30),
+        chrono::Local.ymd(2000, 01, 01).and_hms(10, 02, 30)
+    ]);
+}
+
+/// Rough timing comparison, run with
+/// `cargo test --release -- --ignored --nocapture bench_next_occurence`
+#[test]
+#[ignore]
+fn bench_next_occurence() {
+
+    use CronValue::{Every, On};
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+    let cronlines = [
+        ("every minute", Cronline::from_values([Every, Every, Every, Every, Every])),
+        ("daily", Cronline::from_values([On(30), On(09), Every, Every, Every])),
+        ("yearly", Cronline::from_values([On(00), On(12), On(25), On(12), Every])),
+        ("leap day", Cronline::from_values([On(00), On(09), On(29), On(02), Every])),
+        ("never", Cronline::from_values([Every, Every, On(31), On(02), Every]))
+    ];
+
+    let time = |f: &dyn Fn(&Cronline, &Instant) -> Option<Instant>, cronline| {
+        let start = std::time::Instant::now();
+        for _ in 0..100 {
+            f(cronline, &now);
+        }
+        start.elapsed() / 100
+    };
+
+    for (name, cronline) in cronlines.iter() {
+        println!(
+            "{:<12} brute force: {:>12?}  column by column: {:>12?}",
+            name,
+            time(&brute_force_next_occurence, cronline),
+            time(&next_cron_occurence, cronline)
+        );
+    }
+}
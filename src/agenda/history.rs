@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use crate::{Opts, DateFormat, Button};
+use crate::locale::{Locale, Msg, fill};
+use super::Instant;
+use super::cron::Cronline;
+
+/// A reminder that just fired, on its way to Telegram
+#[derive(Debug, Clone)]
+pub struct Reminder {
+    pub event_id: u64,
+    pub text: String,
+    pub tags: Vec<String>,
+    pub scheduled: Instant,
+    pub cronline: Cronline
+}
+
+impl Reminder {
+
+    /// Recurring events also tell when they come back
+    pub fn notification(&self, opts: &Opts) -> String {
+        match self.cronline.is_recurring() {
+            true => format!("⏰ {}\n🔁 {}", self.text, self.cronline.describe(opts)),
+            false => format!("⏰ {}", self.text)
+        }
+    }
+
+    /// Inline button letting the user acknowledge the reminder
+    pub fn ack_button(&self, locale: Locale) -> Button {
+        (
+            locale.tr(Msg::DoneButton).to_owned(),
+            format!("ack:{}:{}", self.event_id, self.scheduled.timestamp())
+        )
+    }
+}
+
+/// One line of the fire history: what was sent, when, and how it went
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct HistoryEntry {
+    pub(crate) event_id: u64,
+    pub(crate) text: String,
+    pub(crate) tags: Vec<String>,
+    pub(crate) scheduled: Instant,
+    pub(crate) sent: Instant,
+    /// `None` if the message was delivered
+    pub(crate) error: Option<String>,
+    pub(crate) acknowledged: Option<Instant>
+}
+
+impl HistoryEntry {
+
+    pub(crate) fn msg_format(&self, opts: &Opts) -> String {
+
+        let date_fmt = match opts.date_format {
+            DateFormat::DMY => "%d/%m/%Y %H:%M",
+            DateFormat::MDY => "%m/%d/%Y %H:%M"
+        };
+
+        let locale = opts.locale;
+
+        let delivery = match &self.error {
+            None => fill(locale.tr(Msg::HistorySent), &[&self.sent.format("%H:%M:%S")]),
+            Some(err) => fill(locale.tr(Msg::HistoryFailed), &[err])
+        };
+
+        let ack = match &self.acknowledged {
+            None => String::new(),
+            Some(t) => fill(locale.tr(Msg::HistoryDone), &[&t.format(date_fmt)])
+        };
+
+        let sanitized = self.text
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+
+        format!(
+            "<pre>  {} - [{}] {}\n      {}{}</pre>",
+            self.scheduled.format(date_fmt),
+            self.event_id,
+            sanitized,
+            delivery,
+            ack
+        )
+    }
+}
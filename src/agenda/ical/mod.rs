@@ -0,0 +1,662 @@
+//! Minimal iCalendar (RFC 5545) support: exporting events as VEVENTs with
+//! RRULEs, and importing VEVENTs/VTODOs with their VALARMs.
+//!
+//! Times are written as "floating" local times, since that is how Nag
+//! interprets cronlines.
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone};
+use chrono::offset::LocalResult;
+use anyhow::{anyhow, bail, Context};
+use log::debug;
+
+use super::{AgendaState, AgendaEvent, Bounds, Instant, normalize_tag};
+use super::cron::{Cronline, CronColumn, CronValue, CRON_COLUMNS};
+use super::time_parsing::DEFAULT_TIME;
+
+#[cfg(test)]
+mod tests;
+
+const PRODID: &str = "-//Nag//Nag reminders//EN";
+const TIME_FORMAT: &str = "%Y%m%dT%H%M%S";
+
+/// Result of an export or import: what could be converted, and a list of
+/// human-readable reasons for everything that could not.
+pub(crate) struct Conversion<T> {
+    pub(crate) output: T,
+    pub(crate) problems: Vec<String>
+}
+
+pub(crate) fn export(state: &AgendaState, now: &Instant) -> Conversion<String> {
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_owned(),
+        "VERSION:2.0".to_owned(),
+        format!("PRODID:{}", PRODID)
+    ];
+    let mut problems = vec![];
+
+    let mut ids: Vec<&u64> = state.events.keys().collect();
+    ids.sort();
+
+    for id in ids {
+        let event = &state.events[id];
+        match export_event(*id, event, now) {
+            Ok(event_lines) => lines.extend(event_lines),
+            Err(err) => problems.push(format!("event {} (\"{}\"): {}", id, event.text, err))
+        }
+    }
+
+    lines.push("END:VCALENDAR".to_owned());
+
+    let output = lines.iter()
+        .map(|line| fold_line(line))
+        .collect::<Vec<String>>()
+        .join("");
+
+    Conversion { output, problems }
+}
+
+fn export_event(id: u64, event: &AgendaEvent, now: &Instant) -> anyhow::Result<Vec<String>> {
+
+    // A pause with an end only moves the start of the recurrence
+    let start = match event.get_next_active_occurence(now) {
+        Some(start) => start,
+        None if event.is_paused(now) => bail!("paused until resumed"),
+        None => bail!("never occurs again")
+    };
+
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_owned(),
+        format!("UID:nag-event-{}", id),
+        format!("DTSTAMP:{}", now.with_timezone(&chrono::Utc).format("%Y%m%dT%H%M%SZ")),
+        format!("DTSTART:{}", start.format(TIME_FORMAT)),
+        format!("SUMMARY:{}", escape_text(&event.text))
+    ];
+
+    if let Some(categories) = categories(event) {
+        lines.push(categories);
+    }
+
+    if let Some(rrule) = make_rrule(event) {
+        lines.push(format!("RRULE:{}", rrule));
+    }
+
+    lines.extend_from_slice(&[
+        "BEGIN:VALARM".to_owned(),
+        "ACTION:DISPLAY".to_owned(),
+        "TRIGGER:PT0S".to_owned(),
+        format!("DESCRIPTION:{}", escape_text(&event.text)),
+        "END:VALARM".to_owned(),
+        "END:VEVENT".to_owned()
+    ]);
+
+    Ok(lines)
+}
+
+/// Read-only feed of the upcoming occurrences of every event, each
+/// occurrence being a separate VEVENT
+pub(crate) fn feed(state: &AgendaState, now: &Instant) -> String {
+
+    const HORIZON: i64 = 90; // days
+    const MAX_OCCURRENCES: usize = 50; // per event
+
+    let horizon = *now + Duration::days(HORIZON);
+    let dtstamp = now.with_timezone(&chrono::Utc).format("%Y%m%dT%H%M%SZ");
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_owned(),
+        "VERSION:2.0".to_owned(),
+        format!("PRODID:{}", PRODID),
+        "X-WR-CALNAME:Nag".to_owned()
+    ];
+
+    let mut ids: Vec<&u64> = state.events.keys().collect();
+    ids.sort();
+
+    for id in ids {
+
+        let event = &state.events[id];
+
+        let occurences = event.upcoming_occurences(now)
+            .take_while(|t| *t <= horizon)
+            .take(MAX_OCCURRENCES);
+
+        for t in occurences {
+            lines.extend_from_slice(&[
+                "BEGIN:VEVENT".to_owned(),
+                format!("UID:nag-event-{}-{}", id, t.timestamp()),
+                format!("DTSTAMP:{}", dtstamp),
+                format!("DTSTART:{}", t.format(TIME_FORMAT)),
+                format!("SUMMARY:{}", escape_text(&event.text))
+            ]);
+            if let Some(categories) = categories(event) {
+                lines.push(categories);
+            }
+            lines.push("END:VEVENT".to_owned());
+        }
+    }
+
+    lines.push("END:VCALENDAR".to_owned());
+
+    lines.iter()
+        .map(|line| fold_line(line))
+        .collect::<Vec<String>>()
+        .join("")
+}
+
+/// The frequency is given by the smallest wildcard column. Fixed columns
+/// below it are implied by DTSTART; fixed columns above it become BY* rules,
+/// except for a fixed year which bounds the recurrence. The bounds of the
+/// event become COUNT, or UNTIL when they end before that year.
+fn make_rrule(event: &AgendaEvent) -> Option<String> {
+
+    let cronline = &event.cronline;
+
+    let freq_col = CRON_COLUMNS.iter()
+        .find(|col| cronline.get(**col) == CronValue::Every)?;
+
+    let mut parts = vec![format!("FREQ={}", frequency_name(freq_col))];
+
+    for col in CRON_COLUMNS.iter().filter(|col| col.rank() > freq_col.rank()) {
+        match (col, cronline.get(*col)) {
+            (_, CronValue::Every) => (),
+            (CronColumn::Year, CronValue::On(year)) =>
+                parts.push(format!("UNTIL={}1231T235959", year)),
+            (_, CronValue::On(val)) =>
+                parts.push(format!("{}={}", by_rule_name(col), val))
+        }
+    }
+
+    let ends_earlier = |end: &Instant| match cronline.get(CronColumn::Year) {
+        CronValue::On(year) => (end.year() as u64) <= year,
+        CronValue::Every => true
+    };
+
+    match (&event.bounds.remaining, &event.bounds.end) {
+        (Some(count), _) => {
+            parts.retain(|part| !part.starts_with("UNTIL="));
+            parts.push(format!("COUNT={}", count));
+        },
+        (None, Some(end)) if ends_earlier(end) => {
+            parts.retain(|part| !part.starts_with("UNTIL="));
+            parts.push(format!("UNTIL={}", (*end - Duration::seconds(1)).format(TIME_FORMAT)));
+        },
+        _ => ()
+    }
+
+    Some(parts.join(";"))
+}
+
+fn frequency_name(col: &CronColumn) -> &'static str {
+    match col {
+        CronColumn::Minute => "MINUTELY",
+        CronColumn::Hour   => "HOURLY",
+        CronColumn::Day    => "DAILY",
+        CronColumn::Month  => "MONTHLY",
+        CronColumn::Year   => "YEARLY"
+    }
+}
+
+fn by_rule_name(col: &CronColumn) -> &'static str {
+    match col {
+        CronColumn::Minute => "BYMINUTE",
+        CronColumn::Hour   => "BYHOUR",
+        CronColumn::Day    => "BYMONTHDAY",
+        CronColumn::Month  => "BYMONTH",
+        CronColumn::Year   => unreachable!("years are not a BY* rule")
+    }
+}
+
+pub(crate) fn import(data: &str, now: &Instant) -> Conversion<Vec<AgendaEvent>> {
+
+    let mut events = vec![];
+    let mut problems = vec![];
+
+    let components = match parse_components(data) {
+        Ok(components) => components,
+        Err(err) => {
+            problems.push(format!("invalid iCalendar data: {}", err));
+            return Conversion { output: events, problems };
+        }
+    };
+
+    for component in components.iter() {
+
+        if component.name != "VEVENT" && component.name != "VTODO" {
+            continue;
+        }
+
+        let summary = component.get("SUMMARY")
+            .map(|prop| unescape_text(&prop.value))
+            .unwrap_or_default();
+
+        match import_component(component, now) {
+            Ok(event) => events.push(event),
+            Err(err) => problems.push(format!("\"{}\": {}", summary, err))
+        }
+    }
+
+    Conversion { output: events, problems }
+}
+
+fn import_component(component: &Component, now: &Instant) -> anyhow::Result<AgendaEvent> {
+
+    let text = component.get("SUMMARY")
+        .map(|prop| unescape_text(&prop.value))
+        .filter(|text| !text.trim().is_empty())
+        .ok_or_else(|| anyhow!("no summary"))?;
+
+    let start_prop = match component.name.as_str() {
+        "VTODO" => component.get("DUE").or_else(|| component.get("DTSTART")),
+        _ => component.get("DTSTART")
+    }
+    .ok_or_else(|| anyhow!("no start or due date"))?;
+
+    let start = parse_prop_time(start_prop)?;
+
+    let alarm_offset = component.children.iter()
+        .find(|child| child.name == "VALARM")
+        .and_then(|alarm| alarm.get("TRIGGER"))
+        .map(|trigger| {
+            if trigger.param("RELATED") == Some("END") {
+                bail!("alarms relative to the end of an event are not supported");
+            }
+            match trigger.param("VALUE") {
+                Some("DATE-TIME") => Ok(parse_prop_time(trigger)? - start),
+                _ => parse_duration(&trigger.value)
+            }
+        })
+        .transpose()?
+        .unwrap_or_else(Duration::zero);
+
+    let reminder_t = start.checked_add_signed(alarm_offset)
+        .ok_or_else(|| anyhow!("alarm out of range"))?;
+
+    let mut cronline = Cronline::from_time(&reminder_t);
+    let mut bounds = Bounds::default();
+
+    if let Some(rrule) = component.get("RRULE") {
+        let (rrule_cronline, rrule_bounds) = apply_rrule(cronline, &rrule.value)?;
+        cronline = rrule_cronline;
+        // The recurrence begins with DTSTART, which may be in the future
+        bounds = Bounds { start: Some(reminder_t), ..rrule_bounds };
+    }
+
+    let event = AgendaEvent {
+        cronline,
+        text,
+        tags: component.get("CATEGORIES")
+            .map(|prop| split_list(&prop.value).into_iter()
+                .map(|category| normalize_tag(&unescape_text(category)))
+                .filter(|tag| !tag.is_empty())
+                .collect())
+            .unwrap_or_default(),
+        bounds,
+        pause: None
+    };
+
+    if event.get_next_occurence(now).is_none() {
+        bail!("never occurs again");
+    }
+
+    Ok(event)
+}
+
+fn apply_rrule(cronline: Cronline, rrule: &str) -> anyhow::Result<(Cronline, Bounds)> {
+
+    let mut line: Vec<CronValue> = CRON_COLUMNS.iter()
+        .map(|col| cronline.get(*col))
+        .collect();
+
+    let mut freq_col = None;
+    let mut by_rules = vec![];
+    let mut bounded_year = false;
+    let mut bounds = Bounds::default();
+
+    for part in rrule.split(';').filter(|part| !part.is_empty()) {
+
+        let (name, value) = part.split_once('=')
+            .ok_or_else(|| anyhow!("invalid recurrence rule part \"{}\"", part))?;
+
+        match name.to_uppercase().as_str() {
+            "FREQ" => {
+                let col = CRON_COLUMNS.iter()
+                    .find(|col| frequency_name(col) == value.to_uppercase())
+                    .ok_or_else(|| anyhow!("unsupported recurrence frequency {}", value))?;
+                freq_col = Some(*col);
+            },
+            "INTERVAL" if value == "1" => (),
+            // Only a recurrence ending with the year (as exported) can be represented
+            "UNTIL" if matches!(
+                (cronline.get(CronColumn::Year), value.get(..8)),
+                (CronValue::On(year), Some(date)) if date == format!("{}1231", year)
+            ) => bounded_year = true,
+            "UNTIL" => bounds.end = Some(parse_until(value)?),
+            "COUNT" => bounds.remaining = Some(value.parse()
+                .map_err(|_| anyhow!("invalid recurrence count {}", value))?),
+            "WKST" => (),
+            "BYMINUTE" | "BYHOUR" | "BYMONTHDAY" | "BYMONTH" => {
+                let val: u64 = value.parse()
+                    .map_err(|_| anyhow!("unsupported recurrence rule {}", part))?;
+                let col = CRON_COLUMNS.iter()
+                    .filter(|col| **col != CronColumn::Year)
+                    .find(|col| by_rule_name(col) == name.to_uppercase())
+                    .unwrap();
+                by_rules.push((*col, val));
+            },
+            _ => bail!("unsupported recurrence rule {}", part)
+        }
+    }
+
+    let freq_col = freq_col.ok_or_else(|| anyhow!("recurrence rule without frequency"))?;
+
+    for col in CRON_COLUMNS.iter().filter(|col| col.rank() >= freq_col.rank()) {
+        if !(bounded_year && *col == CronColumn::Year) {
+            line[col.rank()] = CronValue::Every;
+        }
+    }
+    for (col, val) in by_rules {
+        if col.rank() <= freq_col.rank() {
+            bail!("unsupported recurrence rule {}={}", by_rule_name(&col), val);
+        }
+        line[col.rank()] = CronValue::On(val);
+    }
+
+    let line = [line[0], line[1], line[2], line[3], line[4]];
+    debug!("Recurrence rule {} mapped to {:?}", rrule, line);
+
+    Ok((Cronline::from_values(line), bounds))
+}
+
+/// UNTIL is inclusive, a date meaning the whole day
+fn parse_until(value: &str) -> anyhow::Result<Instant> {
+    match value.contains('T') {
+        true => parse_time(value)?.checked_add_signed(Duration::seconds(1))
+            .ok_or_else(|| anyhow!("invalid date-time {}", value)),
+        false => {
+            let date = NaiveDate::parse_from_str(value, "%Y%m%d")
+                .with_context(|| format!("invalid date {}", value))?;
+            let next_day = date.succ_opt()
+                .ok_or_else(|| anyhow!("invalid date {}", value))?
+                .and_hms(0, 0, 0);
+            match chrono::Local.from_local_datetime(&next_day) {
+                LocalResult::Single(t) | LocalResult::Ambiguous(t, _) => Ok(t),
+                LocalResult::None => bail!("{} does not exist in the local timezone", value)
+            }
+        }
+    }
+}
+
+/// Time of a DTSTART, DUE or TRIGGER property. Times in a TZID other than
+/// UTC are refused rather than shifted, converting them would need the
+/// timezone database.
+fn parse_prop_time(prop: &Property) -> anyhow::Result<Instant> {
+    match prop.param("TZID") {
+        None => parse_time(&prop.value),
+        Some("UTC") | Some("ETC/UTC") | Some("GMT") | Some("ETC/GMT") if prop.value.contains('T') =>
+            parse_time(&format!("{}Z", prop.value.trim_end_matches('Z'))),
+        Some(tzid) => bail!("time zone {} is not supported, only UTC and floating times are", tzid)
+    }
+}
+
+/// Accepts dates, floating and UTC date-times. Dates get the default time of day.
+fn parse_time(value: &str) -> anyhow::Result<Instant> {
+
+    let naive = if let Some(utc) = value.strip_suffix('Z') {
+        let t = NaiveDateTime::parse_from_str(utc, TIME_FORMAT)
+            .with_context(|| format!("invalid date-time {}", value))?;
+        return Ok(chrono::Utc.from_utc_datetime(&t).with_timezone(&chrono::Local));
+    } else if value.contains('T') {
+        NaiveDateTime::parse_from_str(value, TIME_FORMAT)
+            .with_context(|| format!("invalid date-time {}", value))?
+    } else {
+        let (def_h, def_m) = DEFAULT_TIME;
+        NaiveDate::parse_from_str(value, "%Y%m%d")
+            .with_context(|| format!("invalid date {}", value))?
+            .and_hms(def_h as u32, def_m as u32, 0)
+    };
+
+    match chrono::Local.from_local_datetime(&naive) {
+        LocalResult::Single(t) | LocalResult::Ambiguous(t, _) => Ok(t),
+        LocalResult::None => bail!("{} does not exist in the local timezone", value)
+    }
+}
+
+/// Parses RFC 5545 durations such as `-PT15M` or `P1DT12H`
+fn parse_duration(value: &str) -> anyhow::Result<Duration> {
+
+    let invalid = || anyhow!("invalid duration {}", value);
+
+    let (sign, rest) = match value.chars().next() {
+        Some('-') => (-1, &value[1..]),
+        Some('+') => (1, &value[1..]),
+        _ => (1, value)
+    };
+
+    let rest = rest.strip_prefix('P').ok_or_else(invalid)?;
+
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    let mut in_time = false;
+
+    for c in rest.chars() {
+        match c {
+            'T' => in_time = true,
+            '0'..='9' => number.push(c),
+            _ => {
+                let n: i64 = number.parse().map_err(|_| invalid())?;
+                number.clear();
+                let unit_seconds = match (c, in_time) {
+                    ('W', false) => 7 * 24 * 3600,
+                    ('D', false) => 24 * 3600,
+                    ('H', true)  => 3600,
+                    ('M', true)  => 60,
+                    ('S', true)  => 1,
+                    _ => return Err(invalid())
+                };
+                // The numbers come from the file, they can be anything
+                total = n.checked_mul(unit_seconds)
+                    .and_then(|seconds| seconds.checked_mul(1000))
+                    .map(Duration::milliseconds)
+                    .and_then(|term| total.checked_add(&term))
+                    .ok_or_else(invalid)?;
+            }
+        }
+    }
+
+    if !number.is_empty() {
+        return Err(invalid());
+    }
+
+    Ok(total * sign)
+}
+
+#[derive(Debug)]
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String
+}
+
+impl Property {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter()
+            .find(|(n, _v)| n == name)
+            .map(|(_n, v)| v.as_str())
+    }
+}
+
+#[derive(Debug)]
+struct Component {
+    name: String,
+    properties: Vec<Property>,
+    children: Vec<Component>
+}
+
+impl Component {
+    fn get(&self, name: &str) -> Option<&Property> {
+        self.properties.iter().find(|prop| prop.name == name)
+    }
+}
+
+/// Returns all the components found in the data, flattened
+/// (VALARMs stay attached to their parent).
+fn parse_components(data: &str) -> anyhow::Result<Vec<Component>> {
+
+    let mut stack: Vec<Component> = vec![];
+    let mut done = vec![];
+
+    for line in unfold_lines(data) {
+
+        let prop = parse_property(&line)?;
+
+        match prop.name.as_str() {
+
+            "BEGIN" => stack.push(Component {
+                name: prop.value.to_uppercase(),
+                properties: vec![],
+                children: vec![]
+            }),
+
+            "END" => {
+                let component = stack.pop()
+                    .ok_or_else(|| anyhow!("unexpected END:{}", prop.value))?;
+                if component.name != prop.value.to_uppercase() {
+                    bail!("END:{} does not match BEGIN:{}", prop.value, component.name);
+                }
+                match (component.name.as_str(), stack.last_mut()) {
+                    ("VALARM", Some(parent)) => parent.children.push(component),
+                    _ => done.push(component)
+                }
+            },
+
+            _ => match stack.last_mut() {
+                Some(component) => component.properties.push(prop),
+                None => bail!("property {} outside of any component", prop.name)
+            }
+        }
+    }
+
+    if let Some(component) = stack.last() {
+        bail!("missing END:{}", component.name);
+    }
+
+    Ok(done)
+}
+
+fn unfold_lines(data: &str) -> Vec<String> {
+
+    let mut lines: Vec<String> = vec![];
+
+    for raw_line in data.lines() {
+        match (raw_line.strip_prefix(' ').or_else(|| raw_line.strip_prefix('\t')), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ if raw_line.trim().is_empty() => (),
+            _ => lines.push(raw_line.to_owned())
+        }
+    }
+
+    lines
+}
+
+fn parse_property(line: &str) -> anyhow::Result<Property> {
+
+    let mut in_quotes = false;
+    let colon_pos = line.char_indices()
+        .find(|(_i, c)| {
+            if *c == '"' { in_quotes = !in_quotes; }
+            *c == ':' && !in_quotes
+        })
+        .map(|(i, _c)| i)
+        .ok_or_else(|| anyhow!("invalid line \"{}\"", line))?;
+
+    let (head, value) = (&line[..colon_pos], &line[colon_pos + 1..]);
+
+    let mut head_parts = head.split(';');
+    let name = head_parts.next().unwrap_or_default().to_uppercase();
+    let params = head_parts
+        .filter_map(|param| param.split_once('='))
+        .map(|(n, v)| (n.to_uppercase(), v.trim_matches('"').to_uppercase()))
+        .collect();
+
+    Ok(Property { name, params, value: value.to_owned() })
+}
+
+/// The tags of an event as a CATEGORIES line, if it has any
+fn categories(event: &AgendaEvent) -> Option<String> {
+    match event.tags.is_empty() {
+        true => None,
+        false => {
+            let tags: Vec<String> = event.tags.iter().map(|tag| escape_text(tag)).collect();
+            Some(format!("CATEGORIES:{}", tags.join(",")))
+        }
+    }
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn unescape_text(text: &str) -> String {
+
+    let mut out = String::new();
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => ()
+        }
+    }
+
+    out
+}
+
+/// Splits a list value like CATEGORIES on the commas that are not escaped
+fn split_list(value: &str) -> Vec<&str> {
+
+    let mut parts = vec![];
+    let mut start = 0;
+    let mut escaped = false;
+
+    for (i, c) in value.char_indices() {
+        match (escaped, c) {
+            (false, '\\') => escaped = true,
+            (false, ',') => {
+                parts.push(&value[start..i]);
+                start = i + 1;
+            },
+            _ => escaped = false
+        }
+    }
+    parts.push(&value[start..]);
+
+    parts
+}
+
+/// Content lines are limited to 75 octets, continued on lines starting with a space
+fn fold_line(line: &str) -> String {
+
+    let mut out = String::new();
+    let mut len = 0;
+
+    for c in line.chars() {
+        if len + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            len = 1;
+        }
+        out.push(c);
+        len += c.len_utf8();
+    }
+
+    out.push_str("\r\n");
+    out
+}
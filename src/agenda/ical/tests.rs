@@ -0,0 +1,286 @@
+#![allow(clippy::zero_prefixed_literal)]
+
+use chrono::TimeZone;
+use super::super::{AgendaState, AgendaEvent, Pause};
+use super::super::cron::{Cronline, CronValue};
+use super::{export, import, feed};
+
+fn make_event(text: &str, line: [CronValue; 5]) -> AgendaEvent {
+    AgendaEvent::new(Cronline::from_values(line), text.to_owned())
+}
+
+#[test]
+fn export_rrules() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+
+    let mut state = AgendaState::new();
+
+    // Every day at 5pm
+    state.events.insert(0, make_event("exercise", [
+        CronValue::On(0), CronValue::On(17),
+        CronValue::Every, CronValue::Every, CronValue::Every
+    ]));
+
+    // Every day of September at 9am
+    state.events.insert(1, make_event("school, again", [
+        CronValue::On(0), CronValue::On(9),
+        CronValue::Every, CronValue::On(9), CronValue::Every
+    ]));
+
+    // Every hour in 2000
+    state.events.insert(2, make_event("hourly", [
+        CronValue::On(30), CronValue::Every,
+        CronValue::Every, CronValue::Every, CronValue::On(2000)
+    ]));
+
+    // Already past
+    state.events.insert(3, make_event("past", [
+        CronValue::On(0), CronValue::On(10),
+        CronValue::On(1), CronValue::On(1), CronValue::On(1999)
+    ]));
+
+    let res = export(&state, &now);
+
+    assert!(res.output.contains("DTSTART:20000101T170000\r\n"));
+    assert!(res.output.contains("RRULE:FREQ=DAILY\r\n"));
+    assert!(res.output.contains("SUMMARY:school\\, again\r\n"));
+    assert!(res.output.contains("DTSTART:20000901T090000\r\n"));
+    assert!(res.output.contains("RRULE:FREQ=DAILY;BYMONTH=9\r\n"));
+    assert!(res.output.contains("DTSTART:20000101T083000\r\n"));
+    assert!(res.output.contains("RRULE:FREQ=HOURLY;UNTIL=20001231T235959\r\n"));
+
+    assert_eq!(res.problems.len(), 1);
+    assert!(res.problems[0].contains("past"));
+}
+
+#[test]
+fn import_events() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+
+    let data = "\
+BEGIN:VCALENDAR\r
+VERSION:2.0\r
+BEGIN:VEVENT\r
+DTSTART:20000105T140000\r
+SUMMARY:dentist\r
+CATEGORIES:health,misc\r
+BEGIN:VALARM\r
+TRIGGER:-PT30M\r
+ACTION:DISPLAY\r
+END:VALARM\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+DTSTART;VALUE=DATE:19800801\r
+SUMMARY:Mark's birthday with a summary long enough to be folded over two \r
+ lines\r
+RRULE:FREQ=YEARLY\r
+END:VEVENT\r
+BEGIN:VTODO\r
+DUE:20000110T090000\r
+SUMMARY:unsupported\r
+RRULE:FREQ=WEEKLY;BYDAY=MO\r
+END:VTODO\r
+END:VCALENDAR\r
+";
+
+    let res = import(data, &now);
+
+    assert_eq!(res.output.len(), 2);
+
+    let dentist = &res.output[0];
+    assert_eq!(dentist.text, "dentist");
+    assert_eq!(dentist.tags.iter().collect::<Vec<_>>(), ["health", "misc"]);
+    assert_eq!(
+        dentist.cronline,
+        Cronline::from_time(&chrono::Local.ymd(2000, 01, 05).and_hms(13, 30, 00))
+    );
+
+    let birthday = &res.output[1];
+    assert_eq!(
+        birthday.text,
+        "Mark's birthday with a summary long enough to be folded over two lines"
+    );
+    assert_eq!(birthday.cronline, Cronline::from_values([
+        CronValue::On(0), CronValue::On(10),
+        CronValue::On(1), CronValue::On(8), CronValue::Every
+    ]));
+
+    assert_eq!(res.problems.len(), 1);
+    assert!(res.problems[0].contains("unsupported"));
+}
+
+#[test]
+fn import_problems() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+
+    let data = "\
+BEGIN:VCALENDAR\r
+BEGIN:VEVENT\r
+DTSTART:20000105T140000\r
+SUMMARY:huge alarm\r
+BEGIN:VALARM\r
+TRIGGER:-P9999999999999W\r
+END:VALARM\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+DTSTART:20000105T140000\r
+SUMMARY:far alarm\r
+BEGIN:VALARM\r
+TRIGGER:P99999999W\r
+END:VALARM\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+DTSTART;TZID=Europe/Paris:20000105T140000\r
+SUMMARY:zoned\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+DTSTART;TZID=UTC:20000105T140000\r
+SUMMARY:utc\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+
+    let res = import(data, &now);
+
+    assert_eq!(res.output.len(), 1);
+    assert_eq!(
+        res.output[0].cronline,
+        Cronline::from_time(&chrono::Utc.ymd(2000, 01, 05).and_hms(14, 00, 00).with_timezone(&chrono::Local))
+    );
+
+    assert_eq!(res.problems.len(), 3);
+    assert!(res.problems[0].contains("invalid duration"), "{:?}", res.problems);
+    assert!(res.problems[1].contains("alarm out of range"), "{:?}", res.problems);
+    assert!(res.problems[2].contains("EUROPE/PARIS is not supported"), "{:?}", res.problems);
+}
+
+#[test]
+fn export_paused() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+
+    let daily = [
+        CronValue::On(0), CronValue::On(9),
+        CronValue::Every, CronValue::Every, CronValue::Every
+    ];
+
+    let mut state = AgendaState::new();
+    state.events.insert(0, AgendaEvent {
+        pause: Some(Pause { until: None }),
+        ..make_event("paused", daily)
+    });
+    state.events.insert(1, AgendaEvent {
+        pause: Some(Pause { until: Some(chrono::Local.ymd(2000, 01, 10).and_hms(00, 00, 00)) }),
+        ..make_event("paused until", daily)
+    });
+
+    let res = export(&state, &now);
+
+    assert!(!res.output.contains("SUMMARY:paused\r\n"));
+    assert!(res.output.contains("DTSTART:20000110T090000\r\n"));
+    assert_eq!(res.problems.len(), 1);
+    assert!(res.problems[0].contains("paused until resumed"), "{:?}", res.problems);
+}
+
+#[test]
+fn roundtrip() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+
+    let mut state = AgendaState::new();
+    state.events.insert(0, make_event("monthly; with \\ special chars", [
+        CronValue::On(15), CronValue::On(8),
+        CronValue::On(3), CronValue::Every, CronValue::Every
+    ]));
+    state.events.insert(1, make_event("hourly", [
+        CronValue::On(30), CronValue::Every,
+        CronValue::Every, CronValue::Every, CronValue::On(2000)
+    ]));
+    state.events.get_mut(&1).unwrap().tags = ["a,b", "back\\slash", "work"].iter()
+        .map(|tag| tag.to_string())
+        .collect();
+
+    let exported = export(&state, &now);
+    let imported = import(&exported.output, &now);
+
+    assert!(imported.problems.is_empty());
+    assert_eq!(imported.output.len(), 2);
+    for (event, id) in imported.output.iter().zip([0, 1].iter()) {
+        assert_eq!(event.text, state.events[id].text);
+        assert_eq!(event.cronline, state.events[id].cronline);
+        assert_eq!(event.tags, state.events[id].tags);
+    }
+}
+
+#[test]
+fn bounds_roundtrip() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+    let every_day = [
+        CronValue::On(0), CronValue::On(8),
+        CronValue::Every, CronValue::Every, CronValue::Every
+    ];
+
+    let mut state = AgendaState::new();
+
+    let mut event = make_event("limited", every_day);
+    event.bounds.remaining = Some(5);
+    state.events.insert(0, event);
+
+    let mut event = make_event("ending", every_day);
+    event.bounds.end = Some(chrono::Local.ymd(2000, 03, 11).and_hms(00, 00, 00));
+    state.events.insert(1, event);
+
+    let mut event = make_event("starting", every_day);
+    event.bounds.start = Some(chrono::Local.ymd(2000, 02, 01).and_hms(00, 00, 00));
+    state.events.insert(2, event);
+
+    let exported = export(&state, &now);
+    assert!(exported.output.contains("RRULE:FREQ=DAILY;COUNT=5\r\n"));
+    assert!(exported.output.contains("DTSTART:20000201T080000\r\n"));
+    assert!(exported.output.contains("RRULE:FREQ=DAILY;UNTIL=20000310T235959\r\n"));
+
+    let imported = import(&exported.output, &now);
+    assert!(imported.problems.is_empty());
+    for (event, id) in imported.output.iter().zip([0, 1, 2].iter()) {
+        assert_eq!(event.bounds.end, state.events[id].bounds.end);
+        assert_eq!(event.bounds.remaining, state.events[id].bounds.remaining);
+        assert_eq!(event.get_next_occurence(&now), state.events[id].get_next_occurence(&now));
+    }
+
+    // A date UNTIL includes that whole day
+    let data = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nSUMMARY:daily\r\n\
+        DTSTART:20000101T090000\r\nRRULE:FREQ=DAILY;UNTIL=20000105\r\n\
+        END:VEVENT\r\nEND:VCALENDAR\r\n";
+    let imported = import(data, &now);
+    let occurences: Vec<_> = imported.output[0].upcoming_occurences(&now).collect();
+    assert_eq!(occurences.len(), 5);
+}
+
+#[test]
+fn feed_occurrences() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+
+    let mut state = AgendaState::new();
+
+    // Every month on the 3rd at 8:15
+    let mut event = make_event("monthly", [
+        CronValue::On(15), CronValue::On(8),
+        CronValue::On(3), CronValue::Every, CronValue::Every
+    ]);
+    event.tags.insert("work".to_owned());
+    state.events.insert(0, event);
+
+    let output = feed(&state, &now);
+
+    // Only the occurrences within the next 90 days
+    assert_eq!(output.matches("BEGIN:VEVENT").count(), 3);
+    assert!(output.contains("DTSTART:20000103T081500\r\n"));
+    assert!(output.contains("DTSTART:20000203T081500\r\n"));
+    assert!(output.contains("DTSTART:20000303T081500\r\n"));
+    assert_eq!(output.matches("CATEGORIES:work\r\n").count(), 3);
+}
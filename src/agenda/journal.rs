@@ -0,0 +1,56 @@
+use std::collections::VecDeque;
+use crate::storage::EventChange;
+
+const CAPACITY: usize = 20;
+
+/// Bounded record of the last mutating commands, each with the changes
+/// needed to revert it
+pub(super) struct Journal {
+    entries: VecDeque<JournalEntry>,
+    next_seq: u64
+}
+
+pub(super) struct JournalEntry {
+    pub(super) seq: u64,
+    pub(super) description: String,
+    pub(super) undo_changes: Vec<EventChange>,
+    /// The scheduler changed one of its events since, reverting them would
+    /// also revert that (an occurrence counted, a pause over)
+    pub(super) stale: bool
+}
+
+impl Journal {
+
+    pub(super) fn new() -> Self {
+        Journal { entries: VecDeque::new(), next_seq: 0 }
+    }
+
+    /// Returns the sequence number of the new entry
+    pub(super) fn push(&mut self, description: String, undo_changes: Vec<EventChange>) -> u64 {
+
+        let seq = self.next_seq;
+        self.next_seq += 1;
+
+        if self.entries.len() == CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(JournalEntry { seq, description, undo_changes, stale: false });
+
+        seq
+    }
+
+    pub(super) fn last_seq(&self) -> Option<u64> {
+        self.entries.back().map(|entry| entry.seq)
+    }
+
+    pub(super) fn pop(&mut self) -> Option<JournalEntry> {
+        self.entries.pop_back()
+    }
+
+    /// Marks the entries touching an event as stale, when the scheduler changes it
+    pub(super) fn invalidate(&mut self, id: u64) {
+        self.entries.iter_mut()
+            .filter(|entry| entry.undo_changes.iter().any(|change| change.id() == id))
+            .for_each(|entry| entry.stale = true);
+    }
+}
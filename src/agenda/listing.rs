@@ -0,0 +1,213 @@
+use std::collections::BTreeMap;
+use chrono::Datelike;
+use crate::Opts;
+use crate::locale::{Locale, Msg, Vocabulary, fill};
+use super::{AgendaEvent, AgendaState, Instant, normalize_tag, sanitize};
+
+/// Most characters on a page, leaving room under Telegram's limit of 4096
+/// for the page indicator
+const PAGE_CHARS: usize = 3500;
+
+/// Order of the events in `/events`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum EventOrder {
+    /// Soonest next occurrence first
+    Next,
+    Id,
+    /// Grouped by tag, then by next occurrence
+    Tag
+}
+
+impl EventOrder {
+
+    /// The English words work in every language, the buttons use them
+    pub(super) fn from_word(vocab: &Vocabulary, word: &str) -> Option<Self> {
+        const ORDERS: [EventOrder; 3] = [EventOrder::Next, EventOrder::Id, EventOrder::Tag];
+        [vocab, Locale::En.vocabulary()].iter()
+            .find_map(|vocab| vocab.event_orders.iter().position(|words| words.contains(&word)))
+            .map(|i| ORDERS[i])
+    }
+
+    pub(super) fn word(&self) -> &'static str {
+        match self {
+            Self::Next => "next",
+            Self::Id => "id",
+            Self::Tag => "tag"
+        }
+    }
+}
+
+/// Tags an event must have, and must not have, as in "/events work -home"
+pub(super) struct TagFilter {
+    with: Vec<String>,
+    without: Vec<String>
+}
+
+impl TagFilter {
+
+    pub(super) fn from_words(words: &[&str]) -> Self {
+        let (without, with): (Vec<&str>, Vec<&str>) = words.iter()
+            .partition(|w| w.starts_with('-'));
+        TagFilter {
+            with: with.iter().map(|w| normalize_tag(w)).collect(),
+            without: without.iter().map(|w| normalize_tag(&w[1..])).collect()
+        }
+    }
+
+    pub(super) fn matches(&self, event: &AgendaEvent) -> bool {
+        self.with.iter().all(|tag| event.tags.contains(tag))
+            && !self.without.iter().any(|tag| event.tags.contains(tag))
+    }
+}
+
+impl std::fmt::Display for TagFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let words: Vec<String> = self.with.iter().map(|tag| format!("#{}", tag))
+            .chain(self.without.iter().map(|tag| format!("-#{}", tag)))
+            .collect();
+        write!(f, "{}", words.join(" "))
+    }
+}
+
+/// Number of events with each tag, in alphabetical order
+pub(super) fn tag_counts(state: &AgendaState) -> BTreeMap<&str, usize> {
+    let mut counts = BTreeMap::new();
+    for tag in state.events.values().flat_map(|event| event.tags.iter()) {
+        *counts.entry(tag.as_str()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Lines of the `/events` listing: the tags with their number of events,
+/// then the untagged events, or every event under each of its tags when
+/// sorted by tag
+pub(super) fn events_lines(
+    opts: &Opts, state: &AgendaState, now: &Instant, order: EventOrder
+) -> Vec<String> {
+
+    let locale = opts.locale;
+
+    let mut lines = vec![];
+
+    if order == EventOrder::Tag {
+        for tag in tag_counts(state).keys() {
+            let events = state.events.iter()
+                .filter(|(_id, event)| event.tags.contains(*tag))
+                .collect();
+            lines.push(format!("<b>{}</b> <code>{}</code>:", locale.tr(Msg::TagHeader), sanitize(tag)));
+            lines.extend(event_lines(opts, now, events, order));
+        }
+    } else {
+        lines.extend(tag_counts(state).iter().map(|(tag, count)| format!(
+            "<b>{}</b> <code>{}</code>: {}",
+            locale.tr(Msg::TagHeader), sanitize(tag), fill(locale.tr(Msg::TagCount), &[count])
+        )));
+    }
+
+    let untagged: Vec<(&u64, &AgendaEvent)> = state.events.iter()
+        .filter(|(_id, event)| event.tags.is_empty())
+        .collect();
+
+    if !untagged.is_empty() {
+        lines.push(format!("\n<b>{}</b>", locale.tr(Msg::UntaggedEvents)));
+        lines.extend(event_lines(opts, now, untagged, order));
+    }
+
+    lines
+}
+
+/// Lines of the `/events <tags>` listing
+pub(super) fn tagged_events_lines(
+    opts: &Opts, state: &AgendaState, now: &Instant, filter: &TagFilter, order: EventOrder
+) -> Vec<String> {
+
+    let events: Vec<(&u64, &AgendaEvent)> = state.events.iter()
+        .filter(|(_id, event)| filter.matches(event))
+        .collect();
+
+    if events.is_empty() {
+        return vec![];
+    }
+
+    let header = format!(
+        "<b>{}</b> <code>{}</code>:",
+        opts.locale.tr(Msg::TagHeader), sanitize(&filter.to_string())
+    );
+
+    [vec![header], event_lines(opts, now, events, order)].concat()
+}
+
+/// One line per event, sorted
+fn event_lines(
+    opts: &Opts, now: &Instant, mut events: Vec<(&u64, &AgendaEvent)>, order: EventOrder
+) -> Vec<String> {
+
+    match order {
+        EventOrder::Id => events.sort_by_key(|(id, _event)| **id),
+        // Events that never occur again would be removed already, but they go last anyway
+        EventOrder::Next | EventOrder::Tag => events.sort_by_key(|(id, event)| {
+            let next = event.get_next_active_occurence(now);
+            (next.is_none(), next, **id)
+        })
+    }
+
+    events.iter()
+        .map(|(id, event)| format!(
+            "<pre>  {} - [{}] {}{}{}</pre>",
+            event.cronline.describe(opts),
+            id,
+            sanitize(&event.text),
+            sanitize(&format_tags(event)),
+            format_pause(opts, event, now)
+        ))
+        .collect()
+}
+
+/// Mark after a paused event, empty if it is not paused
+pub(super) fn format_pause(opts: &Opts, event: &AgendaEvent, now: &Instant) -> String {
+
+    let locale = opts.locale;
+
+    match &event.pause {
+        Some(pause) if pause.covers(now) => match pause.until {
+            None => format!(" ⏸ {}", locale.tr(Msg::Paused)),
+            Some(until) => format!(" ⏸ {}", fill(locale.tr(Msg::PausedUntil), &[&written_date(opts, &until)]))
+        },
+        _ => String::new()
+    }
+}
+
+/// Date of an instant in words, as in "10 March 2000"
+pub(super) fn written_date(opts: &Opts, t: &Instant) -> String {
+    opts.locale.written_date(t.day().into(), t.month().into(), Some(t.year() as u64), &opts.date_format)
+}
+
+/// Tags of an event to put after its text, as " #work #urgent"
+pub(super) fn format_tags(event: &AgendaEvent) -> String {
+    event.tags.iter().map(|tag| format!(" #{}", tag)).collect()
+}
+
+/// Splits lines into pages short enough to be sent as one message each
+pub(super) fn paginate(lines: Vec<String>) -> Vec<String> {
+
+    let mut pages = vec![];
+    let mut page: Vec<String> = vec![];
+    let mut page_chars = 0;
+
+    for line in lines {
+        let line_chars = line.chars().count() + 1;
+        if !page.is_empty() && page_chars + line_chars > PAGE_CHARS {
+            pages.push(page.join("\n"));
+            page.clear();
+            page_chars = 0;
+        }
+        page_chars += line_chars;
+        page.push(line);
+    }
+
+    if !page.is_empty() {
+        pages.push(page.join("\n"));
+    }
+
+    pages
+}
@@ -1,114 +1,213 @@
-use std::collections::HashMap;
-use std::path::{Path, PathBuf};
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
 use std::sync::{Arc, Mutex};
-use crossbeam_channel::Sender;
-use std::time::Duration;
+use crossbeam_channel::{Receiver, Sender, unbounded};
 use serde::{Deserialize, Serialize};
 use anyhow::{anyhow, Context, bail};
-use chrono::Timelike;
-use log::{debug, info, warn};
+use chrono::TimeZone;
+use log::{debug, info, warn, error};
 
-use crate::{Opts, BotUpdate, format_error};
+use crate::{Opts, DateFormat, BotUpdate, Button, format_error};
+use crate::storage::{SharedStorage, Storage, EventChange};
+use crate::locale::{Locale, Msg, LOCALES, fill};
 
 mod cron;
 mod time_parsing;
 mod event;
-
-use time_parsing::{parse_cronline, CronlineResult};
-use event::AgendaEvent;
+mod history;
+mod journal;
+mod ical;
+mod preview;
+mod confirmation;
+mod button_values;
+mod scheduler;
+pub(crate) mod clock;
+mod upcoming;
+mod listing;
+mod search;
+mod selector;
+#[cfg(test)]
+mod tests;
+
+use time_parsing::{parse_cronline, parse_until_date, CronlineResult};
+pub(crate) use event::{AgendaEvent, Bounds, Pause, normalize_tag};
+pub(crate) use history::HistoryEntry;
+use journal::Journal;
+use confirmation::{Confirmations, EventTarget};
+use button_values::ButtonValues;
+use scheduler::Scheduler;
+use upcoming::AgendaPeriod;
+use listing::{EventOrder, TagFilter};
+use selector::{Selector, Selection};
+pub(crate) use clock::{SharedClock, SystemClock};
+pub use history::Reminder;
+pub(crate) use preview::explain_time;
+pub(crate) use cron::CronColumn;
 
 pub(super) struct Agenda {
     state: Arc<Mutex<AgendaState>>,
     sender: Sender<BotUpdate>,
-    state_path: PathBuf,
+    storage: SharedStorage,
+    clock: SharedClock,
+    /// Wakes the scheduler up when the events change
+    wake: (Sender<()>, Receiver<()>),
+    journal: Arc<Mutex<Journal>>,
+    confirmations: Mutex<Confirmations>,
+    button_values: Mutex<ButtonValues>,
+    /// Languages picked with /language, by chat
+    chat_locales: HashMap<u32, Locale>,
+    /// Chat the bot is talking to, where replies and reminders go
+    chat_id: Option<u32>,
     opts: Opts
 }
 
-type Instant = chrono::DateTime<chrono::offset::Local>;
+/// Handle on the agenda for the HTTP calendar feed
+#[derive(Clone)]
+pub struct CalendarFeed {
+    state: Arc<Mutex<AgendaState>>,
+    clock: SharedClock
+}
 
-impl Agenda {
+impl CalendarFeed {
+    pub fn render(&self) -> String {
+        let state = self.state.lock().unwrap();
+        ical::feed(&state, &self.clock.now())
+    }
+}
 
-    pub(super) fn new(opts: &Opts, sender: &Sender<BotUpdate>) -> Self {
+/// Answer to a user command, with optional rows of inline buttons
+struct Reply {
+    text: String,
+    buttons: Vec<Vec<Button>>
+}
+
+impl Reply {
 
-        let state_path = opts.data_path.join("agenda.json");
-        debug!("Agenda state path: {}", state_path.to_string_lossy());
+    /// Adds a button at the end of the last row
+    fn with_button(mut self, label: &str, data: String) -> Self {
+        if self.buttons.is_empty() {
+            self.buttons.push(vec![]);
+        }
+        self.buttons.last_mut().unwrap().push((label.to_owned(), data));
+        self
+    }
+
+    fn with_button_row(mut self, row: Vec<Button>) -> Self {
+        self.buttons.push(row);
+        self
+    }
+}
 
-        let state = AgendaState::restore(&state_path)
+impl From<String> for Reply {
+    fn from(text: String) -> Self {
+        Reply { text, buttons: vec![] }
+    }
+}
+
+impl From<&str> for Reply {
+    fn from(text: &str) -> Self {
+        text.to_owned().into()
+    }
+}
+
+/// Outcome of parsing a new event. Several candidates mean the time was
+/// ambiguous, the first one being the reading from the configured date format.
+struct ParsedEvent {
+    candidates: Vec<AgendaEvent>,
+    /// What was filled in by default, if anything
+    comment: Option<String>
+}
+
+pub(crate) type Instant = chrono::DateTime<chrono::offset::Local>;
+
+impl Agenda {
+
+    pub(super) fn new(
+        opts: &Opts, sender: &Sender<BotUpdate>, storage: &SharedStorage, clock: &SharedClock
+    ) -> Self {
+
+        let state = storage.load_agenda()
             .unwrap_or_else(|err| {
                 warn!("No agenda state restored: {}", err);
                 info!("Creating new empty agenda");
                 AgendaState::new()
             });
         let state = Arc::new(Mutex::new(state));
+
+        let chat_locales = storage.load_chat_locales()
+            .unwrap_or_else(|err| {
+                warn!("No chat languages restored: {}", err);
+                HashMap::new()
+            });
+        let chat_id = storage.load_telegram_context().ok()
+            .and_then(|context| context.chat_id);
     
         Agenda { 
             state,
             sender: sender.clone(),
-            state_path,
+            storage: storage.clone(),
+            clock: clock.clone(),
+            wake: unbounded(),
+            journal: Arc::new(Mutex::new(Journal::new())),
+            confirmations: Mutex::new(Confirmations::new()),
+            button_values: Mutex::new(ButtonValues::new()),
+            chat_locales,
+            chat_id,
             opts: opts.clone()
         }
     }
 
+    /// Language of the current chat
+    pub(super) fn locale(&self) -> Locale {
+        self.chat_id
+            .and_then(|chat_id| self.chat_locales.get(&chat_id).copied())
+            .unwrap_or(self.opts.locale)
+    }
+
+    fn tr(&self, msg: Msg) -> &'static str {
+        self.locale().tr(msg)
+    }
+
+    pub(super) fn event_over_notification(&self, text: &str) -> String {
+        fill(self.tr(Msg::EventOver), &[&text])
+    }
+
+    /// Options with the language of the current chat
+    pub(super) fn chat_opts(&self) -> Opts {
+        let mut opts = self.opts.clone();
+        opts.locale = self.locale();
+        opts
+    }
+
+    fn error_reply(&self, err: anyhow::Error) -> Reply {
+        fill(self.tr(Msg::Error), &[&format_error(err)]).into()
+    }
+
+    pub(super) fn calendar_feed(&self) -> CalendarFeed {
+        CalendarFeed { state: self.state.clone(), clock: self.clock.clone() }
+    }
+
     pub(super) fn get_loop(&self) -> impl FnOnce() {
+        let scheduler = self.scheduler();
+        move || scheduler.run()
+    }
 
-        const INTERVAL: Duration = Duration::from_millis(500);
-
-        let state = self.state.clone();
-        let state_path = self.state_path.clone();
-        let sender = self.sender.clone();
-
-        move || {
-
-            let mut prev_t = chrono::Local::now();
-
-            info!("Starting agenda event loop");
-        
-            loop {
-        
-                let curr_t = chrono::Local::now();
-        
-                if curr_t.minute() != prev_t.minute() {
-        
-                    let mut state = state.lock().unwrap();
-        
-                    let keys_list: Vec<u64> = state.events
-                        .keys()
-                        .cloned()
-                        .collect();
-        
-                    let mut state_changed = false;
-                    for id in keys_list {
-                        let event = &state.events[&id];
-                        if event.check_fires(&curr_t) {
-
-                            info!("It's {}, firing event {}", curr_t, id);
-        
-                            let notification = format!("⏰ {}", event.text);
-                            sender.send(BotUpdate::MsgOut(notification)).unwrap();
-        
-                            if event.get_next_occurence(&curr_t).is_none() {
-                                info!("Event {} never occurs again, removing", id);
-                                state.events.remove(&id);
-                                state_changed = true;
-                            }
-                        }
-                    }
-        
-                    if state_changed {
-                        state.save(&state_path);
-                    }
-        
-                    prev_t = curr_t;
-                }
-        
-                std::thread::sleep(INTERVAL);
-            }
-        }
+    fn scheduler(&self) -> Scheduler {
+        Scheduler::new(
+            self.state.clone(),
+            self.storage.clone(),
+            self.journal.clone(),
+            self.sender.clone(),
+            self.clock.clone(),
+            self.wake.1.clone()
+        )
     }
 
-    pub(super) fn process(&mut self, msg: &str) {
+    pub(super) fn process(&mut self, chat_id: u32, msg: &str) {
+
+        self.chat_id = Some(chat_id);
 
-        let msg = msg.to_ascii_lowercase();
+        let msg = msg.to_lowercase();
         let words: Vec<&str> = msg.split_whitespace().collect();
 
         debug!("words {:?}", words);
@@ -122,325 +221,1147 @@ impl Agenda {
             }
         }();
 
-        let msg = match command_res {
+        let reply = match command_res {
 
             Some((w, rem_words)) => match (w, rem_words) {
                 ("/help", _)     => self.print_help(),
-                ("/events", [])   => self.print_events(),
-                ("/events", args) => self.print_tagged_events(args),
+                ("/events", args) => self.print_events(args),
+                ("/next", args)  => self.print_next(args),
+                ("/agenda", args) => self.print_agenda(args),
+                ("/find", args)  => self.find_events(args),
                 ("/del", args)   => self.remove_events(args),
+                ("/edit", args)  => self.edit_event(args),
                 ("/tag", args)   => self.tag_event(args),
                 ("/untag", args) => self.untag_event(args),
-                _                => Ok("Unknown command".into())
+                ("/pause", args) => self.pause_events(args),
+                ("/resume", args) => self.resume_events(args),
+                ("/tags", [])    => self.print_tags(),
+                ("/renametag", args) => self.rename_tag(args),
+                ("/deltag", args) => self.delete_tag(args),
+                ("/undo", [])    => self.undo(None),
+                ("/history", args) => self.print_history(args),
+                ("/export", [])  => self.export_calendar(),
+                ("/when", args)  => self.explain_time(args),
+                ("/language", args) => self.set_language(args),
+                _                => Ok(self.tr(Msg::UnknownCommand).into())
             },
 
             None => self.add_event(&words)
         }
-        .unwrap_or_else(
-            |err| format!("Error: {}", format_error(err)));
+        .unwrap_or_else(|err| self.error_reply(err));
 
-        self.sender.send(BotUpdate::MsgOut(msg)).unwrap();
+        self.send_reply(reply);
     }
 
-    fn add_event(&self, words: &[&str]) -> anyhow::Result<String> {
+    pub(super) fn process_callback(&mut self, chat_id: u32, data: &str) {
+
+        self.chat_id = Some(chat_id);
+
+        let reply = match data.split(':').collect::<Vec<&str>>().as_slice() {
+            ["ack", event_id, timestamp] => self.acknowledge(event_id, timestamp),
+            ["confirm", seq, choice] => self.confirm_event(seq, Some(choice)),
+            ["cancel", seq] => self.confirm_event(seq, None),
+            ["deltag", action, tag @ ..] => self.apply_delete_tag(action, &tag.join(":")),
+            ["del", event_id] => self.remove_events(&[event_id]),
+            ["edit", event_id] => self.edit_hint(event_id),
+            ["events", order, page, tag @ ..] => self.events_button(order, page, &tag.join(":")),
+            ["undo", seq] => seq.parse()
+                .context("invalid undo button")
+                .and_then(|seq| self.undo(Some(seq))),
+            _ => Err(anyhow!("unknown button"))
+        }
+        .unwrap_or_else(|err| self.error_reply(err));
 
-        info!("Adding new event");
+        self.send_reply(reply);
+    }
+
+    fn send_reply(&self, reply: Reply) {
+        let update = match reply.buttons.is_empty() {
+            true => BotUpdate::MsgOut(reply.text),
+            false => BotUpdate::MsgOutButtons(reply.text, reply.buttons)
+        };
+        self.sender.send(update).unwrap();
+    }
 
-        let now = chrono::Local::now();
+    pub(super) fn record_delivery(&self, reminder: &Reminder, res: anyhow::Result<()>) {
+
+        let error = res.err().map(format_error);
+        if let Some(err) = &error {
+            error!("Could not deliver reminder for event {}: {}", reminder.event_id, err);
+        }
+
+        let entry = HistoryEntry {
+            event_id: reminder.event_id,
+            text: reminder.text.clone(),
+            tags: reminder.tags.clone(),
+            scheduled: reminder.scheduled,
+            sent: self.clock.now(),
+            error,
+            acknowledged: None
+        };
+
+        self.storage.append_history(&entry)
+            .unwrap_or_else(|err| error!(
+                "Cannot record history entry: {}",
+                format_error(err)));
+    }
+
+    fn acknowledge(&self, event_id: &str, timestamp: &str) -> anyhow::Result<Reply> {
+
+        let event_id: u64 = event_id.parse().context("invalid event number")?;
+        let timestamp: i64 = timestamp.parse().context("invalid timestamp")?;
+        let scheduled = chrono::Local.timestamp(timestamp, 0);
+
+        info!("Acknowledging event {} scheduled at {}", event_id, scheduled);
+
+        self.storage.acknowledge_history(event_id, &scheduled, &self.clock.now())?;
+
+        Ok(self.tr(Msg::MarkedAsDone).into())
+    }
+
+    fn events_button(&self, order: &str, page: &str, filter: &str) -> anyhow::Result<Reply> {
+
+        let order = EventOrder::from_word(self.locale().vocabulary(), order)
+            .ok_or_else(|| anyhow!("invalid events button"))?;
+        let page: usize = page.parse().context("invalid events button")?;
+        let filter = self.button_values.lock().unwrap().resolve(filter)
+            .ok_or_else(|| anyhow!(self.tr(Msg::ButtonExpired)))?;
+        let filter = Some(filter.as_str()).filter(|filter| !filter.is_empty());
+
+        self.events_page(filter, order, page)
+    }
+
+    /// Applies and persists the changes made by a user command, keeping
+    /// what's needed to undo them. Returns the journal sequence number.
+    fn commit(
+        &self, state: &mut AgendaState, description: String, changes: Vec<EventChange>
+    ) -> u64 {
+        save_changes(self.storage.as_ref(), &changes);
+        let undo_changes = state.apply_reversible(&changes);
+        self.reschedule();
+        self.journal.lock().unwrap().push(description, undo_changes)
+    }
+
+    fn reschedule(&self) {
+        // The agenda holds a receiver too, so this cannot fail
+        self.wake.0.send(()).unwrap();
+    }
+
+    /// Reverts the last mutating command. When coming from an inline button,
+    /// `seq` must match it so that an old button cannot undo something else.
+    fn undo(&self, seq: Option<u64>) -> anyhow::Result<Reply> {
+
+        // Same locking order as when committing and firing
+        let mut state = self.state.lock().unwrap();
+        let mut journal = self.journal.lock().unwrap();
+
+        match (seq, journal.last_seq()) {
+            (_, None) => bail!(self.tr(Msg::NothingToUndo)),
+            (Some(seq), Some(last_seq)) if seq != last_seq =>
+                bail!(self.tr(Msg::NotLastChange)),
+            _ => ()
+        }
+
+        let entry = journal.pop().unwrap();
+
+        // Dropped, so that the command before it can be undone next
+        if entry.stale {
+            info!("Not undoing stale change: {}", entry.description);
+            bail!(fill(self.tr(Msg::UndoStale), &[&entry.description]));
+        }
+
+        info!("Undoing: {}", entry.description);
+
+        save_changes(self.storage.as_ref(), &entry.undo_changes);
+        state.apply(&entry.undo_changes);
+        self.reschedule();
+
+        Ok(fill(self.tr(Msg::Undone), &[&entry.description]).into())
+    }
+
+    /// Parses a "<time> <message>" specification into a new event. Dates
+    /// written in digits which also make sense in the other date format
+    /// yield a second candidate.
+    fn parse_event(&self, words: &[&str]) -> anyhow::Result<ParsedEvent> {
+
+        let now = self.clock.now();
+        let opts = self.chat_opts();
 
         debug!("Time now is {}", now);
 
+        // Hashtags can be anywhere, and are not part of the message
+        let (hashtags, words): (Vec<&str>, Vec<&str>) = words.iter()
+            .partition(|w| w.len() > 1 && w.starts_with('#'));
+        let tags = parse_tags(&hashtags);
+        let words = words.as_slice();
+
         let CronlineResult {
             cronline,
             remaining_words,
-            comment
-        } = parse_cronline(&self.opts, &now, words)
-            .context("cannot parse time")?;
+            comment,
+            steps,
+            bounds
+        } = parse_cronline(&opts, &now, words)
+            .context(self.tr(Msg::CannotParseTime))?;
 
         debug!("Parsed cronline {:?}", cronline);
         debug!("Remaining words {:?}", remaining_words);
 
         if remaining_words.is_empty() {
-            bail!("no message specified")
+            bail!(self.tr(Msg::NoMessage))
         }
 
-        let mut state = self.state.lock().unwrap();
+        let make_event = |cronline, bounds| AgendaEvent {
+            bounds,
+            tags: tags.clone(),
+            ..AgendaEvent::new(cronline, remaining_words.join(" "))
+        };
 
-        let new_id = (0..).find(|id| !state.events.contains_key(&id)).unwrap();
+        let mut candidates = vec![make_event(cronline.clone(), bounds)];
 
-        debug!("New event ID {}", new_id);
+        if steps.iter().any(|step| step.parser == "date digits") {
 
-        let agenda_event = AgendaEvent {
-            text: remaining_words.join(" "),
-            cronline,
-            tag: None
+            let mut swapped_opts = opts.clone();
+            swapped_opts.date_format = match opts.date_format {
+                DateFormat::DMY => DateFormat::MDY,
+                DateFormat::MDY => DateFormat::DMY
+            };
+
+            let alternative = parse_cronline(&swapped_opts, &now, words).ok()
+                .filter(|res| res.cronline != cronline)
+                .map(|res| make_event(res.cronline, res.bounds))
+                .filter(|event| event.get_next_occurence(&now).is_some());
+
+            if let Some(event) = alternative {
+                debug!("Ambiguous date, alternative cronline {:?}", event.cronline);
+                candidates.push(event);
+            }
+        }
+
+        Ok(ParsedEvent { candidates, comment })
+    }
+
+    fn add_event(&self, words: &[&str]) -> anyhow::Result<Reply> {
+
+        info!("Adding new event");
+
+        let parsed = self.parse_event(words)?;
+
+        self.save_or_confirm(EventTarget::New, parsed)
+    }
+
+    fn edit_event(&self, words: &[&str]) -> anyhow::Result<Reply> {
+
+        let (selection_word, event_words) = match words {
+            []                                 => Err(anyhow!(self.tr(Msg::NoArguments))),
+            [_]                                => Err(anyhow!(self.tr(Msg::NoTimeAndMessage))),
+            [selection_word, event_words @ ..] => Ok((selection_word, event_words))
+        }?;
+
+        let selection = self.select_events(&self.state.lock().unwrap(), &[selection_word])?;
+
+        let id = match (selection.ids.as_slice(), selection.missing.as_slice()) {
+            ([id], []) => *id,
+            ([], _) => bail!(self.tr(Msg::NoEventAtNumber)),
+            _ => bail!(self.tr(Msg::SingleEventOnly))
+        };
+
+        info!("Editing event {}", id);
+
+        let parsed = self.parse_event(event_words)?;
+
+        self.save_or_confirm(EventTarget::Existing(id), parsed)
+    }
+
+    /// Saves the event right away if its time was unambiguous, otherwise
+    /// asks the user to pick one of the candidates first
+    fn save_or_confirm(&self, target: EventTarget, parsed: ParsedEvent) -> anyhow::Result<Reply> {
+
+        let ParsedEvent { mut candidates, comment } = parsed;
+
+        if candidates.len() == 1 && comment.is_none() {
+            return self.save_event(target, candidates.remove(0));
+        }
+
+        let now = self.clock.now();
+        candidates.retain(|event| event.get_next_occurence(&now).is_some());
+        if candidates.is_empty() {
+            bail!(self.tr(Msg::NeverOccurs));
+        }
+
+        let date_fmt = match self.opts.date_format {
+            DateFormat::DMY => "%a %d/%m/%Y %H:%M",
+            DateFormat::MDY => "%a %m/%d/%Y %H:%M"
+        };
+
+        let next_times: Vec<String> = candidates.iter()
+            .map(|event| event.get_next_occurence(&now).unwrap().format(date_fmt).to_string())
+            .collect();
+
+        let mut text = vec![];
+        if let Some(comment) = comment {
+            text.push(comment);
+        }
+        if candidates.len() > 1 {
+            text.push(self.tr(Msg::AmbiguousDate).to_owned());
+        }
+        text.push(fill(self.tr(Msg::SaveFor), &[&sanitize(&candidates[0].text)]));
+        text.extend(candidates.iter().zip(&next_times).map(|(event, next_t)| format!(
+            "  {}", fill(self.tr(Msg::NextOn), &[&event.cronline.describe(&self.chat_opts()), next_t])
+        )));
+
+        let seq = self.confirmations.lock().unwrap().push(target, candidates);
+
+        let reply = next_times.into_iter()
+            .enumerate()
+            .fold(Reply::from(text.join("\n")), |reply, (i, label)| {
+                reply.with_button(&format!("✅ {}", label), format!("confirm:{}:{}", seq, i))
+            })
+            .with_button(self.tr(Msg::CancelButton), format!("cancel:{}", seq));
+
+        Ok(reply)
+    }
+
+    fn confirm_event(&self, seq: &str, choice: Option<&str>) -> anyhow::Result<Reply> {
+
+        let seq: u64 = seq.parse().context("invalid confirmation button")?;
+
+        let pending = self.confirmations.lock().unwrap().take(seq)
+            .ok_or_else(|| anyhow!(self.tr(Msg::ConfirmationExpired)))?;
+
+        let choice: usize = match choice {
+            None => return Ok(self.tr(Msg::Cancelled).into()),
+            Some(choice) => choice.parse().context("invalid confirmation button")?
         };
 
-        let now = chrono::Local::now();
+        info!("Confirming candidate {} of pending event {}", choice, seq);
+
+        let event = pending.candidates.into_iter().nth(choice)
+            .ok_or(anyhow!("invalid confirmation button"))?;
+
+        self.save_event(pending.target, event)
+    }
+
+    fn save_event(&self, target: EventTarget, mut agenda_event: AgendaEvent) -> anyhow::Result<Reply> {
+
+        let mut state = self.state.lock().unwrap();
+
+        let now = self.clock.now();
         let occ_t = agenda_event.get_next_occurence(&now)
-            .ok_or(anyhow!("Invalid time: never occurs"))?;
+            .ok_or_else(|| anyhow!(self.tr(Msg::NeverOccurs)))?;
 
         debug!("Event occurs at {}", occ_t);
 
-        state.events.insert(new_id, agenda_event);
-        state.save(&self.state_path);
+        let occ_text = format_time_diff(self.locale(), occ_t - now);
+
+        match target {
+
+            EventTarget::New => {
 
-        let occ_text = format_time_diff(occ_t - now);
+                let new_id = state.free_ids().next().unwrap();
 
-        let text = {
-            let mut text = String::new();
-            if let Some(comment) = comment {
-                text = format!("{}\n", comment);
+                debug!("New event ID {}", new_id);
+
+                let recurrence = agenda_event.cronline.describe(&self.chat_opts());
+                let description = fill(self.tr(Msg::DescAdd), &[&agenda_event.text]);
+                let changes = vec![EventChange::Upsert(new_id, Box::new(agenda_event))];
+                self.commit(&mut state, description, changes);
+
+                Ok(fill(self.tr(Msg::EventAdded), &[&new_id, &recurrence, &occ_text]).into())
+            },
+
+            EventTarget::Existing(id) => {
+
+                let old_event = state.events.get(&id)
+                    .ok_or_else(|| anyhow!(self.tr(Msg::NoEventAtNumber)))?;
+
+                // Hashtags in the new message come on top of the tags it had
+                agenda_event.tags.extend(old_event.tags.iter().cloned());
+                agenda_event.pause = old_event.pause.clone();
+
+                let recurrence = agenda_event.cronline.describe(&self.chat_opts());
+
+                let description = fill(self.tr(Msg::DescEdit), &[&old_event.text]);
+                let changes = vec![EventChange::Upsert(id, Box::new(agenda_event))];
+                self.commit(&mut state, description, changes);
+
+                Ok(fill(self.tr(Msg::EventEdited), &[&id, &recurrence, &occ_text]).into())
             }
-            format!(
-                "{}New event added (number {}).\nNext occurence in {}.",
-                text, new_id, occ_text
-            )
-        };
+        }
+    }
+
+    fn explain_time(&self, words: &[&str]) -> anyhow::Result<Reply> {
 
+        info!("Explaining time expression");
 
-        Ok(text)
+        let text = explain_time(&self.chat_opts(), &self.clock.now(), &words.join(" "))?;
+
+        Ok(format!("<pre>{}</pre>", sanitize(&text)).into())
     }
 
-    fn remove_events(&self, words: &[&str]) -> anyhow::Result<String> {
+    fn export_calendar(&self) -> anyhow::Result<Reply> {
 
-        if words.is_empty() {
-            bail!("No event number supplied");
+        info!("Exporting events to iCalendar");
+
+        let state = self.state.lock().unwrap();
+        let ical::Conversion { output, problems } = ical::export(&state, &self.clock.now());
+
+        let nb_exported = state.events.len() - problems.len();
+        self.sender.send(BotUpdate::DocumentOut(
+            "nag.ics".to_owned(),
+            output.into_bytes(),
+            fill(self.tr(Msg::EventsExported), &[&nb_exported])
+        )).unwrap();
+
+        match problems.is_empty() {
+            true => Ok(self.tr(Msg::CalendarSent).into()),
+            false => Ok(conversion_report(self.tr(Msg::ExportProblems), &problems).into())
+        }
+    }
+
+    pub(super) fn process_document(&mut self, chat_id: u32, filename: &str, data: &[u8]) {
+
+        self.chat_id = Some(chat_id);
+
+        let reply = self.import_calendar(filename, data)
+            .unwrap_or_else(|err| self.error_reply(err));
+
+        self.send_reply(reply);
+    }
+
+    fn import_calendar(&self, filename: &str, data: &[u8]) -> anyhow::Result<Reply> {
+
+        if !filename.to_lowercase().ends_with(".ics") {
+            bail!(self.tr(Msg::OnlyIcsFiles));
         }
 
-        let event_ids = words.iter()
-            .map(|w| w.parse::<u64>().context("Invalid event number"))
-            .collect::<anyhow::Result<Vec<u64>>>()?;
+        info!("Importing events from {}", filename);
 
-        info!("Removing events {:?}", event_ids);
+        let data = std::str::from_utf8(data).context("file is not valid UTF-8")?;
+        let ical::Conversion { output, problems } = ical::import(data, &self.clock.now());
 
         let mut state = self.state.lock().unwrap();
 
-        let out_lines = event_ids.iter().map(
-            |ev_id| match state.events.remove(&ev_id) {
-                Some(event) => format!("Removed event \"{}\"", event.text),
-                None => format!("Error: no event at number \"{}\"", ev_id)
-            })
-            .collect::<Vec<String>>();
+        let ids: Vec<u64> = state.free_ids().take(output.len()).collect();
+        let changes: Vec<EventChange> = ids.iter()
+            .zip(output)
+            .map(|(id, event)| EventChange::Upsert(*id, Box::new(event)))
+            .collect();
+
+        let mut text = fill(self.tr(Msg::EventsImported), &[&ids.len()]);
+        if !ids.is_empty() {
+            let ids_str: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+            text = fill(self.tr(Msg::ImportedNumbers), &[&text, &ids_str.join(", ")]);
+            let description = fill(self.tr(Msg::DescImport), &[&ids.len(), &filename]);
+            self.commit(&mut state, description, changes);
+        }
+
+        if !problems.is_empty() {
+            text = format!("{}\n{}", text, conversion_report(self.tr(Msg::ImportProblems), &problems));
+        }
+
+        Ok(text.into())
+    }
+
+    fn remove_events(&self, words: &[&str]) -> anyhow::Result<Reply> {
+
+        if words.is_empty() {
+            bail!(self.tr(Msg::NoEventNumber));
+        }
+
+        let mut state = self.state.lock().unwrap();
+        let selection = self.select_events(&state, words)?;
+
+        info!("Removing events {:?}", selection.ids);
 
-        state.save(&self.state_path);
+        let mut changes = vec![];
+        let mut removed = vec![];
+        let mut out_lines = vec![];
+
+        for id in selection.ids.iter() {
+            let event = &state.events[id];
+            changes.push(EventChange::Remove(*id));
+            removed.push(format!("\"{}\"", event.text));
+            out_lines.push(fill(self.tr(Msg::EventRemoved), &[&event.text]));
+        }
+        out_lines.extend(self.missing_lines(&selection));
+
+        let reply = Reply::from(out_lines.join("\n"));
+
+        if changes.is_empty() {
+            return Ok(reply);
+        }
 
-        Ok(out_lines.join("\n"))
+        let description = fill(self.tr(Msg::DescDelete), &[&removed.join(", ")]);
+        let seq = self.commit(&mut state, description, changes);
+
+        Ok(reply.with_button(self.tr(Msg::UndoButton), format!("undo:{}", seq)))
     }
 
-    fn tag_event(&self, words: &[&str]) -> anyhow::Result<String> {
+    fn tag_event(&self, words: &[&str]) -> anyhow::Result<Reply> {
 
-        let (id_str, tag_words) = match words {
-            []                       => Err(anyhow!("No arguments specified")),
-            [_]                      => Err(anyhow!("No tag specified")),
-            [id_str, tag_words @ ..] => Ok((id_str, tag_words))
+        let (selection_word, tag_words) = match words {
+            []                               => Err(anyhow!(self.tr(Msg::NoArguments))),
+            [_]                              => Err(anyhow!(self.tr(Msg::NoTagSpecified))),
+            [selection_word, tag_words @ ..] => Ok((selection_word, tag_words))
         }?;
 
-        let id: u64 = id_str
-            .parse()
-            .context("Invalid event number")?;
+        let tags = parse_tags(tag_words);
+        if tags.is_empty() {
+            bail!(self.tr(Msg::NoTagSpecified));
+        }
+
+        let mut state = self.state.lock().unwrap();
+        let selection = self.select_events(&state, &[selection_word])?;
+
+        info!("Tagging events {:?} with {:?}", selection.ids, tags);
+
+        let mut changes = vec![];
+        let mut tagged = vec![];
+        let mut out_lines = vec![];
+
+        for id in selection.ids.iter() {
+            let mut event = state.events[id].clone();
+            event.tags.extend(tags.iter().cloned());
+            tagged.push(format!("\"{}\"", event.text));
+            out_lines.push(fill(
+                self.tr(Msg::EventTagged),
+                &[&event.text, &listing::format_tags(&event).trim()]
+            ));
+            changes.push(EventChange::Upsert(*id, Box::new(event)));
+        }
+        out_lines.extend(self.missing_lines(&selection));
+
+        if !changes.is_empty() {
+            let description = fill(self.tr(Msg::DescTag), &[&tagged.join(", ")]);
+            self.commit(&mut state, description, changes);
+        }
+
+        Ok(out_lines.join("\n").into())
+    }
+
+    /// Removes the given tags from events, or all of them if none is given
+    fn untag_event(&self, words: &[&str]) -> anyhow::Result<Reply> {
+
+        let (selection_word, tag_words) = words.split_first()
+            .ok_or_else(|| anyhow!(self.tr(Msg::NoEventNumber)))?;
+
+        let tags = parse_tags(tag_words);
+
+        let mut state = self.state.lock().unwrap();
+        let selection = self.select_events(&state, &[selection_word])?;
+
+        info!("Untagging events {:?}", selection.ids);
+
+        let mut changes = vec![];
+        let mut untagged = vec![];
+        let mut out_lines = vec![];
+
+        for id in selection.ids.iter() {
+
+            let mut event = state.events[id].clone();
+            let nb_tags = event.tags.len();
+
+            match tags.is_empty() {
+                true => event.tags.clear(),
+                false => event.tags.retain(|tag| !tags.contains(tag))
+            }
+
+            if event.tags.len() < nb_tags {
+                untagged.push(format!("\"{}\"", event.text));
+                out_lines.push(fill(self.tr(Msg::EventUntagged), &[&event.text]));
+                changes.push(EventChange::Upsert(*id, Box::new(event)));
+            }
+        }
+
+        if changes.is_empty() && selection.missing.is_empty() {
+            let tags: Vec<&str> = tags.iter().map(String::as_str).collect();
+            bail!(fill(self.tr(Msg::EventNotTagged), &[&tags.join(", ")]));
+        }
+        out_lines.extend(self.missing_lines(&selection));
+
+        if !changes.is_empty() {
+            let description = fill(self.tr(Msg::DescUntag), &[&untagged.join(", ")]);
+            self.commit(&mut state, description, changes);
+        }
+
+        Ok(out_lines.join("\n").into())
+    }
+
+    /// Skips the occurrences of events until they are resumed, by hand or
+    /// at the start of the given day
+    fn pause_events(&self, words: &[&str]) -> anyhow::Result<Reply> {
+
+        let (selection_word, until_words) = words.split_first()
+            .ok_or_else(|| anyhow!(self.tr(Msg::NoEventNumber)))?;
+
+        let opts = self.chat_opts();
+        let now = self.clock.now();
+
+        let until = match until_words {
+            [] => None,
+            _ => match parse_until_date(&opts, &now, until_words).map(upcoming::start_of_day) {
+                Some(until) if until > now => Some(until),
+                _ => bail!(self.tr(Msg::PauseUsage))
+            }
+        };
 
         let mut state = self.state.lock().unwrap();
+        let selection = self.select_events(&state, &[selection_word])?;
+
+        info!("Pausing events {:?} until {:?}", selection.ids, until);
+
+        let mut changes = vec![];
+        let mut paused = vec![];
+        let mut out_lines = vec![];
+
+        for id in selection.ids.iter() {
+            let mut event = state.events[id].clone();
+            event.pause = Some(Pause { until });
+            paused.push(format!("\"{}\"", event.text));
+            out_lines.push(match until {
+                None => fill(self.tr(Msg::EventPaused), &[&event.text]),
+                Some(until) => fill(
+                    self.tr(Msg::EventPausedUntil),
+                    &[&event.text, &listing::written_date(&opts, &until)]
+                )
+            });
+            changes.push(EventChange::Upsert(*id, Box::new(event)));
+        }
+        out_lines.extend(self.missing_lines(&selection));
 
-        let event = state.events.get_mut(&id)
-            .ok_or(anyhow!("No event at this number"))?;
+        if !changes.is_empty() {
+            let description = fill(self.tr(Msg::DescPause), &[&paused.join(", ")]);
+            self.commit(&mut state, description, changes);
+        }
+
+        Ok(out_lines.join("\n").into())
+    }
+
+    fn resume_events(&self, words: &[&str]) -> anyhow::Result<Reply> {
+
+        let selection_word = match words {
+            [selection_word] => selection_word,
+            [] => bail!(self.tr(Msg::NoEventNumber)),
+            _ => bail!(self.tr(Msg::ResumeUsage))
+        };
+
+        let now = self.clock.now();
+
+        let mut state = self.state.lock().unwrap();
+        let selection = self.select_events(&state, &[selection_word])?;
 
-        info!("Tagging event {}", id);
+        info!("Resuming events {:?}", selection.ids);
 
-        let tag = tag_words.join(" ");
-        let out_str = format!("Tagged event \"{}\" with \"{}\"", event.text, tag);
-        event.tag = Some(tag);
+        let mut changes = vec![];
+        let mut resumed = vec![];
+        let mut out_lines = vec![];
 
-        state.save(&self.state_path);
+        for id in selection.ids.iter() {
 
-        Ok(out_str)
+            let mut event = state.events[id].clone();
+            if !event.is_paused(&now) {
+                continue;
+            }
+            event.pause = None;
+            resumed.push(format!("\"{}\"", event.text));
+
+            // A one-time event may have been due while it was paused
+            match event.get_next_occurence(&now) {
+                Some(t) => {
+                    out_lines.push(fill(
+                        self.tr(Msg::EventResumed),
+                        &[&event.text, &format_time_diff(self.locale(), t - now)]
+                    ));
+                    changes.push(EventChange::Upsert(*id, Box::new(event)));
+                },
+                None => {
+                    out_lines.push(fill(self.tr(Msg::EventOver), &[&event.text]));
+                    changes.push(EventChange::Remove(*id));
+                }
+            }
+        }
+
+        if changes.is_empty() && selection.missing.is_empty() {
+            bail!(self.tr(Msg::NotPaused));
+        }
+        out_lines.extend(self.missing_lines(&selection));
+
+        if !changes.is_empty() {
+            let description = fill(self.tr(Msg::DescResume), &[&resumed.join(", ")]);
+            self.commit(&mut state, description, changes);
+        }
+
+        Ok(out_lines.join("\n").into())
+    }
+
+    /// Events picked by command arguments like "3", "3-7", "1,4,9" or "tag:work"
+    fn select_events(&self, state: &AgendaState, words: &[&str]) -> anyhow::Result<Selection> {
+
+        let selectors = words.iter()
+            .map(|w| Selector::parse_word(w)
+                .ok_or_else(|| anyhow!(fill(self.tr(Msg::InvalidSelection), &[w]))))
+            .collect::<anyhow::Result<Vec<Vec<Selector>>>>()?
+            .concat();
+
+        let selection = selector::select(state, &selectors);
+
+        if selection.is_empty() {
+            bail!(fill(self.tr(Msg::NothingSelected), &[&words.join(" ")]));
+        }
+
+        Ok(selection)
     }
 
-    fn untag_event(&self, words: &[&str]) -> anyhow::Result<String> {
+    fn missing_lines(&self, selection: &Selection) -> Vec<String> {
+        selection.missing.iter()
+            .map(|id| fill(self.tr(Msg::NoEventAt), &[id]))
+            .collect()
+    }
 
-        let id: u64 = words.get(0)
-            .ok_or(anyhow!("No event number supplied"))?
-            .parse()
-            .context("Invalid event number")?;
+    fn print_tags(&self) -> anyhow::Result<Reply> {
+
+        info!("Printing tags");
+
+        let state = self.state.lock().unwrap();
+        let counts = listing::tag_counts(&state);
+
+        if counts.is_empty() {
+            return Ok(self.tr(Msg::NoTags).into());
+        }
+
+        let lines: Vec<String> = counts.iter()
+            .map(|(tag, count)| format!(
+                "<pre>  #{} - {}</pre>", sanitize(tag), fill(self.tr(Msg::TagCount), &[count])
+            ))
+            .collect();
+
+        Ok(format!("<b>{}</b>\n{}", self.tr(Msg::TagsHeader), lines.join("\n")).into())
+    }
 
-        info!("Untagging event {}", id);
+    fn rename_tag(&self, words: &[&str]) -> anyhow::Result<Reply> {
+
+        let (old_tag, new_tag) = match words {
+            [old_tag, new_tag] => (normalize_tag(old_tag), normalize_tag(new_tag)),
+            _ => bail!(self.tr(Msg::RenameTagUsage))
+        };
+
+        if new_tag.is_empty() {
+            bail!(self.tr(Msg::RenameTagUsage));
+        }
+
+        info!("Renaming tag {} to {}", old_tag, new_tag);
+
+        let mut state = self.state.lock().unwrap();
+
+        let changes: Vec<EventChange> = state.events.iter()
+            .filter(|(_id, event)| event.tags.contains(&old_tag))
+            .map(|(id, event)| {
+                let mut event = event.clone();
+                event.tags.remove(&old_tag);
+                event.tags.insert(new_tag.clone());
+                EventChange::Upsert(*id, Box::new(event))
+            })
+            .collect();
+
+        if changes.is_empty() {
+            bail!(fill(self.tr(Msg::NoEventsWithTag), &[&old_tag]));
+        }
+
+        let text = fill(self.tr(Msg::TagRenamed), &[&old_tag, &new_tag, &changes.len()]);
+        let description = fill(self.tr(Msg::DescRenameTag), &[&old_tag]);
+        let seq = self.commit(&mut state, description, changes);
+
+        Ok(Reply::from(text).with_button(self.tr(Msg::UndoButton), format!("undo:{}", seq)))
+    }
+
+    /// Asks whether the events with the tag should lose it or be deleted
+    fn delete_tag(&self, words: &[&str]) -> anyhow::Result<Reply> {
+
+        let tag = match words {
+            [tag] => normalize_tag(tag),
+            _ => bail!(self.tr(Msg::DelTagUsage))
+        };
+
+        let state = self.state.lock().unwrap();
+        let nb_events = state.events.values()
+            .filter(|event| event.tags.contains(&tag))
+            .count();
+
+        if nb_events == 0 {
+            bail!(fill(self.tr(Msg::NoEventsWithTag), &[&tag]));
+        }
+
+        let text = fill(self.tr(Msg::DelTagChoice), &[&nb_events, &tag]);
+        let mut button_values = self.button_values.lock().unwrap();
+
+        Ok(Reply::from(text)
+            .with_button(self.tr(Msg::UntagAllButton), button_values.data("deltag:untag:", &tag))
+            .with_button(self.tr(Msg::DeleteAllButton), button_values.data("deltag:delete:", &tag)))
+    }
+
+    fn apply_delete_tag(&self, action: &str, tag: &str) -> anyhow::Result<Reply> {
+
+        let tag = self.button_values.lock().unwrap().resolve(tag)
+            .ok_or_else(|| anyhow!(self.tr(Msg::ButtonExpired)))?;
+        let tag = tag.as_str();
+
+        info!("Deleting tag {} ({})", tag, action);
 
         let mut state = self.state.lock().unwrap();
 
-        let event = state.events.get_mut(&id)
-            .ok_or(anyhow!("No event at this number"))?;
+        let tagged = state.events.iter()
+            .filter(|(_id, event)| event.tags.contains(tag));
+
+        let (changes, text, description): (Vec<EventChange>, _, _) = match action {
+            "untag" => (
+                tagged
+                    .map(|(id, event)| {
+                        let mut event = event.clone();
+                        event.tags.remove(tag);
+                        EventChange::Upsert(*id, Box::new(event))
+                    })
+                    .collect(),
+                self.tr(Msg::TagRemoved),
+                self.tr(Msg::DescUntagAll)
+            ),
+            "delete" => (
+                tagged.map(|(id, _event)| EventChange::Remove(*id)).collect(),
+                self.tr(Msg::TaggedEventsRemoved),
+                self.tr(Msg::DescDeleteTagged)
+            ),
+            _ => bail!("unknown button")
+        };
+
+        if changes.is_empty() {
+            bail!(fill(self.tr(Msg::NoEventsWithTag), &[&tag]));
+        }
 
-        event.tag = None;
+        let text = fill(text, &[&tag, &changes.len()]);
+        let seq = self.commit(&mut state, fill(description, &[&tag]), changes);
+
+        Ok(Reply::from(text).with_button(self.tr(Msg::UndoButton), format!("undo:{}", seq)))
+    }
+
+    fn print_events(&self, words: &[&str]) -> anyhow::Result<Reply> {
+
+        let vocab = self.locale().vocabulary();
+        let by_words = [vocab.order_prefixes, Locale::En.vocabulary().order_prefixes].concat();
+
+        let (filter_words, order) = match words {
+            [tag_words @ .., by, w] if by_words.contains(by) => (
+                tag_words,
+                EventOrder::from_word(vocab, w).ok_or_else(|| anyhow!(self.tr(Msg::EventsUsage)))?
+            ),
+            _ => (words, EventOrder::Next)
+        };
 
-        state.save(&self.state_path);
+        let filter = match filter_words {
+            [] => None,
+            _ => Some(filter_words.join(" "))
+        };
 
-        Ok("Untagged event".to_string())
+        self.events_page(filter.as_deref(), order, 0)
     }
 
-    fn print_events(&self) -> anyhow::Result<String> {
+    /// One page of `/events`, with buttons to the previous and next ones.
+    /// The tag filter is kept as written to be put in the buttons.
+    fn events_page(&self, filter: Option<&str>, order: EventOrder, page: usize) -> anyhow::Result<Reply> {
 
-        info!("Printing events");
+        info!("Printing events page {} by {:?}", page, order);
 
         let state = self.state.lock().unwrap();
 
         if state.events.is_empty() {
-            return Ok("No events".to_owned())
+            return Ok(self.tr(Msg::NoEvents).into())
         }
 
-        let untagged_events: HashMap<&u64, &AgendaEvent> = state.events
-            .iter()
-            .filter(|(_id, event)| event.tag.is_none())
-            .collect();
+        let opts = self.chat_opts();
+        let now = self.clock.now();
 
-        let msg = [
-            make_tags_print_list(&state.events),
-            vec!["\n<b>Untagged events:</b>".to_owned()],
-            make_events_print_list(&self.opts, untagged_events)
-        ]
-        .concat().join("\n");
+        let tag_filter = filter.map(|filter| {
+            TagFilter::from_words(&filter.split_whitespace().collect::<Vec<&str>>())
+        });
+
+        let lines = match &tag_filter {
+            None => listing::events_lines(&opts, &state, &now, order),
+            Some(tag_filter) => listing::tagged_events_lines(&opts, &state, &now, tag_filter, order)
+        };
+
+        if let (true, Some(tag_filter)) = (lines.is_empty(), &tag_filter) {
+            bail!(fill(self.tr(Msg::NoEventsWithTag), &[tag_filter]));
+        }
+
+        let pages = listing::paginate(lines);
+        let nb_pages = pages.len();
+
+        // The events may have changed since the button was sent
+        let page = page.min(nb_pages - 1);
 
-        Ok(msg)
+        if nb_pages == 1 {
+            return Ok(pages[0].clone().into());
+        }
+
+        let text = format!(
+            "{}\n\n{}",
+            pages[page],
+            fill(self.tr(Msg::PageOf), &[&(page + 1), &nb_pages])
+        );
+        let mut button_values = self.button_values.lock().unwrap();
+        let mut button_data = |page: usize| button_values.data(
+            &format!("events:{}:{}:", order.word(), page), filter.unwrap_or_default()
+        );
+
+        let mut reply = Reply::from(text);
+        if page > 0 {
+            reply = reply.with_button(self.tr(Msg::PreviousPage), button_data(page - 1));
+        }
+        if page + 1 < nb_pages {
+            reply = reply.with_button(self.tr(Msg::NextPage), button_data(page + 1));
+        }
+
+        Ok(reply)
     }
 
-    fn print_tagged_events(&self, words: &[&str]) -> anyhow::Result<String> {
+    fn find_events(&self, words: &[&str]) -> anyhow::Result<Reply> {
+
+        /// Most events in the results, each with its row of buttons
+        const MAX_RESULTS: usize = 10;
 
         if words.is_empty() {
-            return Err(anyhow!("No tag supplied"))
+            bail!(self.tr(Msg::FindUsage));
+        }
+
+        info!("Finding events matching {:?}", words);
+
+        let state = self.state.lock().unwrap();
+        let opts = self.chat_opts();
+        let now = self.clock.now();
+
+        let found = search::find_events(&state, &now, words);
+
+        if found.is_empty() {
+            return Ok(fill(self.tr(Msg::NothingFound), &[&sanitize(&words.join(" "))]).into());
+        }
+
+        let mut lines = vec![format!("<b>{}</b>", self.tr(Msg::FoundHeader))];
+        let mut reply_buttons = vec![];
+
+        for id in found.iter().take(MAX_RESULTS) {
+
+            let event = &state.events[id];
+            let when = match event.get_next_active_occurence(&now) {
+                Some(t) => upcoming::format_occurence(&opts, &t),
+                None => event.cronline.describe(&opts)
+            };
+            lines.push(format!("<pre>  {} - [{}] {}</pre>", when, id, sanitize(&event.text)));
+
+            reply_buttons.push(vec![
+                (fill(self.tr(Msg::DeleteButton), &[id]), format!("del:{}", id)),
+                (fill(self.tr(Msg::EditButton), &[id]), format!("edit:{}", id))
+            ]);
         }
 
-        info!("Printing tagged events");
+        if found.len() > MAX_RESULTS {
+            lines.push(fill(self.tr(Msg::AndMore), &[&(found.len() - MAX_RESULTS)]));
+        }
+
+        Ok(reply_buttons.into_iter()
+            .fold(Reply::from(lines.join("\n")), Reply::with_button_row))
+    }
+
+    /// Answer to an "edit" button: a new time and message have to be typed in
+    fn edit_hint(&self, event_id: &str) -> anyhow::Result<Reply> {
+
+        let id: u64 = event_id.parse().context("invalid edit button")?;
+
+        let state = self.state.lock().unwrap();
+        let event = state.events.get(&id)
+            .ok_or_else(|| anyhow!(self.tr(Msg::NoEventAtNumber)))?;
+
+        let text = fill(self.tr(Msg::EditHint), &[
+            &id, &id, &event.cronline.describe(&self.chat_opts()), &sanitize(&event.text)
+        ]);
+
+        Ok(text.into())
+    }
+
+    fn print_next(&self, words: &[&str]) -> anyhow::Result<Reply> {
+
+        const DEFAULT_NB_OCCURENCES: usize = 5;
+        const MAX_NB_OCCURENCES: usize = 50;
+
+        let nb = match words {
+            [] => DEFAULT_NB_OCCURENCES,
+            [w] => w.parse::<usize>().context(self.tr(Msg::NextUsage))?.min(MAX_NB_OCCURENCES),
+            _ => bail!(self.tr(Msg::NextUsage))
+        };
+
+        info!("Printing next {} occurrences", nb);
+
+        let state = self.state.lock().unwrap();
+        let msg = upcoming::next_occurences(&self.chat_opts(), &state, &self.clock.now(), nb);
+
+        Ok(msg.into())
+    }
+
+    fn print_agenda(&self, words: &[&str]) -> anyhow::Result<Reply> {
+
+        let period = match words {
+            [] => AgendaPeriod::Today,
+            [w] => AgendaPeriod::from_word(self.locale().vocabulary(), w)
+                .ok_or_else(|| anyhow!(self.tr(Msg::AgendaUsage)))?,
+            _ => bail!(self.tr(Msg::AgendaUsage))
+        };
 
-        let tag = words.join(" ");
+        info!("Printing agenda for {:?}", period);
 
         let state = self.state.lock().unwrap();
-        let selected_events:HashMap<&u64, &AgendaEvent> = state.events
-            .iter()
-            .filter(|(_id, event)| match &event.tag {
-                None => false,
-                Some(event_tag) => *event_tag == tag
-            }).collect();
+        let msg = upcoming::agenda(&self.chat_opts(), &state, &self.clock.now(), period);
 
-        if selected_events.is_empty() {
-            return Err(anyhow!("No events with tag \"{}\"", tag))
+        Ok(msg.into())
+    }
+
+    fn print_history(&self, words: &[&str]) -> anyhow::Result<Reply> {
+
+        const DEFAULT_NB_ENTRIES: usize = 10;
+
+        let (limit, tag_words) = match words {
+            [w, rem_words @ ..] if w.parse::<usize>().is_ok() =>
+                (w.parse::<usize>().unwrap(), rem_words),
+            _ => (DEFAULT_NB_ENTRIES, words)
+        };
+
+        let tag = match tag_words {
+            [] => None,
+            _ => Some(normalize_tag(&tag_words.join(" ")))
+        };
+
+        info!("Printing history");
+
+        let entries = self.storage.load_history(limit, tag.as_deref())
+            .context("cannot read history")?;
+
+        if entries.is_empty() {
+            return Ok(self.tr(Msg::NoHistory).into())
         }
 
+        let header = match &tag {
+            None => format!("<b>{}</b>", self.tr(Msg::HistoryHeader)),
+            Some(tag) => format!("<b>{}</b> <code>{}</code>:", self.tr(Msg::HistoryTagHeader), tag)
+        };
+
+        let opts = self.chat_opts();
         let msg = [
-            vec![format!("<b>Tag</b> <code>{}</code>:", tag)],
-            make_events_print_list(&self.opts, selected_events)
+            vec![header],
+            entries.iter().map(|entry| entry.msg_format(&opts)).collect()
         ]
         .concat().join("\n");
 
-        Ok(msg)
+        Ok(msg.into())
     }
 
-    fn print_help(&self) -> anyhow::Result<String> {
+    fn print_help(&self) -> anyhow::Result<Reply> {
 
         info!("Printing help");
 
         let commands = [
-            (
-                "/help",
-                "Show this message"
-            ),
-            (
-                "/events",
-                "Lists upcoming events"
-            ),
-            (
-                "/events &lt;tag&gt",
-                "Lists upcoming events tagged with &lt;tag&gt;"
-            ),
-            (
-                "/del  &lt;n&gt;",
-                "Delete event number &lt;n&gt;"
-            ),
-            (
-                "/tag &lt;n&gt; &lt;tag&gt;",
-                "Tag event number &lt;n&gt; with tag &lt;tag&gt;"
-            ),
-            (
-                "/untag &lt;n&gt;",
-                "Untag event number &lt;n&gt;"
-            )
+            ("/help", Msg::HelpHelp),
+            ("/events [by next|id|tag]", Msg::HelpEvents),
+            ("/events &lt;tag&gt; [-&lt;tag&gt;]...", Msg::HelpEventsTag),
+            ("/next [n]", Msg::HelpNext),
+            ("/agenda [today|tomorrow|week]", Msg::HelpAgenda),
+            ("/find &lt;words&gt;", Msg::HelpFind),
+            ("/del &lt;events&gt;...", Msg::HelpDel),
+            ("/edit &lt;n&gt; &lt;time&gt; &lt;message&gt;", Msg::HelpEdit),
+            ("/when &lt;time&gt;", Msg::HelpWhen),
+            ("/undo", Msg::HelpUndo),
+            ("/tag &lt;events&gt; &lt;tag&gt;...", Msg::HelpTag),
+            ("/untag &lt;events&gt; [tag]...", Msg::HelpUntag),
+            ("/pause &lt;events&gt; [until &lt;date&gt;]", Msg::HelpPause),
+            ("/resume &lt;events&gt;", Msg::HelpResume),
+            ("/tags", Msg::HelpTags),
+            ("/renametag &lt;old&gt; &lt;new&gt;", Msg::HelpRenameTag),
+            ("/deltag &lt;tag&gt;", Msg::HelpDelTag),
+            ("/export", Msg::HelpExport),
+            ("/history [n] [tag]", Msg::HelpHistory),
+            ("/language [code|default]", Msg::HelpLanguage)
         ];
 
         let commands_msg = commands.iter()
             .map(|(cmd, txt)| {
-                format!("<b>{}</b>\n    {}", cmd, txt)
+                format!("<b>{}</b>\n    {}", cmd, self.tr(*txt))
             })
             .collect::<Vec<String>>()
             .join("\n");
 
-        let agenda_msg = "\
-            To add a new event, send \
-            <code>&lt;time&gt; &lt;message&gt;</code> \
-            without a leading <b>/</b>.";
-
-        let examples = [
-            "at 9am pick up groceries",
-            "every year on April 4th Dan's birthday",
-            "on 21/08 dentist appointment",
-            "in 30 minutes check cake in oven",
-            "every day at 5pm do some exercise"
-        ];
-
-        let examples_msg = examples.iter()
+        let examples_msg = self.locale().examples().iter()
             .map(|txt| { format!("    {}", txt) })
             .collect::<Vec<String>>()
             .join("\n");
 
 
         let msg = format!(
-            "{}\n\n{}\nExamples:\n\n<code>{}</code>",
-            commands_msg, agenda_msg, examples_msg);
+            "{}\n\n{}\n\n{}\n{}\n\n<code>{}</code>",
+            commands_msg, self.tr(Msg::HelpSelection),
+            self.tr(Msg::HelpAddEvent), self.tr(Msg::HelpExamples), examples_msg);
 
-        Ok(msg)
+        Ok(msg.into())
     }
-}
 
-fn make_tags_print_list(events: &HashMap<u64, AgendaEvent>) -> Vec<String> {
+    fn set_language(&mut self, words: &[&str]) -> anyhow::Result<Reply> {
 
-    let mut tags_count = HashMap::<&String, usize>::new();
+        let chat_id = self.chat_id.ok_or_else(|| anyhow!("no active chat"))?;
 
-    events
-        .values()
-        .for_each(|event| if let Some(ref tag) = event.tag {
-            let counter = tags_count.entry(tag).or_insert(0);
-            *counter += 1;
-        });
+        let locale = match words {
 
-    tags_count.iter()
-        .map(|(tag, count)| format!("<b>Tag</b> <code>{}</code>: {} events", tag, count))
-        .collect()
-}
+            [] => {
+                let available: Vec<String> = LOCALES.iter()
+                    .map(|locale| format!("{} ({})", locale.code(), locale.name()))
+                    .collect();
+                let text = fill(
+                    self.tr(Msg::CurrentLanguage),
+                    &[&self.locale().name(), &available.join(", ")]
+                );
+                return Ok(text.into());
+            },
 
+            ["default"] => None,
 
-fn make_events_print_list(
-    opts: &Opts, events: HashMap<&u64, &AgendaEvent>
-) -> Vec<String> {
+            [code] => Some(code.parse::<Locale>()
+                .map_err(|_| anyhow!(fill(self.tr(Msg::UnknownLanguage), &[code])))?),
 
-    let mut ids_list: Vec<&u64> = events.keys().cloned().collect();
-    ids_list.sort();
-    
-    ids_list.iter().map(|id| {
-
-        let event = &events[id];
-
-        let sanitized = event.text
-            .replace("<", "&lt;")
-            .replace(">", "&gt;")
-            .replace("<", "&amp;");
-        
-        format!(
-            "<pre>  {} - [{}] {}</pre>",
-            event.cronline.msg_format(opts),
-            id,
-            sanitized
-        )
-    }).collect()
+            _ => bail!(fill(self.tr(Msg::UnknownLanguage), &[&words.join(" ")]))
+        };
+
+        info!("Setting language of chat {} to {:?}", chat_id, locale);
+
+        self.storage.save_chat_locale(chat_id, locale)?;
+
+        let text = match locale {
+            Some(locale) => {
+                self.chat_locales.insert(chat_id, locale);
+                fill(self.tr(Msg::LanguageSet), &[&locale.name()])
+            },
+            None => {
+                self.chat_locales.remove(&chat_id);
+                fill(self.tr(Msg::LanguageReset), &[&self.locale().name()])
+            }
+        };
+
+        Ok(text.into())
+    }
+}
+
+/// Tags from words like "work" or "#work", leaving out the empty ones
+fn parse_tags(words: &[&str]) -> BTreeSet<String> {
+    words.iter()
+        .map(|w| normalize_tag(w))
+        .filter(|tag| !tag.is_empty())
+        .collect()
 }
 
-fn format_time_diff(dt: chrono::Duration) -> String {
+fn format_time_diff(locale: Locale, dt: chrono::Duration) -> String {
 
     let mut nb_minutes = dt.num_minutes();
     if (dt.num_seconds() % 60) >= 30 {
@@ -449,62 +1370,126 @@ fn format_time_diff(dt: chrono::Duration) -> String {
 
     let rounded_dt = chrono::Duration::minutes(nb_minutes);
 
-    let (weeks, days, hours, minutes, seconds) = (
+    let (weeks, days, hours, minutes) = (
         rounded_dt.num_weeks(),
         rounded_dt.num_days(),
         rounded_dt.num_hours(),
-        rounded_dt.num_minutes(),
-        rounded_dt.num_seconds()
+        rounded_dt.num_minutes()
     );
 
     let mut text = vec![];
-    if weeks > 0 { text.push(format!("{} weeks", weeks)); }
-    if days > 0 { text.push(format!("{} days", days % 7)); }
-    if hours > 0 { text.push(format!("{} hours", hours % 24)); }
+    if weeks > 0 { text.push(fill(locale.tr(Msg::Weeks), &[&weeks])); }
+    if days > 0 { text.push(fill(locale.tr(Msg::Days), &[&(days % 7)])); }
+    if hours > 0 { text.push(fill(locale.tr(Msg::Hours), &[&(hours % 24)])); }
     if minutes > 0 {
-        text.push(format!("{} minutes", minutes % 60));
+        text.push(fill(locale.tr(Msg::Minutes), &[&(minutes % 60)]));
     } else {
-        text.push("less than a minute".to_owned());
+        text.push(locale.tr(Msg::LessThanAMinute).to_owned());
     }
 
     text.join(" ")
 }
 
 
-#[derive(Clone, Serialize, Deserialize)]
-struct AgendaState {
-    events: HashMap<u64, AgendaEvent>
+fn conversion_report(header: &str, problems: &[String]) -> String {
+    let lines: Vec<String> = problems.iter()
+        .map(|problem| format!("  - {}", sanitize(problem)))
+        .collect();
+    format!("{}\n{}", header, lines.join("\n"))
 }
 
+fn sanitize(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
 
-impl AgendaState {
+/// `export-ics` subcommand
+pub(super) fn export_ics_file(storage: &dyn Storage, path: &Path) -> anyhow::Result<String> {
 
-    fn restore(state_path: &Path) -> anyhow::Result<Self> {
+    let state = storage.load_agenda().unwrap_or_else(|_| AgendaState::new());
+    let ical::Conversion { output, problems } = ical::export(&state, &chrono::Local::now());
 
-        let path_str = state_path.to_string_lossy();
+    std::fs::write(path, output)
+        .with_context(|| format!("cannot write {}", path.to_string_lossy()))?;
+
+    let text = format!("Exported {} events", state.events.len() - problems.len());
+    Ok(match problems.is_empty() {
+        true => text,
+        false => format!("{}\n{}", text, problems.join("\n"))
+    })
+}
+
+/// `import-ics` subcommand
+pub(super) fn import_ics_file(storage: &dyn Storage, path: &Path) -> anyhow::Result<String> {
+
+    let data = std::fs::read_to_string(path)
+        .with_context(|| format!("cannot read {}", path.to_string_lossy()))?;
+    let ical::Conversion { output, problems } = ical::import(&data, &chrono::Local::now());
+
+    let state = storage.load_agenda().unwrap_or_else(|_| AgendaState::new());
+    let changes: Vec<EventChange> = state.free_ids()
+        .zip(output)
+        .map(|(id, event)| EventChange::Upsert(id, Box::new(event)))
+        .collect();
+
+    storage.update_agenda(&changes)?;
+
+    let text = format!("Imported {} events", changes.len());
+    Ok(match problems.is_empty() {
+        true => text,
+        false => format!("{}\nSkipped:\n{}", text, problems.join("\n"))
+    })
+}
+
+/// Changes must be persisted before replying, so a storage failure is fatal
+fn save_changes(storage: &dyn Storage, changes: &[EventChange]) {
+    storage.update_agenda(changes)
+        .unwrap_or_else(|err| panic!("Cannot save agenda data: {}", format_error(err)));
+}
 
-        info!("Attempting to restore agenda from {}", path_str);
-        let data = std::fs::read_to_string(&state_path)?;
-        let state: Self = serde_json::from_str(&data)
-            .map_err(anyhow::Error::new)
-            .expect(&format!("Error parsing agenda data from {}", path_str));
-        Ok(state)
-    }
 
-    fn new() -> Self {
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct AgendaState {
+    pub(crate) events: HashMap<u64, AgendaEvent>
+}
+
+
+impl AgendaState {
+
+    pub(crate) fn new() -> Self {
         AgendaState { events: HashMap::new() }
     }
 
-    fn save(&self, state_path: &Path) {
+    fn free_ids(&self) -> impl Iterator<Item = u64> + '_ {
+        (0..).filter(move |id| !self.events.contains_key(id))
+    }
 
-        let path_str = state_path.to_string_lossy();
+    /// Same as `apply`, returning the changes that revert it
+    fn apply_reversible(&mut self, changes: &[EventChange]) -> Vec<EventChange> {
+
+        let mut undo_changes: Vec<EventChange> = changes.iter()
+            .map(|change| {
+                let id = change.id();
+                let undo_change = match self.events.get(&id) {
+                    Some(event) => EventChange::Upsert(id, Box::new(event.clone())),
+                    None => EventChange::Remove(id)
+                };
+                self.apply(std::slice::from_ref(change));
+                undo_change
+            })
+            .collect();
+
+        undo_changes.reverse();
+        undo_changes
+    }
 
-        || -> anyhow::Result<()> {
-            info!("Saving agenda to: {}", path_str);
-            let data = serde_json::to_string_pretty(self)?;
-            std::fs::write(&state_path, data)?;
-            Ok(())
-        }()
-        .expect(&format!("Cannot save agenda data to {}", path_str));
+    pub(crate) fn apply(&mut self, changes: &[EventChange]) {
+        for change in changes {
+            match change {
+                EventChange::Upsert(id, event) => { self.events.insert(*id, (**event).clone()); },
+                EventChange::Remove(id) => { self.events.remove(id); }
+            }
+        }
     }
 }
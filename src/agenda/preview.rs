@@ -0,0 +1,74 @@
+use anyhow::Context;
+use crate::{Opts, DateFormat};
+use crate::locale::{Msg, fill};
+use super::Instant;
+use super::event::AgendaEvent;
+use super::time_parsing::{parse_cronline, CronlineResult};
+
+const NB_OCCURENCES: usize = 5;
+
+/// Explains how a time expression is understood, without saving anything.
+/// Plain text, shared by the `/when` command and the HTTP route.
+pub(crate) fn explain_time(opts: &Opts, now: &Instant, text: &str) -> anyhow::Result<String> {
+
+    let locale = opts.locale;
+    let words: Vec<&str> = text.split_whitespace().collect();
+
+    if words.is_empty() {
+        anyhow::bail!(locale.tr(Msg::NoTimeExpression));
+    }
+
+    let CronlineResult {
+        cronline,
+        remaining_words,
+        comment,
+        steps,
+        bounds
+    } = parse_cronline(opts, now, &words)
+        .context(locale.tr(Msg::CannotParseTime))?;
+
+    let mut lines = vec![locale.tr(Msg::WhenInterpretation).to_owned()];
+    lines.extend(steps.iter().map(
+        |step| format!("  \"{}\" -> {}", step.words.join(" "), step.parser)));
+
+    if !remaining_words.is_empty() {
+        lines.push(format!("  \"{}\" -> {}", remaining_words.join(" "), locale.tr(Msg::WhenMessage)));
+    }
+
+    lines.push(fill(locale.tr(Msg::WhenCronline), &[&cronline.msg_format(opts)]));
+
+    if let Some(comment) = comment {
+        lines.push(comment);
+    }
+
+    let date_fmt = match opts.date_format {
+        DateFormat::DMY => "%a %d/%m/%Y %H:%M",
+        DateFormat::MDY => "%a %m/%d/%Y %H:%M"
+    };
+
+    if let Some(start) = bounds.start {
+        lines.push(fill(locale.tr(Msg::WhenStart), &[&start.format(date_fmt)]));
+    }
+    if let Some(end) = bounds.end {
+        lines.push(fill(locale.tr(Msg::WhenEnd), &[&end.format(date_fmt)]));
+    }
+    if let Some(count) = bounds.remaining {
+        lines.push(fill(locale.tr(Msg::WhenCount), &[&count]));
+    }
+
+    let event = AgendaEvent { bounds, ..AgendaEvent::new(cronline, String::new()) };
+    let occurences: Vec<String> = event.upcoming_occurences(now)
+        .take(NB_OCCURENCES)
+        .map(|t| format!("  {}", t.format(date_fmt)))
+        .collect();
+
+    match occurences.is_empty() {
+        true => lines.push(locale.tr(Msg::WhenNeverOccurs).to_owned()),
+        false => {
+            lines.push(locale.tr(Msg::WhenNextOccurences).to_owned());
+            lines.extend(occurences);
+        }
+    }
+
+    Ok(lines.join("\n"))
+}
@@ -0,0 +1,205 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use log::{debug, info, warn};
+
+use crate::BotUpdate;
+use crate::storage::{SharedStorage, EventChange};
+use super::{AgendaState, Instant, Reminder, SharedClock, save_changes};
+use super::journal::Journal;
+
+/// Longest sleep without looking at the wall clock, so that a clock change
+/// is noticed even when nothing is due for a long time
+const MAX_SLEEP: Duration = Duration::from_secs(60);
+
+/// Gap between the wall clock and the time actually slept above which the
+/// clock is considered to have been changed
+const CLOCK_TOLERANCE_MS: i64 = 2000;
+
+/// Fires the events at their next occurrences. Sleeps until the earliest
+/// one, and is woken through a channel whenever the events change.
+pub(super) struct Scheduler {
+    state: Arc<Mutex<AgendaState>>,
+    storage: SharedStorage,
+    journal: Arc<Mutex<Journal>>,
+    sender: Sender<BotUpdate>,
+    clock: SharedClock,
+    wake: Receiver<()>,
+    /// Next occurrence of each event, earliest first
+    queue: BinaryHeap<Reverse<(Instant, u64)>>,
+    /// Every occurrence up to that time has been fired
+    checked_until: Instant
+}
+
+impl Scheduler {
+
+    pub(super) fn new(
+        state: Arc<Mutex<AgendaState>>,
+        storage: SharedStorage,
+        journal: Arc<Mutex<Journal>>,
+        sender: Sender<BotUpdate>,
+        clock: SharedClock,
+        wake: Receiver<()>
+    ) -> Self {
+        let checked_until = clock.now();
+        let mut scheduler = Scheduler {
+            state,
+            storage,
+            journal,
+            sender,
+            clock,
+            wake,
+            queue: BinaryHeap::new(),
+            checked_until
+        };
+        scheduler.reschedule();
+        scheduler
+    }
+
+    pub(super) fn run(mut self) {
+
+        info!("Starting agenda scheduler");
+
+        loop {
+
+            let sleep_start = (std::time::Instant::now(), self.clock.now());
+
+            let timeout = match self.next_due() {
+                Some(t) => (t - sleep_start.1)
+                    .to_std()
+                    .unwrap_or(Duration::ZERO)
+                    .min(MAX_SLEEP),
+                None => MAX_SLEEP
+            };
+
+            let woken = match self.wake.recv_timeout(timeout) {
+                Ok(()) => true,
+                Err(RecvTimeoutError::Timeout) => false,
+                Err(RecvTimeoutError::Disconnected) => {
+                    info!("Agenda gone, stopping scheduler");
+                    return;
+                }
+            };
+
+            let now = self.clock.now();
+
+            let slept = chrono::Duration::from_std(sleep_start.0.elapsed()).unwrap();
+            let clock_shift = now - (sleep_start.1 + slept);
+            if clock_shift.num_milliseconds().abs() > CLOCK_TOLERANCE_MS {
+                // Occurrences skipped by the change are not caught up on,
+                // and the ones it brings back are not fired twice
+                warn!("System clock changed by {}, rescheduling from {}", clock_shift, now);
+                self.checked_until = now;
+                self.reschedule();
+            }
+
+            self.step(woken);
+        }
+    }
+
+    /// Earliest occurrence in the queue
+    pub(super) fn next_due(&self) -> Option<Instant> {
+        self.queue.peek().map(|Reverse((t, _id))| *t)
+    }
+
+    /// Fires everything due by now, after rescheduling if the events changed
+    pub(super) fn step(&mut self, woken: bool) {
+
+        // Several changes in a row only need one rescheduling
+        if self.wake.try_iter().count() > 0 || woken {
+            self.reschedule();
+        }
+
+        let now = self.clock.now();
+        self.fire_due(&now);
+    }
+
+    /// Recomputes the whole queue from the current events
+    fn reschedule(&mut self) {
+
+        let state = self.state.lock().unwrap();
+
+        self.queue = state.events.iter()
+            .filter_map(|(id, event)| {
+                event.get_next_active_occurence(&self.checked_until)
+                    .or_else(|| event.pause_end_without_occurence(&self.checked_until))
+                    .map(|t| Reverse((t, *id)))
+            })
+            .collect();
+
+        debug!("Scheduled {} events, next: {:?}", self.queue.len(), self.queue.peek());
+    }
+
+    fn fire_due(&mut self, now: &Instant) {
+
+        let mut state = self.state.lock().unwrap();
+        let mut changes = vec![];
+
+        while let Some(&Reverse((t, id))) = self.queue.peek() {
+
+            if t > *now {
+                break;
+            }
+            self.queue.pop();
+
+            // The event may have changed since the queue was computed
+            let just_before = t - chrono::Duration::seconds(1);
+            let event = match state.events.get_mut(&id) {
+                Some(event) if event.get_next_active_occurence(&just_before) == Some(t) => event,
+                Some(event) if event.pause_end_without_occurence(&just_before) == Some(t) => {
+                    info!("Event {} paused past its last occurence, removing", id);
+                    self.sender.send(BotUpdate::EventOver(event.text.clone())).unwrap();
+                    state.events.remove(&id);
+                    changes.push(EventChange::Remove(id));
+                    continue;
+                },
+                _ => continue
+            };
+
+            info!("It's {}, firing event {} scheduled at {}", now, id, t);
+
+            let reminder = Reminder {
+                event_id: id,
+                text: event.text.clone(),
+                tags: event.tags.iter().cloned().collect(),
+                scheduled: t,
+                cronline: event.cronline.clone()
+            };
+            self.sender.send(BotUpdate::Reminder(reminder)).unwrap();
+
+            event.record_occurence();
+
+            // Firing means the pause, if any, is over
+            let resumed = event.pause.take().is_some();
+            if resumed {
+                info!("Event {} resumed", id);
+            }
+
+            match event.get_next_occurence(&t) {
+                Some(next_t) => {
+                    if event.bounds.remaining.is_some() || resumed {
+                        changes.push(EventChange::Upsert(id, Box::new(event.clone())));
+                    }
+                    self.queue.push(Reverse((next_t, id)));
+                },
+                None => {
+                    info!("Event {} never occurs again, removing", id);
+                    state.events.remove(&id);
+                    changes.push(EventChange::Remove(id));
+                }
+            }
+        }
+
+        self.checked_until = *now;
+
+        if !changes.is_empty() {
+            save_changes(self.storage.as_ref(), &changes);
+            let mut journal = self.journal.lock().unwrap();
+            for change in changes.iter() {
+                journal.invalidate(change.id());
+            }
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use super::{AgendaEvent, AgendaState, Instant};
+
+/// Events matching a `/find` query, best matches first, then soonest first.
+/// Each word of the query has to match a word of the text or tag of the
+/// event, either by being part of it or by being close enough to it.
+pub(super) fn find_events(state: &AgendaState, now: &Instant, query: &[&str]) -> Vec<u64> {
+
+    let query: Vec<String> = query.iter().flat_map(|w| words(w)).collect();
+
+    if query.is_empty() {
+        return vec![];
+    }
+
+    let mut found: Vec<(usize, Option<Instant>, u64)> = state.events.iter()
+        .filter_map(|(id, event)| {
+            let distance = match_distance(&query, event)?;
+            Some((distance, event.get_next_active_occurence(now), *id))
+        })
+        .collect();
+
+    found.sort_by_key(|(distance, next, id)| (*distance, next.is_none(), *next, *id));
+
+    found.into_iter().map(|(_distance, _next, id)| id).collect()
+}
+
+/// Sum over the query words of the distance to their closest event word,
+/// None if one of them has no close enough match
+fn match_distance(query: &[String], event: &AgendaEvent) -> Option<usize> {
+
+    let event_words: Vec<String> = words(&event.text).into_iter()
+        .chain(event.tags.iter().flat_map(|tag| words(tag)))
+        .collect();
+
+    query.iter()
+        .map(|q| event_words.iter()
+            .map(|w| word_distance(q, w))
+            .min()
+            .filter(|distance| *distance <= max_typos(q)))
+        .sum()
+}
+
+/// How many typos a query word can have, more for longer words
+fn max_typos(word: &str) -> usize {
+    word.chars().count() / 4
+}
+
+fn word_distance(query_word: &str, word: &str) -> usize {
+    match word.contains(query_word) {
+        true => 0,
+        false => levenshtein(query_word, word)
+    }
+}
+
+/// Lowercase words, split on anything that is not a letter or a digit
+fn words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_owned())
+        .collect()
+}
+
+/// Number of single character insertions, removals or substitutions
+/// turning one word into the other
+fn levenshtein(a: &str, b: &str) -> usize {
+
+    let b: Vec<char> = b.chars().collect();
+    let mut prev_row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev_row[j] + usize::from(ca != *cb);
+            row.push(substitution.min(prev_row[j + 1] + 1).min(row[j] + 1));
+        }
+        prev_row = row;
+    }
+
+    prev_row[b.len()]
+}
@@ -0,0 +1,95 @@
+use super::{AgendaState, normalize_tag};
+
+/// One part of an event selection, as in "3", "3-7" or "tag:work". Several
+/// parts can be joined with commas, as in "1,4,9".
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Selector {
+    Id(u64),
+    /// Both ends included
+    Range(u64, u64),
+    Tag(String)
+}
+
+impl Selector {
+
+    /// Parses one command argument, None if it is not a selection
+    pub(super) fn parse_word(word: &str) -> Option<Vec<Selector>> {
+        word.split(',')
+            .filter(|part| !part.is_empty())
+            .map(Selector::parse_part)
+            .collect::<Option<Vec<Selector>>>()
+            .filter(|selectors| !selectors.is_empty())
+    }
+
+    fn parse_part(part: &str) -> Option<Selector> {
+
+        if let Some(tag) = part.strip_prefix("tag:") {
+            return Some(normalize_tag(tag))
+                .filter(|tag| !tag.is_empty())
+                .map(Selector::Tag);
+        }
+
+        match part.split_once('-') {
+            Some((start, end)) => {
+                let (start, end) = (start.parse().ok()?, end.parse().ok()?);
+                Some(Selector::Range(start, end)).filter(|_| start <= end)
+            },
+            None => part.parse().ok().map(Selector::Id)
+        }
+    }
+}
+
+/// Events picked by selectors
+#[derive(Debug, Default, PartialEq)]
+pub(super) struct Selection {
+    /// In the order they were given, ranges and tags in ascending order
+    pub(super) ids: Vec<u64>,
+    /// Numbers given one by one with no event behind them
+    pub(super) missing: Vec<u64>
+}
+
+impl Selection {
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.ids.is_empty() && self.missing.is_empty()
+    }
+}
+
+pub(super) fn select(state: &AgendaState, selectors: &[Selector]) -> Selection {
+
+    let mut selection = Selection::default();
+
+    let mut sorted_ids: Vec<u64> = state.events.keys().copied().collect();
+    sorted_ids.sort_unstable();
+
+    for selector in selectors {
+
+        let ids: Vec<u64> = match selector {
+            Selector::Id(id) => {
+                if !state.events.contains_key(id) {
+                    if !selection.missing.contains(id) {
+                        selection.missing.push(*id);
+                    }
+                    continue;
+                }
+                vec![*id]
+            },
+            Selector::Range(start, end) => sorted_ids.iter()
+                .filter(|id| (start..=end).contains(id))
+                .copied()
+                .collect(),
+            Selector::Tag(tag) => sorted_ids.iter()
+                .filter(|id| state.events[id].tags.contains(tag))
+                .copied()
+                .collect()
+        };
+
+        for id in ids {
+            if !selection.ids.contains(&id) {
+                selection.ids.push(id);
+            }
+        }
+    }
+
+    selection
+}
@@ -0,0 +1,727 @@
+#![allow(clippy::zero_prefixed_literal)]
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use chrono::{Duration, TimeZone};
+use clap::Clap;
+use crossbeam_channel::{unbounded, Receiver};
+use crate::{Opts, BotUpdate, Button};
+use super::{Agenda, Instant, SharedClock};
+use super::clock::Clock;
+use super::scheduler::Scheduler;
+
+const CHAT_ID: u32 = 1;
+
+/// Time standing still until the test moves it
+struct SimulatedClock(Mutex<Instant>);
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> Instant {
+        *self.0.lock().unwrap()
+    }
+}
+
+impl SimulatedClock {
+    fn set(&self, t: Instant) {
+        *self.0.lock().unwrap() = t;
+    }
+}
+
+/// An agenda and its scheduler, running on simulated time
+struct Harness {
+    agenda: Agenda,
+    scheduler: Scheduler,
+    clock: Arc<SimulatedClock>,
+    receiver: Receiver<BotUpdate>,
+    /// Buttons under the last message
+    buttons: Vec<Button>
+}
+
+impl Harness {
+
+    fn new(name: &str, now: Instant) -> Self {
+
+        let data_path = temp_data_path(name);
+        let opts = Opts::parse_from(["placeholder", data_path.to_str().unwrap()]);
+        let storage = crate::storage::open(&opts).unwrap();
+
+        let (sender, receiver) = unbounded();
+        let clock = Arc::new(SimulatedClock(Mutex::new(now)));
+        let shared_clock: SharedClock = clock.clone();
+
+        let agenda = Agenda::new(&opts, &sender, &storage, &shared_clock);
+        let scheduler = agenda.scheduler();
+
+        Harness { agenda, scheduler, clock, receiver, buttons: vec![] }
+    }
+
+    /// Sends a message as the user, returns the replies
+    fn send(&mut self, msg: &str) -> Vec<String> {
+        self.agenda.process(CHAT_ID, msg);
+        self.scheduler.step(false);
+        self.messages()
+    }
+
+    /// Presses the button with that label under the last message, returns the replies
+    fn press(&mut self, label: &str) -> Vec<String> {
+        let (_label, data) = self.buttons.iter()
+            .find(|(l, _data)| l == label)
+            .unwrap_or_else(|| panic!("no button {} in {:?}", label, self.buttons))
+            .clone();
+        self.agenda.process_callback(CHAT_ID, &data);
+        self.messages()
+    }
+
+    /// Moves time forward, waking the scheduler up at each due occurrence
+    /// like the real one does, returns the messages sent meanwhile
+    fn advance(&mut self, duration: Duration) -> Vec<String> {
+
+        let end = self.clock.now() + duration;
+
+        while let Some(t) = self.scheduler.next_due().filter(|t| *t <= end) {
+            self.clock.set(t);
+            self.scheduler.step(false);
+        }
+
+        self.clock.set(end);
+        self.scheduler.step(false);
+
+        self.messages()
+    }
+
+    /// Messages sent since the last call, with the reminders
+    fn messages(&mut self) -> Vec<String> {
+        let agenda = &self.agenda;
+        let buttons = &mut self.buttons;
+        self.receiver.try_iter()
+            .filter_map(|update| match update {
+                BotUpdate::MsgOut(msg) => {
+                    buttons.clear();
+                    Some(msg)
+                },
+                BotUpdate::MsgOutButtons(msg, msg_buttons) => {
+                    *buttons = msg_buttons.concat();
+                    Some(msg)
+                },
+                BotUpdate::EventOver(text) => Some(agenda.event_over_notification(&text)),
+                BotUpdate::Reminder(reminder) => {
+                    agenda.record_delivery(&reminder, Ok(()));
+                    *buttons = vec![reminder.ack_button(agenda.locale())];
+                    Some(reminder.notification(&agenda.chat_opts()))
+                },
+                _ => None
+            })
+            .collect()
+    }
+}
+
+fn temp_data_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("nag-agenda-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir(&path).unwrap();
+    path
+}
+
+#[test]
+fn one_shot_event() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+    let mut harness = Harness::new("one-shot", now);
+
+    assert_eq!(
+        harness.send("in 30 minutes check the oven"),
+        ["New event added (number 0): once on 1 January 2000 at 8:30.\nNext occurence in 30 minutes."]
+    );
+
+    assert!(harness.advance(Duration::minutes(29)).is_empty());
+    assert_eq!(harness.advance(Duration::minutes(1)), ["⏰ check the oven"]);
+    assert!(harness.advance(Duration::days(1)).is_empty());
+
+    assert_eq!(harness.send("/events"), ["No events"]);
+}
+
+#[test]
+fn seconds() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 45);
+    let mut harness = Harness::new("seconds", now);
+
+    harness.send("in 30 seconds tea");
+
+    assert!(harness.advance(Duration::seconds(29)).is_empty());
+    assert_eq!(harness.advance(Duration::seconds(1)), ["⏰ tea"]);
+}
+
+#[test]
+fn recurring_event() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+    let mut harness = Harness::new("recurring", now);
+
+    harness.send("every day at 9am standup");
+
+    assert_eq!(harness.advance(Duration::days(3)), ["⏰ standup\n🔁 every day at 9:00"; 3]);
+
+    let events = harness.send("/events").join("\n");
+    assert!(events.contains("standup"), "{}", events);
+}
+
+#[test]
+fn occurrence_count() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+    let mut harness = Harness::new("count", now);
+
+    harness.send("every day at 9am 2 times stretch");
+
+    assert_eq!(harness.advance(Duration::days(5)), ["⏰ stretch\n🔁 every day at 9:00"; 2]);
+    assert_eq!(harness.send("/events"), ["No events"]);
+}
+
+#[test]
+fn start_bound() {
+
+    // That date is a Saturday
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+    let mut harness = Harness::new("start", now);
+
+    harness.send("every day at 9:30 starting monday standup");
+
+    assert!(harness.advance(Duration::days(2) + Duration::minutes(89)).is_empty());
+    assert_eq!(first_lines(harness.advance(Duration::minutes(1))), ["⏰ standup"]);
+    assert!(harness.advance(Duration::minutes(1)).is_empty());
+    assert_eq!(first_lines(harness.advance(Duration::days(1))), ["⏰ standup"]);
+}
+
+#[test]
+fn history() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+    let mut harness = Harness::new("history", now);
+
+    assert_eq!(harness.send("/history"), ["No reminders sent yet"]);
+
+    harness.send("at 9am standup #work");
+    harness.send("at 10am groceries #home");
+
+    assert_eq!(harness.advance(Duration::hours(1)), ["⏰ standup"]);
+    harness.advance(Duration::minutes(30));
+    assert_eq!(harness.press("✅ Done"), ["Marked as done"]);
+    assert_eq!(harness.advance(Duration::minutes(30)), ["⏰ groceries"]);
+
+    assert_eq!(harness.send("/history"), [[
+        "<b>Sent reminders:</b>",
+        "<pre>  01/01/2000 09:00 - [0] standup",
+        "      sent 09:00:00, done at 01/01/2000 09:30</pre>",
+        "<pre>  01/01/2000 10:00 - [1] groceries",
+        "      sent 10:00:00</pre>"
+    ].join("\n")]);
+    assert_eq!(harness.send("/history #Work"), [[
+        "<b>Sent reminders tagged</b> <code>work</code>:",
+        "<pre>  01/01/2000 09:00 - [0] standup",
+        "      sent 09:00:00, done at 01/01/2000 09:30</pre>"
+    ].join("\n")]);
+    assert_eq!(harness.send("/history 1")[0].matches("<pre>").count(), 1);
+    assert_eq!(harness.send("/history errands"), ["No reminders sent yet"]);
+}
+
+#[test]
+fn changes_reschedule() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+    let mut harness = Harness::new("changes", now);
+
+    harness.send("at 9am water the plants");
+    harness.send("at 10am feed the cat");
+    harness.send("/del 0");
+    harness.send("/edit 1 at 8:30 feed the cat");
+
+    assert_eq!(harness.advance(Duration::hours(3)), ["⏰ feed the cat"]);
+
+    harness.send("tomorrow at 9am call mom");
+    harness.send("/undo");
+
+    assert!(harness.advance(Duration::days(2)).is_empty());
+}
+
+#[test]
+fn upcoming_occurences() {
+
+    // That date is a Saturday
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+    let mut harness = Harness::new("upcoming", now);
+
+    assert_eq!(harness.send("/next"), ["Nothing coming up"]);
+
+    harness.send("every day at 9am standup");
+    harness.send("tomorrow at 8:30 call mom");
+    harness.send("in 30 seconds tea");
+
+    assert_eq!(harness.send("/next 3"), [[
+        "<b>Coming up:</b>",
+        "<pre>  Saturday 01/01/2000 8:00:30 - [2] tea</pre>",
+        "<pre>  Saturday 01/01/2000 9:00 - [0] standup</pre>",
+        "<pre>  Sunday 02/01/2000 8:30 - [1] call mom</pre>"
+    ].join("\n")]);
+
+    assert_eq!(harness.send("/agenda tomorrow"), [[
+        "<b>Sunday 02/01</b>",
+        "<pre>  8:30 - [1] call mom</pre>",
+        "<pre>  9:00 - [0] standup</pre>"
+    ].join("\n")]);
+
+    harness.advance(Duration::hours(2));
+
+    let week = harness.send("/agenda week").join("\n");
+    assert!(!week.contains("Saturday"), "{}", week);
+    assert_eq!(week.matches("standup").count(), 6, "{}", week);
+    assert!(week.contains("<b>Friday 07/01</b>"), "{}", week);
+
+    assert_eq!(harness.send("/agenda today"), ["Nothing coming up"]);
+    assert_eq!(harness.send("/agenda someday"), ["Error: usage: /agenda today, tomorrow or week"]);
+}
+
+#[test]
+fn localized_arguments() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+    let mut harness = Harness::new("localized-arguments", now);
+
+    harness.send("tomorrow at 9am meeting #work");
+    harness.send("at 10am review #home");
+    harness.send("/language fr");
+
+    let tomorrow = harness.send("/agenda demain").join("\n");
+    assert!(tomorrow.contains("meeting") && !tomorrow.contains("review"), "{}", tomorrow);
+    assert_eq!(harness.send("/agenda tomorrow").join("\n"), tomorrow);
+
+    let by_tag = harness.send("/events par étiquette");
+    assert!(by_tag[0].starts_with("<b>Étiquette</b> <code>home</code>:\n<pre>"), "{}", by_tag[0]);
+    assert_eq!(harness.send("/events by tag"), by_tag);
+
+    assert_eq!(
+        harness.send("/events par date"),
+        ["Erreur : utilisation : /events [étiquette] [par prochain, numéro ou étiquette]"]
+    );
+    assert_eq!(
+        harness.send("/agenda hier"),
+        ["Erreur : utilisation : /agenda aujourd'hui, demain ou semaine"]
+    );
+}
+
+#[test]
+fn events_listing() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+    let mut harness = Harness::new("listing", now);
+
+    harness.send("every day at 9am standup");
+    harness.send("in 10 minutes tea");
+    harness.send("tomorrow at 8am run");
+    harness.send("/tag 2 sport");
+    harness.send("at 7pm gym");
+    harness.send("/tag 3 sport");
+    harness.send("at 6pm call mom");
+    harness.send("/tag 4 family");
+
+    assert_eq!(harness.send("/events"), [[
+        "<b>Tag</b> <code>family</code>: 1 events",
+        "<b>Tag</b> <code>sport</code>: 2 events",
+        "",
+        "<b>Untagged events:</b>",
+        "<pre>  once on 1 January 2000 at 8:10 - [1] tea</pre>",
+        "<pre>  every day at 9:00 - [0] standup</pre>"
+    ].join("\n")]);
+
+    let by_id = harness.send("/events by id").join("\n");
+    assert!(by_id.find("[0] standup") < by_id.find("[1] tea"), "{}", by_id);
+
+    assert_eq!(harness.send("/events by tag"), [[
+        "<b>Tag</b> <code>family</code>:",
+        "<pre>  once on 1 January 2000 at 18:00 - [4] call mom #family</pre>",
+        "<b>Tag</b> <code>sport</code>:",
+        "<pre>  once on 1 January 2000 at 19:00 - [3] gym #sport</pre>",
+        "<pre>  once on 2 January 2000 at 8:00 - [2] run #sport</pre>",
+        "",
+        "<b>Untagged events:</b>",
+        "<pre>  once on 1 January 2000 at 8:10 - [1] tea</pre>",
+        "<pre>  every day at 9:00 - [0] standup</pre>"
+    ].join("\n")]);
+
+    let sport = harness.send("/events sport by id").join("\n");
+    assert!(sport.find("[2] run") < sport.find("[3] gym"), "{}", sport);
+
+    assert_eq!(harness.send("/events by date"), ["Error: usage: /events [tag] [by next, id or tag]"]);
+}
+
+#[test]
+fn events_pages() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+    let mut harness = Harness::new("pages", now);
+
+    for i in 0..150 {
+        harness.send(&format!("every day at 9am task number {}", i));
+    }
+
+    let first = harness.send("/events");
+    assert_eq!(first.len(), 1);
+    assert!(first[0].chars().count() <= 4096);
+    assert!(first[0].ends_with("Page 1 of 3"), "{}", first[0]);
+    assert!(first[0].contains("[0] task number 0"), "{}", first[0]);
+
+    let second = harness.press("Next ▶️");
+    assert!(second[0].ends_with("Page 2 of 3"), "{}", second[0]);
+    assert!(!second[0].contains("[0] task number 0"), "{}", second[0]);
+
+    let third = harness.press("Next ▶️");
+    assert!(third[0].ends_with("Page 3 of 3"), "{}", third[0]);
+    assert!(third[0].contains("[149] task number 149"), "{}", third[0]);
+    assert_eq!(harness.buttons.len(), 1);
+
+    assert_eq!(harness.press("◀️ Previous"), second);
+}
+
+#[test]
+fn long_tag_buttons() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+    let mut harness = Harness::new("long-tag", now);
+
+    let tag = "quarterly-financial-reporting-and-compliance-review";
+    for i in 0..150 {
+        harness.send(&format!("every day at 9am task number {} #{}", i, tag));
+    }
+
+    let first = harness.send(&format!("/events {} -other", tag));
+    assert!(first[0].ends_with("Page 1 of 5"), "{}", first[0]);
+    assert!(harness.buttons.iter().all(|(_label, data)| data.len() <= 64), "{:?}", harness.buttons);
+
+    let second = harness.press("Next ▶️");
+    assert!(second[0].ends_with("Page 2 of 5"), "{}", second[0]);
+    assert!(!second[0].contains("[0] task number 0 "), "{}", second[0]);
+    assert_eq!(harness.press("◀️ Previous"), first);
+
+    harness.agenda.process_callback(CHAT_ID, "events:next:1:~42");
+    assert_eq!(harness.messages(), ["Error: this button has expired"]);
+
+    let long_tag = format!("{}-{}", tag, tag);
+    harness.send(&format!("/tag 0-4 {}", long_tag));
+    assert_eq!(
+        harness.send(&format!("/deltag {}", long_tag)),
+        [format!("5 events are tagged {}. Remove the tag from them, or delete them?", long_tag)]
+    );
+    assert!(harness.buttons.iter().all(|(_label, data)| data.len() <= 64), "{:?}", harness.buttons);
+    assert_eq!(
+        harness.press("🏷 Remove the tag"),
+        [format!("Removed tag {} from 5 events", long_tag)]
+    );
+}
+
+#[test]
+fn find_events() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+    let mut harness = Harness::new("find", now);
+
+    harness.send("every monday at 9am water the plants");
+    harness.send("on friday at 3pm Dentist appointment");
+    harness.send("tomorrow at 10am call the dentist back");
+    harness.send("at 6pm groceries");
+    harness.send("/tag 3 errands");
+
+    assert_eq!(harness.send("/find dentst"), [[
+        "<b>Matching events:</b>",
+        "<pre>  Sunday 02/01/2000 10:00 - [2] call the dentist back</pre>",
+        "<pre>  Friday 07/01/2000 15:00 - [1] dentist appointment</pre>"
+    ].join("\n")]);
+    assert_eq!(harness.buttons.len(), 4);
+
+    assert_eq!(harness.send("/find ERRAND").len(), 1);
+    assert!(harness.send("/find errands")[0].contains("[3] groceries"));
+    assert_eq!(harness.send("/find plant water")[0].matches("<pre>").count(), 1);
+    assert_eq!(harness.send("/find cat"), ["No events matching \"cat\""]);
+    assert_eq!(harness.send("/find"), ["Error: usage: /find &lt;words&gt;"]);
+
+    harness.send("/find dentist");
+    assert_eq!(
+        harness.press("✏️ Edit 1"),
+        ["To edit event 1, send <code>/edit 1 &lt;time&gt; &lt;message&gt;</code>\n\
+        It is now: once on 7 January 2000 at 15:00 - dentist appointment"]
+    );
+    harness.send("/find dentist");
+    assert_eq!(harness.press("🗑 Delete 2"), ["Removed event \"call the dentist back\""]);
+    assert!(!harness.send("/find dentist")[0].contains("[2]"));
+}
+
+#[test]
+fn multiple_tags() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+    let mut harness = Harness::new("tags", now);
+
+    harness.send("at 9am #work standup #Daily");
+    harness.send("at 10am review");
+    harness.send("/tag 1 work #urgent");
+    harness.send("at 6pm groceries #home");
+
+    assert_eq!(harness.send("/tags"), [[
+        "<b>Tags:</b>",
+        "<pre>  #daily - 1 events</pre>",
+        "<pre>  #home - 1 events</pre>",
+        "<pre>  #urgent - 1 events</pre>",
+        "<pre>  #work - 2 events</pre>"
+    ].join("\n")]);
+
+    assert_eq!(harness.send("/events work -urgent"), [[
+        "<b>Tag</b> <code>#work -#urgent</code>:",
+        "<pre>  once on 1 January 2000 at 9:00 - [0] standup #daily #work</pre>"
+    ].join("\n")]);
+    assert_eq!(harness.send("/events work urgent")[0].matches("<pre>").count(), 1);
+
+    harness.send("/untag 1 urgent");
+    harness.send("/edit 1 at 11am review #later");
+    assert!(harness.send("/events work")[0].contains("[1] review #later #work"));
+
+    assert_eq!(harness.send("/renametag work job"), ["Renamed tag work to job on 2 events"]);
+    assert_eq!(harness.send("/tags")[0].matches("#job - 2 events").count(), 1);
+
+    assert_eq!(
+        harness.send("/deltag job"),
+        ["2 events are tagged job. Remove the tag from them, or delete them?"]
+    );
+    assert_eq!(harness.press("🗑 Delete the events"), ["Deleted the events tagged job (2 events)"]);
+    assert_eq!(harness.press("↩️ Undo"), ["Undone: delete events tagged job"]);
+
+    harness.send("/deltag job");
+    assert_eq!(harness.press("🏷 Remove the tag"), ["Removed tag job from 2 events"]);
+    assert_eq!(harness.send("/events job"), ["Error: No events with tag \"#job\""]);
+}
+
+#[test]
+fn bulk_selection() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+    let mut harness = Harness::new("bulk", now);
+
+    for i in 0..10 {
+        harness.send(&format!("every day at 9am task {}", i));
+    }
+
+    assert_eq!(harness.send("/tag 1,4,9 work"), [[
+        "Tagged event \"task 1\" with \"#work\"",
+        "Tagged event \"task 4\" with \"#work\"",
+        "Tagged event \"task 9\" with \"#work\""
+    ].join("\n")]);
+
+    assert_eq!(harness.send("/del 3-5 tag:work 42"), [[
+        "Removed event \"task 3\"",
+        "Removed event \"task 4\"",
+        "Removed event \"task 5\"",
+        "Removed event \"task 1\"",
+        "Removed event \"task 9\"",
+        "Error: no event at number \"42\""
+    ].join("\n")]);
+
+    assert_eq!(harness.send("/undo"), [
+        "Undone: delete \"task 3\", \"task 4\", \"task 5\", \"task 1\", \"task 9\""
+    ]);
+
+    assert_eq!(harness.send("/untag 0-9 work")[0].lines().count(), 3);
+    assert_eq!(harness.send("/untag 0-9 work"), ["Error: none of these events is tagged work"]);
+
+    assert_eq!(harness.send("/edit 0-1 at 10am task"), ["Error: only one event can be edited at a time"]);
+    assert_eq!(harness.send("/del 7-3"), ["Error: invalid events \"7-3\""]);
+    assert_eq!(harness.send("/del tag:home"), ["Error: No events match \"tag:home\""]);
+    assert_eq!(harness.send("/del 20-30"), ["Error: No events match \"20-30\""]);
+}
+
+#[test]
+fn pause_resume() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+    let mut harness = Harness::new("pause", now);
+
+    harness.send("every day at 9am standup #work");
+    harness.send("every day at 10am review #work");
+    harness.send("in 2 hours tea");
+
+    assert_eq!(harness.send("/pause tag:work"), [[
+        "Paused event \"standup\"",
+        "Paused event \"review\""
+    ].join("\n")]);
+    harness.send("/pause 2");
+
+    let events = harness.send("/events work by id").join("\n");
+    assert!(events.contains("[0] standup #work ⏸ paused"), "{}", events);
+
+    assert!(harness.advance(Duration::days(1)).is_empty());
+
+    let resumed = harness.send("/resume 0-2").join("\n");
+    assert!(resumed.contains("Resumed event \"standup\", next occurence in"), "{}", resumed);
+    assert!(resumed.contains("Event \"tea\" was due while paused and has been removed"), "{}", resumed);
+    assert_eq!(harness.send("/resume 0"), ["Error: none of these events is paused"]);
+
+    assert_eq!(first_lines(harness.advance(Duration::hours(2))), ["⏰ standup", "⏰ review"]);
+
+    // Today is Sunday 2 January, the pause ends at the start of Tuesday
+    assert_eq!(harness.send("/pause 0 until 4 january"), [
+        "Paused event \"standup\" until 4 January 2000"
+    ]);
+    let events = harness.send("/events work").join("\n");
+    assert!(events.contains("[0] standup #work ⏸ paused until 4 January 2000"), "{}", events);
+
+    assert_eq!(
+        first_lines(harness.advance(Duration::days(2))),
+        ["⏰ review", "⏰ standup", "⏰ review"]
+    );
+
+    let events = harness.send("/events work").join("\n");
+    assert!(!events.contains('⏸'), "{}", events);
+
+    assert_eq!(harness.send("/pause 0 until yesterday"), [
+        "Error: usage: /pause &lt;events&gt; [until &lt;date&gt;], with a date in the future"
+    ]);
+    assert_eq!(harness.send("/resume"), ["Error: No event number supplied"]);
+}
+
+#[test]
+fn pause_past_last_occurence() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+    let mut harness = Harness::new("pause-over", now);
+
+    harness.send("in 2 hours tea");
+    harness.send("every day at 9am until 3 january stretch");
+    harness.send("every day at 9am standup");
+
+    harness.send("/pause 0 until 2 january");
+    harness.send("/pause 1 until 4 january");
+
+    assert_eq!(first_lines(harness.advance(Duration::hours(15))), ["⏰ standup"]);
+    assert_eq!(
+        harness.advance(Duration::hours(1)),
+        ["Event \"tea\" was due while paused and has been removed"]
+    );
+    assert_eq!(
+        first_lines(harness.advance(Duration::days(2))),
+        ["⏰ standup", "⏰ standup", "Event \"stretch\" was due while paused and has been removed"]
+    );
+
+    let events = harness.send("/events").join("\n");
+    assert!(!events.contains("tea") && !events.contains("stretch"), "{}", events);
+}
+
+/// Reminders without the recurrence under them
+fn first_lines(messages: Vec<String>) -> Vec<String> {
+    messages.iter().map(|msg| msg.lines().next().unwrap_or("").to_owned()).collect()
+}
+
+#[test]
+fn undo_and_edit() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+    let mut harness = Harness::new("undo", now);
+
+    assert_eq!(harness.send("/undo"), ["Error: nothing to undo"]);
+
+    harness.send("every day at 9am standup");
+    assert_eq!(harness.send("/edit 0 every day at 10am standup"), [
+        "Edited event 0: every day at 10:00.\nNext occurence in 2 hours 0 minutes."
+    ]);
+    assert_eq!(harness.send("/undo"), ["Undone: edit event \"standup\""]);
+    assert!(harness.send("/events")[0].contains("every day at 9:00 - [0] standup"));
+
+    // The Undo button only undoes the change it came with
+    harness.send("/del 0");
+    let undo_delete = harness.buttons.clone();
+    harness.send("at 8pm tea");
+    harness.buttons = undo_delete.clone();
+    assert_eq!(
+        harness.press("↩️ Undo"),
+        ["Error: this is not the last change anymore, use /undo instead"]
+    );
+    assert_eq!(harness.send("/undo"), ["Undone: add event \"tea\""]);
+    harness.buttons = undo_delete;
+    assert_eq!(harness.press("↩️ Undo"), ["Undone: delete \"standup\""]);
+    assert_eq!(first_lines(harness.advance(Duration::hours(2))), ["⏰ standup"]);
+}
+
+#[test]
+fn undo_after_firing() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+    let mut harness = Harness::new("undo-fired", now);
+
+    harness.send("at 8:30 tea");
+    harness.send("every day at 9am 5 times standup");
+    harness.send("/edit 1 every day at 10am 5 times standup");
+
+    assert_eq!(
+        first_lines(harness.advance(Duration::days(1) + Duration::hours(2))),
+        ["⏰ tea", "⏰ standup", "⏰ standup"]
+    );
+
+    // Restoring the event as it was before the edit would bring back
+    // the occurrences that have fired since
+    assert_eq!(harness.send("/undo"), [
+        "Error: cannot undo edit event \"standup\", reminders of these events were sent since"
+    ]);
+    assert_eq!(harness.advance(Duration::days(7)).len(), 3);
+
+    // Neither can the creation of the events that fired
+    assert_eq!(harness.send("/undo"), [
+        "Error: cannot undo add event \"standup\", reminders of these events were sent since"
+    ]);
+    assert_eq!(harness.send("/undo"), [
+        "Error: cannot undo add event \"tea\", reminders of these events were sent since"
+    ]);
+    assert_eq!(harness.send("/undo"), ["Error: nothing to undo"]);
+}
+
+#[test]
+fn confirmations() {
+
+    // That date is a Saturday
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+    let mut harness = Harness::new("confirm", now);
+
+    // 3/4 is the 3rd of April, or March 4th
+    assert_eq!(harness.send("on 3/4 at 9am dentist"), [[
+        "This date can be read in more than one way.",
+        "Save \"dentist\" for:",
+        "  once on 3 April 2000 at 9:00 (next on Mon 03/04/2000 09:00)",
+        "  once on 4 March 2000 at 9:00 (next on Sat 04/03/2000 09:00)"
+    ].join("\n")]);
+    let labels: Vec<&str> = harness.buttons.iter().map(|(label, _data)| label.as_str()).collect();
+    assert_eq!(labels, ["✅ Mon 03/04/2000 09:00", "✅ Sat 04/03/2000 09:00", "✖️ Cancel"]);
+
+    let confirmed = harness.press("✅ Sat 04/03/2000 09:00");
+    assert!(confirmed[0].starts_with("New event added (number 0): once on 4 March 2000 at 9:00."), "{:?}", confirmed);
+
+    // A defaulted time is only saved once confirmed
+    assert_eq!(harness.send("on friday call mom"), [[
+        "Defaulting time to 10:00am",
+        "Save \"call mom\" for:",
+        "  once on 7 January 2000 at 10:00 (next on Fri 07/01/2000 10:00)"
+    ].join("\n")]);
+    let defaulted = harness.buttons.clone();
+    assert_eq!(harness.press("✖️ Cancel"), ["Cancelled"]);
+    harness.buttons = defaulted;
+    assert_eq!(harness.press("✅ Fri 07/01/2000 10:00"), ["Error: this confirmation has expired"]);
+
+    // Only the buttons of the latest question still work
+    harness.send("on 5/6 at 9am haircut");
+    let replaced = harness.buttons.clone();
+    harness.send("on 7/8 at 9am haircut");
+    let latest = harness.buttons.clone();
+    harness.buttons = replaced;
+    assert_eq!(harness.press("✅ Mon 05/06/2000 09:00"), ["Error: this confirmation has expired"]);
+    harness.buttons = latest;
+    assert!(harness.press("✅ Mon 07/08/2000 09:00")[0].starts_with("New event added (number 1)"));
+
+    let events = harness.send("/events by id").join("\n");
+    assert!(events.contains("[0] dentist"), "{}", events);
+    assert!(events.contains("once on 7 August 2000 at 9:00 - [1] haircut"), "{}", events);
+    assert!(!events.contains("call mom"), "{}", events);
+}
@@ -3,20 +3,22 @@ use std::collections::{HashSet, HashMap};
 use chrono::DateTime;
 use anyhow::bail;
 use log::debug;
+use crate::locale::{Locale, Msg, fill};
 use super::super::cron::{CronValue, CronColumn, Cronline, CRON_COLUMNS};
 
-const DEFAULT_TIME: (u64, u64) = (10, 0);
+pub(crate) const DEFAULT_TIME: (u64, u64) = (10, 0);
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(super) struct CronlineBuilder {
-    pub(super) map: HashMap<CronColumn, CronValue>
+    pub(super) map: HashMap<CronColumn, CronValue>,
+    second: Option<u64>
 }
 
 
 impl CronlineBuilder {
 
     pub fn new() -> Self {
-        CronlineBuilder { map: HashMap::new() }
+        CronlineBuilder { map: HashMap::new(), second: None }
     }
 
     pub fn set(&mut self, col: CronColumn, val: CronValue) -> anyhow::Result<()> {
@@ -27,7 +29,15 @@ impl CronlineBuilder {
         }
     }
 
-    pub fn autofill(&mut self, now: &DateTime<chrono::offset::Local>) -> Option<String> {
+    pub fn set_second(&mut self, second: u64) -> anyhow::Result<()> {
+        debug!("Setting second to {}", second);
+        match self.second.replace(second) {
+            None => Ok(()),
+            Some(_) => bail!("second already specified")
+        }
+    }
+
+    pub fn autofill(&mut self, now: &DateTime<chrono::offset::Local>, locale: Locale) -> Option<String> {
 
         debug!("Autofilling cronline: {:?}", self.map);
     
@@ -40,7 +50,7 @@ impl CronlineBuilder {
     
                 debug!("Column {:?}, wildcard={}", col, wildcard_fill_state);
     
-                match self.map.get(&col).copied() {
+                match self.map.get(col).copied() {
         
                     None if wildcard_fill_state => { self.map.insert(*col, CronValue::Every); },
                     None => (),
@@ -67,7 +77,7 @@ impl CronlineBuilder {
 
                 debug!("Column {:?}, fixed={}", col, fixed_fill_state);
                 
-                match self.map.get(&col).copied() {
+                match self.map.get(col).copied() {
         
                     None if fixed_fill_state => { self.map.insert(*col, cronline_now.get(*col)); },
                     None => (),
@@ -94,10 +104,8 @@ impl CronlineBuilder {
 
             let (h_12, am_pm) = to_am_pm_format(def_h);
 
-            comment = Some(format!(
-                "Defaulting time to {}:{:02}{}",
-                h_12, def_m, am_pm
-            ));
+            let time = format!("{}:{:02}{}", h_12, def_m, am_pm);
+            comment = Some(fill(locale.tr(Msg::DefaultingTime), &[&time]));
         }
 
         debug!("Filled cronline: {:?}", self.map);
@@ -136,7 +144,7 @@ impl CronlineBuilder {
                 // and accounted for
         };
         
-        Ok(Cronline::from_values(line))
+        Ok(Cronline::from_values(line).with_second(self.second.unwrap_or(0)))
     }
 }
 
@@ -0,0 +1,160 @@
+#![allow(clippy::zero_prefixed_literal)]
+
+use chrono::TimeZone;
+use clap::Clap;
+use crate::Opts;
+
+use super::parse_cronline;
+
+// Each expression must be understood like its English counterpart
+
+#[test]
+fn french() {
+    check_translations("fr", &[
+        ("à 9h test1", "at 9:00 test1"),
+        ("à 9h30 test1", "at 9:30 test1"),
+        ("à 17 heures test1", "at 17:00 test1"),
+        ("demain à 18h test1", "tomorrow at 18:00 test1"),
+        ("après-demain à midi test1", "the day after tomorrow at noon test1"),
+        ("ce soir test1", "this evening test1"),
+        ("tous les matins test1", "every morning test1"),
+        ("dans 2 heures test1", "in 2 hours test1"),
+        ("dans une demi-heure test1", "in half an hour test1"),
+        ("dans une heure et demie test1", "in an hour and a half test1"),
+        ("dans dix-sept minutes test1", "in seventeen minutes test1"),
+        ("dans vingt-cinq minutes test1", "in twenty-five minutes test1"),
+        ("dans 30 secondes test1", "in 30 seconds test1"),
+        ("dans 2 semaines vendredi test1", "in 2 weeks on friday test1"),
+        ("le 5 septembre à 8h test1", "on the 5th september at 8:00 test1"),
+        ("le 1er mai test1", "on the 1st may test1"),
+        ("le premier mai test1", "on the first may test1"),
+        ("le vingt-trois sept. test1", "on the 23rd sep test1"),
+        ("jeu à 8h test1", "on thu at 8:00 test1"),
+        ("le 21.08.24 test1", "on 21/08/2024 test1"),
+        ("tous les ans le 4 avril test1", "every year on the 4th april test1"),
+        ("lundi prochain à 8h test1", "next monday at 8:00 test1"),
+        ("mardi à 8h test1", "on tuesday at 8:00 test1"),
+        ("la semaine prochaine test1", "next week test1"),
+        ("le 21/08 test1", "on 21/08 test1"),
+        ("en 2001 le 05/09 test1", "in 2001 on 05/09 test1"),
+        ("test1 test2 demain à 18h", "test1 test2 tomorrow at 18:00"),
+        ("tous les jours à 8h jusqu'au 10 mars test1", "every day at 8:00 until march 10 test1"),
+        ("tous les jours à 8h pendant 2 semaines test1", "every day at 8:00 for 2 weeks test1"),
+        ("tous les jours à 8h 5 fois test1", "every day at 8:00 5 times test1"),
+        ("à partir de lundi prochain, tous les jours à 9h test1", "starting next monday, every day at 9:00 test1"),
+    ]);
+}
+
+#[test]
+fn german() {
+    check_translations("de", &[
+        ("um 9 uhr test1", "at 9:00 test1"),
+        ("um 9:30 test1", "at 9:30 test1"),
+        ("morgen um 18 uhr test1", "tomorrow at 18:00 test1"),
+        ("übermorgen mittags test1", "the day after tomorrow at noon test1"),
+        ("morgen früh test1", "tomorrow morning test1"),
+        ("heute abend test1", "this evening test1"),
+        ("jeden morgen test1", "every morning test1"),
+        ("in 2 stunden test1", "in 2 hours test1"),
+        ("in einer halben stunde test1", "in half an hour test1"),
+        ("in anderthalb stunden test1", "in an hour and a half test1"),
+        ("in zwanzig minuten test1", "in twenty minutes test1"),
+        ("in 30 sekunden test1", "in 30 seconds test1"),
+        ("am 5. september um 8 uhr test1", "on the 5th september at 8:00 test1"),
+        ("am ersten mai test1", "on the first may test1"),
+        ("am 3. okt. test1", "on the 3rd oct test1"),
+        ("am do. um 8 uhr test1", "on thu at 8:00 test1"),
+        ("am 21.08.2024 test1", "on 21/08/2024 test1"),
+        ("jedes jahr am 4. april test1", "every year on the 4th april test1"),
+        ("nächsten montag um 8 uhr test1", "next monday at 8:00 test1"),
+        ("am dienstag um 8 uhr test1", "on tuesday at 8:00 test1"),
+        ("nächste woche test1", "next week test1"),
+        ("am 21/08 test1", "on 21/08 test1"),
+        ("test1 test2 morgen um 18 uhr", "test1 test2 tomorrow at 18:00"),
+        ("jeden tag um 8 uhr bis zum 10. märz test1", "every day at 8:00 until march 10 test1"),
+        ("jeden tag um 8 uhr für 2 wochen test1", "every day at 8:00 for 2 weeks test1"),
+        ("jeden tag um 8 uhr fünf mal test1", "every day at 8:00 5 times test1"),
+        ("ab nächsten montag jeden tag um 9 uhr test1", "starting next monday every day at 9:00 test1"),
+    ]);
+}
+
+#[test]
+fn spanish() {
+    check_translations("es", &[
+        ("a las 9:00 test1", "at 9:00 test1"),
+        ("mañana a las 18:00 test1", "tomorrow at 18:00 test1"),
+        ("pasado mañana al mediodía test1", "the day after tomorrow at noon test1"),
+        ("mañana por la mañana test1", "tomorrow morning test1"),
+        ("esta tarde test1", "this afternoon test1"),
+        ("cada noche test1", "every night test1"),
+        ("en 2 horas test1", "in 2 hours test1"),
+        ("dentro de media hora test1", "in half an hour test1"),
+        ("en una hora y media test1", "in an hour and a half test1"),
+        ("en quince minutos test1", "in fifteen minutes test1"),
+        ("en 30 segundos test1", "in 30 seconds test1"),
+        ("el 5 de septiembre a las 8:00 test1", "on the 5th september at 8:00 test1"),
+        ("el primero de mayo test1", "on the first may test1"),
+        ("el treinta y uno de dic test1", "on the 31st dec test1"),
+        ("el jue a las 8:00 test1", "on thu at 8:00 test1"),
+        ("el 2024-08-21 test1", "on 21/08/2024 test1"),
+        ("cada año el 4 de abril test1", "every year on the 4th april test1"),
+        ("el próximo lunes a las 8:00 test1", "next monday at 8:00 test1"),
+        ("el martes a las 8:00 test1", "on tuesday at 8:00 test1"),
+        ("la semana que viene test1", "next week test1"),
+        ("el 21/08 test1", "on 21/08 test1"),
+        ("test1 test2 mañana a las 18:00", "test1 test2 tomorrow at 18:00"),
+        ("cada día a las 8:00 hasta el 10 de marzo test1", "every day at 8:00 until march 10 test1"),
+        ("cada día a las 8:00 durante 2 semanas test1", "every day at 8:00 for 2 weeks test1"),
+        ("cada día a las 8:00 5 veces test1", "every day at 8:00 5 times test1"),
+        ("a partir del próximo lunes, cada día a las 9:00 test1", "starting next monday, every day at 9:00 test1"),
+    ]);
+}
+
+#[test]
+fn localized_comment() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+    let opts = make_opts("fr");
+
+    let words = ["demain", "test1"];
+    let res = parse_cronline(&opts, &now, &words).unwrap();
+
+    assert_eq!(res.comment.as_deref(), Some("Heure par défaut : 10:00am"));
+}
+
+#[test]
+fn no_english_fallback() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+    let opts = make_opts("de");
+
+    let words = ["tomorrow", "test1"];
+    assert!(parse_cronline(&opts, &now, &words).is_err());
+}
+
+fn make_opts(locale: &str) -> Opts {
+    Opts::parse_from(["placeholder", "placeholder", "--locale", locale])
+}
+
+fn check_translations(locale: &str, cases: &[(&str, &str)]) {
+
+    // That date is a Saturday
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+
+    let opts = make_opts(locale);
+    let en_opts = make_opts("en");
+
+    for (msg, en_msg) in cases.iter() {
+
+        let words: Vec<&str> = msg.split_whitespace().collect();
+        let en_words: Vec<&str> = en_msg.split_whitespace().collect();
+
+        let res = parse_cronline(&opts, &now, &words)
+            .unwrap_or_else(|err| panic!("cannot parse \"{}\": {}", msg, err));
+        let en_res = parse_cronline(&en_opts, &now, &en_words).unwrap();
+
+        assert_eq!(res.cronline, en_res.cronline, "\"{}\"", msg);
+        assert_eq!(res.remaining_words, en_res.remaining_words, "\"{}\"", msg);
+        assert_eq!(res.bounds, en_res.bounds, "\"{}\"", msg);
+    }
+}
@@ -1,75 +1,108 @@
-use chrono::DateTime;
+use chrono::{Date, DateTime};
 use log::debug;
 
 mod cronline_builder;
 mod parsers;
 #[cfg(test)]
 mod tests;
+#[cfg(test)]
+mod locale_tests;
 
 use crate::Opts;
 use super::Instant;
 use super::cron::{CronColumn, CronValue, Cronline};
+use super::event::{Bounds, next_cron_occurence};
 use cronline_builder::CronlineBuilder;
+pub(super) use cronline_builder::DEFAULT_TIME;
+pub(super) use parsers::parse_until_date;
 
 #[derive(Debug, PartialEq)]
 pub(super) struct CronlineResult<'a> { 
     pub cronline: Cronline,
-    pub remaining_words: &'a[&'a str],
-    pub comment: Option<String>
+    pub remaining_words: Vec<&'a str>,
+    pub comment: Option<String>,
+    pub steps: Vec<ParseStep<'a>>,
+    pub bounds: Bounds
+}
+
+/// Words consumed by one of the parsers, for diagnostics
+#[derive(Debug, PartialEq)]
+pub(super) struct ParseStep<'a> {
+    pub parser: &'static str,
+    pub words: &'a[&'a str]
 }
 
 #[derive(Debug)]
 struct ParseUpdate<'a> { 
     cron_updates: Vec<(CronColumn, CronValue)>,
-    remaining_words: &'a[&'a str]
+    remaining_words: &'a[&'a str],
+    bound: Option<Bound>,
+    /// Second of the minute, when it matters
+    second: Option<u64>
+}
+
+/// Limit put on a recurring event by a qualifier
+#[derive(Debug, Clone, Copy)]
+enum Bound {
+    /// "starting next monday"
+    Start(Instant),
+    /// "until march 10", "for 2 weeks"
+    End(Instant),
+    /// "5 times"
+    Count(u64)
+}
+
+impl Bound {
+    fn apply(self, bounds: &mut Bounds) -> anyhow::Result<()> {
+        match self {
+            Bound::Start(_) if bounds.start.is_some() => anyhow::bail!("start already specified"),
+            Bound::End(_) if bounds.end.is_some() => anyhow::bail!("end already specified"),
+            Bound::Count(_) if bounds.remaining.is_some() => anyhow::bail!("count already specified"),
+            Bound::Start(start) => bounds.start = Some(start),
+            Bound::End(end) => bounds.end = Some(end),
+            Bound::Count(count) => bounds.remaining = Some(count)
+        }
+        Ok(())
+    }
 }
 
 pub(super) fn parse_cronline<'a>(
     opts: &'a Opts, now: &DateTime<chrono::Local>, words: &'a [&'a str]
 ) -> anyhow::Result<CronlineResult<'a>> {
 
-    let mut state = ParsingState::new(opts, words, now.clone());
-
-    loop {
+    let mut state = ParsingState::new(opts, words, *now);
 
-        let parse_update = parsers::parse(&state);
-
-        debug!("Parse update? {}", parse_update.is_some());
-
-        if let Some(parse_update) = parse_update {
-
-            debug!(
-                "Parse update: cron={:?} rem_words={:?}",
-                parse_update.cron_updates, parse_update.remaining_words
-            );
-
-            let ParseUpdate { cron_updates, remaining_words } = parse_update;
-            for (col, val) in cron_updates.into_iter() {
-                state.update(col, val)?;
-            }
-            state.remaining_words = remaining_words;
+    // Fast path: the message starts with the time expression
+    if state.parse_expressions(false)? > 0 {
+        state.text_words = state.remaining_words.to_vec();
+        return state.finalize(now);
+    }
 
-            if state.remaining_words.is_empty() {
-                break;
-            }
+    // Otherwise look for time expressions anywhere in the message
+    debug!("No time prefix, scanning the whole message");
 
-        } else {
-            break;
+    while let Some((&word, rem_words)) = state.remaining_words.split_first() {
+        if !parsers::starts_with_marker(&state) || state.parse_expressions(true)? == 0 {
+            state.text_words.push(word);
+            state.remaining_words = rem_words;
         }
-
-        debug!(
-            "Parse state: cron={:?} rem_words={:?}",
-            state.cronline_builder.map, state.remaining_words
-        );
     }
 
-    state.finalize(&now)
+    state.finalize(now)
 }
 
 #[derive(Debug)]
 struct ParsingState<'a> {
     remaining_words: &'a[&'a str],
+    text_words: Vec<&'a str>,
+    steps: Vec<ParseStep<'a>>,
     cronline_builder: CronlineBuilder,
+    bounds: Bounds,
+    /// Only dates are parsed, for the date of a "starting" or "until" qualifier
+    dates_only: bool,
+    /// The last expression of the current run gave the day, as "tomorrow"
+    /// in "tomorrow morning"
+    after_day: bool,
     now: DateTime<chrono::Local>,
     opts: &'a Opts
 }
@@ -80,26 +113,114 @@ impl<'a> ParsingState<'a> {
     fn new(opts: &'a Opts, words: &'a[&'a str], now: DateTime<chrono::Local>) -> Self {
         ParsingState {
             remaining_words: words,
+            text_words: vec![],
+            steps: vec![],
             cronline_builder: CronlineBuilder::new(),
+            bounds: Bounds::default(),
+            dates_only: false,
+            after_day: false,
             now,
             opts
         }
     }
 
-    fn update(&mut self, col: CronColumn, val: CronValue) -> anyhow::Result<()> {
-        self.cronline_builder.set(col, val)
+    /// Consumes consecutive time expressions from the front of the remaining
+    /// words, returns how many words were consumed. When `lenient`, an
+    /// expression conflicting with what's already parsed ends the run instead
+    /// of failing, so that it can be kept as text.
+    fn parse_expressions(&mut self, lenient: bool) -> anyhow::Result<usize> {
+
+        let nb_words = self.remaining_words.len();
+        self.after_day = false;
+
+        while let Some((parser, parse_update)) = parsers::parse(self) {
+
+            debug!(
+                "Parse update ({}): cron={:?} rem_words={:?}",
+                parser, parse_update.cron_updates, parse_update.remaining_words
+            );
+
+            let ParseUpdate { cron_updates, remaining_words, bound, second } = parse_update;
+
+            // "until friday at 8am": the time of day and the recurrence are for the event
+            if self.dates_only && cron_updates.iter().any(|(col, val)| {
+                *col == CronColumn::Hour || *col == CronColumn::Minute || *val == CronValue::Every
+            }) {
+                break;
+            }
+
+            let gives_day = cron_updates.iter().any(|(col, val)| {
+                *col == CronColumn::Day && *val != CronValue::Every
+            });
+
+            let mut builder = self.cronline_builder.clone();
+            let mut bounds = self.bounds.clone();
+            let res = cron_updates.into_iter()
+                .try_for_each(|(col, val)| builder.set(col, val))
+                .and_then(|()| second.map_or(Ok(()), |second| builder.set_second(second)))
+                .and_then(|()| bound.map_or(Ok(()), |bound| bound.apply(&mut bounds)));
+
+            match res {
+                Err(err) if lenient => {
+                    debug!("Ignoring {} expression: {}", parser, err);
+                    break;
+                },
+                Err(err) => return Err(err),
+                Ok(()) => {
+                    self.cronline_builder = builder;
+                    self.bounds = bounds;
+                    self.after_day = gives_day;
+                }
+            }
+
+            let nb_consumed = self.remaining_words.len() - remaining_words.len();
+            self.steps.push(ParseStep {
+                parser,
+                words: &self.remaining_words[..nb_consumed]
+            });
+            self.remaining_words = remaining_words;
+
+            // "starting monday, every day": the comma ends the date
+            if self.dates_only && self.steps.last()
+                .and_then(|step| step.words.last())
+                .is_some_and(|word| word.ends_with(',')) {
+                break;
+            }
+
+            debug!(
+                "Parse state: cron={:?} rem_words={:?}",
+                self.cronline_builder.map, self.remaining_words
+            );
+        }
+
+        Ok(nb_words - self.remaining_words.len())
+    }
+
+    /// Next day matching the date parsed so far, today included
+    fn parsed_date(mut self) -> Option<Date<chrono::Local>> {
+
+        self.cronline_builder.set(CronColumn::Hour, CronValue::On(23)).ok()?;
+        self.cronline_builder.set(CronColumn::Minute, CronValue::On(59)).ok()?;
+        self.cronline_builder.autofill(&self.now, self.opts.locale);
+
+        let cronline = self.cronline_builder.build().ok()?;
+        let last_minute = next_cron_occurence(&cronline, &self.now)?;
+
+        Some(last_minute.date())
     }
 
     fn finalize(mut self, now: &Instant) -> anyhow::Result<CronlineResult<'a>> {
 
-        let comment = self.cronline_builder.autofill(now);
+        let comment = self.cronline_builder.autofill(now, self.opts.locale);
 
         let cronline = self.cronline_builder.build()?;
 
         let result = CronlineResult {
             cronline,
-            remaining_words: self.remaining_words,
-            comment
+            remaining_words: self.text_words,
+            comment,
+            steps: self.steps,
+            bounds: self.bounds
         };
 
         Ok(result)
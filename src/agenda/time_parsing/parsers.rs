@@ -1,63 +1,132 @@
+use std::collections::HashMap;
 use std::convert::TryInto;
+use std::sync::Mutex;
 use log::debug;
+use once_cell::sync::Lazy;
 use regex::Regex;
-use chrono::{DateTime, Datelike, Duration, Timelike};
-use crate::DateFormat;
-use super::super::cron::{CronColumn, CronValue, CRON_COLUMNS};
-use super::{ParsingState, ParseUpdate};
-
-pub(super) fn parse<'a, 'b>(state: &'b ParsingState<'a>) -> Option<ParseUpdate<'a>> where 'a: 'b {
-
-    let parsers: Vec<&ParserFunc> = vec![
-        &try_parse_day,
-        &try_parse_month,
-        &try_parse_clocktime,
-        &try_parse_duration,
-        &try_parse_year,
-        &try_parse_every,
-        &try_parse_date_digits,
-        &try_parse_relative,
-        &try_parse_weekday,
+use chrono::{Date, DateTime, Datelike, Duration, NaiveDate, TimeZone, Timelike};
+use crate::{DateFormat, Opts};
+use crate::locale::{Vocabulary, Period, RelativeDate};
+use super::super::cron::{CronColumn, CronValue};
+use super::{ParsingState, ParseUpdate, Bound};
+
+/// Returns the name of the parser that matched along with its update
+pub(super) fn parse<'a, 'b>(state: &'b ParsingState<'a>) -> Option<(&'static str, ParseUpdate<'a>)> where 'a: 'b {
+
+    let parsers: Vec<(&'static str, &ParserFunc)> = vec![
+        ("day", &try_parse_day),
+        ("month", &try_parse_month),
+        ("clock time", &try_parse_clocktime),
+        ("time of day", &try_parse_time_of_day),
+        ("duration", &try_parse_duration),
+        ("year", &try_parse_year),
+        ("every", &try_parse_every),
+        ("date digits", &try_parse_date_digits),
+        ("iso date", &try_parse_iso_date),
+        ("relative", &try_parse_relative),
+        ("weekday", &try_parse_weekday),
+        ("starting", &try_parse_starting),
+        ("until", &try_parse_until),
+        ("for", &try_parse_for),
+        ("times", &try_parse_times),
     ];
 
     parsers
         .iter()
-        .find_map(|func| func(state))
+        .find_map(|(name, func)| func(state).map(|update| (*name, update)))
 }
 
 
 type ParserFunc<'a, 'b> = dyn Fn(&'b ParsingState<'a>) -> Option<ParseUpdate<'a>>;
 
-fn try_parse_day<'a, 'b>(state: &'b ParsingState<'a>) -> Option<ParseUpdate<'a>> {
+/// Whether the remaining words start the way a time expression in the
+/// middle of a message has to, with a number or a word such as "at", "on",
+/// "in", "every" or "tomorrow". Names of days and months, ordinal words and
+/// times of day are too common in text to be taken on their own.
+pub(super) fn starts_with_marker(state: &ParsingState) -> bool {
+
+    let vocab = state.opts.locale.vocabulary();
+    let words = state.remaining_words;
+
+    let starts_with_digit = words.first()
+        .and_then(|word| word.chars().next())
+        .is_some_and(|c| c.is_ascii_digit());
+
+    let markers = [
+        vocab.clock_prefixes,
+        vocab.date_prefixes,
+        vocab.duration_prefixes,
+        vocab.year_prefixes,
+        vocab.every,
+        vocab.tonight,
+        vocab.today_period_prefixes,
+        vocab.next_weekday_prefixes,
+        vocab.starting,
+        vocab.until,
+        vocab.during
+    ];
 
-    let (&word, remaining_words, has_prep) = match state.remaining_words {
-        ["on", "the", word, rem_words @ ..] => (word, rem_words, true),
-        ["the", word, rem_words @ ..] => (word, rem_words, true),
-        [word, rem_words @ ..] => (word, rem_words, false),
-        _ => return  None
-    };
+    starts_with_digit
+        || markers.iter().any(|phrases| strip_phrase(words, phrases).is_some())
+        || find_phrase(words, vocab.relative_dates).is_some()
+}
+
+/// Regexes built from the vocabulary, compiled once since the parsers are
+/// tried on every word of a message
+fn vocab_regex(pattern: &str) -> Regex {
+
+    static CACHE: Lazy<Mutex<HashMap<String, Regex>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+    let mut cache = CACHE.lock().unwrap();
+    if let Some(reg) = cache.get(pattern) {
+        return reg.clone();
+    }
+    let reg = Regex::new(pattern).unwrap();
+    cache.insert(pattern.to_owned(), reg.clone());
+    reg
+}
 
-    let reg = Regex::new(r"^([0-9]{1,2})((st)|(nd)|(rd)|(th))?$").unwrap();
+fn try_parse_day<'a>(state: &ParsingState<'a>) -> Option<ParseUpdate<'a>> {
+
+    let vocab = state.opts.locale.vocabulary();
+
+    let (words, has_prep) = strip_optional(state.remaining_words, vocab.day_prefixes);
+    let (&word, remaining_words) = words.split_first()?;
+
+    let reg = vocab_regex(&format!(r"^([0-9]{{1,2}})({})?$", vocab.ordinal_suffixes));
     let word = word.to_lowercase();
-    let captures = reg.captures(word.as_str())?;
 
-    let day: u64 = captures
-        .get(1)?
-        .as_str()
-        .parse()
-        .ok()?;
+    let (day, has_suffix, remaining_words) = match reg.captures(word.as_str()) {
+        Some(captures) => {
+            let day: u64 = captures.get(1)?.as_str().parse().ok()?;
+            (day, captures.get(2).is_some(), remaining_words)
+        },
+        // "the first", "the twenty-third"
+        None => {
+            let table: Vec<(&str, u64)> = vocab.ordinal_days.iter()
+                .enumerate()
+                .map(|(position, &name)| (name, position as u64 + 1))
+                .collect();
+            let (day, rem_words) = find_phrase(words, &table)?;
+            (day, false, rem_words)
+        }
+    };
 
-    let has_suffix = captures.get(2).is_some();
+    // "10 march", "10 de marzo"
+    let (month_words, _) = strip_optional(remaining_words, vocab.month_prefixes);
+    let has_month = find_name(month_words, &vocab.months).is_some();
 
     // Make sure we have at least some indication that the
-    // numbers represents a day
-    if !(has_prep || has_suffix) {
+    // numbers represents a day, ordinal words are too common on their own
+    if !(has_prep || has_suffix || (has_month && is_day_digits(&word))) {
         return None;
     }
 
     let update = ParseUpdate {
         cron_updates: vec![(CronColumn::Day, CronValue::On(day))],
-        remaining_words
+        remaining_words,
+        bound: None,
+        second: None
     };
 
     debug!("Parsed: day");
@@ -65,42 +134,38 @@ fn try_parse_day<'a, 'b>(state: &'b ParsingState<'a>) -> Option<ParseUpdate<'a>>
     Some(update)
 }
 
-fn try_parse_month<'a, 'b>(state: &'b ParsingState<'a>) -> Option<ParseUpdate<'a>> {
-
-    const MONTHS: [&str; 12] = [
-        "january",
-        "february",
-        "march",
-        "april",
-        "may",
-        "june",
-        "july",
-        "august",
-        "september",
-        "october",
-        "november",
-        "december"
-    ];
+fn try_parse_month<'a>(state: &ParsingState<'a>) -> Option<ParseUpdate<'a>> {
 
-    let (word, remaining_words) = match state.remaining_words {
-        ["in", word, rem_words @ ..] => (word, rem_words),
-        ["on", word, rem_words @ ..] => (word, rem_words),
-        [word, rem_words @ ..] => (word, rem_words),
-        _ => return  None
-    };
+    let vocab = state.opts.locale.vocabulary();
 
-    let word = word.to_lowercase();
+    let (words, _) = strip_optional(state.remaining_words, vocab.month_prefixes);
+    let (month_0, remaining_words) = find_name(words, &vocab.months)?;
+    let month = month_0 as u64 + 1;
+
+    let mut cron_updates = vec![(CronColumn::Month, CronValue::On(month))];
 
-    let month = {
-        let month_0 = MONTHS
-            .iter()
-            .position(|&m| m == word)?;
-        month_0 as u64 + 1
+    // "march 10", unless the number is an hour ("march 10 am")
+    let day_words = match remaining_words {
+        [word, rem_words @ ..] if is_day_digits(word) => match rem_words.first() {
+            Some(next) if ["am", "pm"].contains(&next.to_lowercase().as_str()) => None,
+            _ => Some((word.parse().ok()?, rem_words))
+        },
+        _ => None
+    };
+
+    let remaining_words = match day_words {
+        Some((day, rem_words)) => {
+            cron_updates.push((CronColumn::Day, CronValue::On(day)));
+            rem_words
+        },
+        None => remaining_words
     };
 
     let update = ParseUpdate {
-        cron_updates: vec![(CronColumn::Month, CronValue::On(month))],
-        remaining_words
+        cron_updates,
+        remaining_words,
+        bound: None,
+        second: None
     };
 
     debug!("Parsed: month");
@@ -108,21 +173,23 @@ fn try_parse_month<'a, 'b>(state: &'b ParsingState<'a>) -> Option<ParseUpdate<'a
     Some(update)
 }
 
-fn try_parse_clocktime<'a, 'b>(state: &'b ParsingState<'a>) -> Option<ParseUpdate<'a>> {
+fn try_parse_clocktime<'a>(state: &ParsingState<'a>) -> Option<ParseUpdate<'a>> {
 
-    let (time_word, mut remaining_words, has_prep) = match state.remaining_words {
-        ["at", time_word, rem_words @ ..] => (*time_word, rem_words, true),
-        [time_word, rem_words @ ..] => (*time_word, rem_words, false),
-        _ => return  None
-    };
+    let vocab = state.opts.locale.vocabulary();
 
-    let has_colon = time_word.contains(":");
+    let (words, has_prep) = strip_optional(state.remaining_words, vocab.clock_prefixes);
+    let (&time_word, mut remaining_words) = words.split_first()?;
 
-    let time_reg = Regex::new(r"^([0-9]{1,2})(:([0-9]{1,2}))?([ap]m)?$").unwrap();
+    let time_reg = vocab_regex(&format!(
+        r"^([0-9]{{1,2}})(({})([0-9]{{1,2}})?)?([ap]m)?$",
+        vocab.clock_separators
+    ));
     let time_word = time_word.to_lowercase();
     let captures = time_reg.captures(time_word.as_str())?;
 
-    let am_pm = captures.get(4)
+    let has_separator = captures.get(2).is_some();
+
+    let am_pm = captures.get(5)
         .map(|am_pm| am_pm.as_str().to_lowercase())
         .or_else(|| { // check for am/pm in the next word
             let mut res = None;
@@ -138,6 +205,15 @@ fn try_parse_clocktime<'a, 'b>(state: &'b ParsingState<'a>) -> Option<ParseUpdat
 
     let has_am_pm = am_pm.is_some();
 
+    // "9 Uhr"
+    let has_suffix = match strip_phrase(remaining_words, vocab.clock_suffixes) {
+        Some(rem_words) => {
+            remaining_words = rem_words;
+            true
+        },
+        None => false
+    };
+
     let hour: u64 = {
 
         let raw_val: u64 = captures.get(1)?
@@ -162,14 +238,13 @@ fn try_parse_clocktime<'a, 'b>(state: &'b ParsingState<'a>) -> Option<ParseUpdat
     };
 
     let minute: u64 = captures
-        .get(3)
-        .map(|s| s.as_str().parse().ok())
-        .flatten()
+        .get(4)
+        .and_then(|s| s.as_str().parse().ok())
         .unwrap_or(0);
 
     // Make sure we have at least some indication that the
     // numbers represents a time of day
-    if !(has_prep || has_colon || has_am_pm) {
+    if !(has_prep || has_separator || has_am_pm || has_suffix) {
         return None;
     }
 
@@ -178,7 +253,9 @@ fn try_parse_clocktime<'a, 'b>(state: &'b ParsingState<'a>) -> Option<ParseUpdat
             (CronColumn::Hour, CronValue::On(hour)),
             (CronColumn::Minute, CronValue::On(minute))
         ],
-        remaining_words
+        remaining_words,
+        bound: None,
+        second: None
     };
 
     debug!("Parsed: clock time");
@@ -186,50 +263,115 @@ fn try_parse_clocktime<'a, 'b>(state: &'b ParsingState<'a>) -> Option<ParseUpdat
     Some(update)
 }
 
-fn try_parse_duration<'a, 'b>(state: &'b ParsingState<'a>) -> Option<ParseUpdate<'a>> {
+fn try_parse_time_of_day<'a>(state: &ParsingState<'a>) -> Option<ParseUpdate<'a>> {
 
-    let (word, mut remaining_words) = match state.remaining_words {
-        ["in", word, rem_words @ ..] => (word, rem_words),
-        _ => return None
-    };
+    let today = get_cron_from_time(
+        state.now,
+        &[
+            CronColumn::Day,
+            CronColumn::Month,
+            CronColumn::Year
+        ]
+    );
 
-    let reg = Regex::new(r"^[0-9]+").unwrap();
-    let reg_match = reg.find(word)?;
+    let vocab = state.opts.locale.vocabulary();
 
-    let value: i64 = reg_match.as_str().parse().ok()?;
+    // "this evening" and "tonight" also mean today, "every evening" means every day
+    let (period, remaining_words, day_updates) = match strip_phrase(state.remaining_words, vocab.tonight) {
 
-    let durations: Vec<_> = vec![
-        (r"^m(in(utes?)?)?$", Duration::minutes(value)),
-        (r"^h(ours?)?$", Duration::hours(value)),
-        (r"^d(ays?)?$", Duration::days(value)),
-        (r"^w(eeks?)?$", Duration::weeks(value)),
-    ]
-    .into_iter()
-    .map(|(reg, dur)| (Regex::new(reg).unwrap(), dur))
-    .collect();
+        Some(rem_words) => (Period::Night, rem_words, today),
 
-    let suffix = &word[reg_match.end()..].to_lowercase();
+        None => {
 
-    let get_duration = |text| {
-        durations
-            .iter()
-            .find_map(|(reg, dur)| {
-                if reg.is_match(text) { Some(dur) }
-                else { None }
-            })
-    };
+            let (words, day_updates, has_modifier) = if let Some(rem_words) = strip_phrase(state.remaining_words, vocab.today_period_prefixes) {
+                (rem_words, today, true)
+            } else if let Some(rem_words) = strip_phrase(state.remaining_words, vocab.every) {
+                (rem_words, vec![(CronColumn::Day, CronValue::Every)], true)
+            } else {
+                (state.remaining_words, vec![], false)
+            };
 
-    let &duration = {
-        let mut res = get_duration(suffix);
-        if res.is_none() {
-            let (word, rem_words) = remaining_words.split_first()?;
-            remaining_words = rem_words;
-            res = get_duration(word);
+            let (words, has_prep) = strip_optional(words, vocab.period_prefixes);
+
+            // On its own, a period word is only a time right after the day
+            // ("tomorrow morning"), it is too common in text otherwise
+            // ("morning run at 7am"). A word that is also a date is the date
+            // ("mañana", "morgen").
+            if !(has_modifier || has_prep || state.after_day) {
+                return None;
+            }
+
+            let (period, rem_words) = find_phrase(words, vocab.periods)?;
+            (period, rem_words, day_updates)
         }
-        res
-    }?;
+    };
+
+    let opts = state.opts;
+    let (hour, minute) = match period {
+        Period::Noon      => (12, 0),
+        Period::Midnight  => (0, 0),
+        Period::Morning   => (opts.morning_time.hour, opts.morning_time.minute),
+        Period::Afternoon => (opts.afternoon_time.hour, opts.afternoon_time.minute),
+        Period::Evening   => (opts.evening_time.hour, opts.evening_time.minute),
+        Period::Night     => (opts.night_time.hour, opts.night_time.minute)
+    };
+
+    let cron_updates = [
+        vec![
+            (CronColumn::Hour, CronValue::On(hour)),
+            (CronColumn::Minute, CronValue::On(minute))
+        ],
+        day_updates
+    ].concat();
+
+    let update = ParseUpdate {
+        cron_updates,
+        remaining_words,
+        bound: None,
+        second: None
+    };
+
+    debug!("Parsed: time of day");
+
+    Some(update)
+}
+
+fn try_parse_duration<'a>(state: &ParsingState<'a>) -> Option<ParseUpdate<'a>> {
+
+    let vocab = state.opts.locale.vocabulary();
+
+    let words = strip_phrase(state.remaining_words, vocab.duration_prefixes)?;
+
+    let (offset, remaining_words) = parse_offset(vocab, words)?;
+
+    let time = offset.apply(state.now)?;
 
-    let time = state.now + duration;
+    // "in 2 weeks on friday": that day of the week, in the week we land in
+    let (weekday_words, _) = strip_optional(remaining_words, vocab.weekday_prefixes);
+
+    if let Some((event_offset, rem_words)) = find_name(weekday_words, &vocab.weekdays) {
+
+        let current_offset = time.weekday().num_days_from_monday();
+        let time = time.checked_add_signed(Duration::days(event_offset as i64 - current_offset as i64))?;
+
+        let update = ParseUpdate {
+            cron_updates: get_cron_from_time(
+                time,
+                &[
+                    CronColumn::Day,
+                    CronColumn::Month,
+                    CronColumn::Year
+                ]
+            ),
+            remaining_words: rem_words,
+            bound: None,
+            second: None
+        };
+
+        debug!("Parsed: duration and weekday");
+
+        return Some(update);
+    }
 
     let cron_updates = get_cron_from_time(
         time,
@@ -242,9 +384,17 @@ fn try_parse_duration<'a, 'b>(state: &'b ParsingState<'a>) -> Option<ParseUpdate
         ]
     );
 
+    // "in 30 seconds" is the only place where seconds matter
+    let second = match offset.duration.num_seconds() % 60 {
+        0 => None,
+        _ => Some(time.second().into())
+    };
+
     let update = ParseUpdate {
         cron_updates,
-        remaining_words
+        remaining_words,
+        bound: None,
+        second
     };
 
     debug!("Parsed: duration");
@@ -252,17 +402,336 @@ fn try_parse_duration<'a, 'b>(state: &'b ParsingState<'a>) -> Option<ParseUpdate
     Some(update)
 }
 
-fn try_parse_year<'a, 'b>(state: &'b ParsingState<'a>) -> Option<ParseUpdate<'a>> {
+/// Recurring events only: the others already happen once
+fn is_recurring(state: &ParsingState) -> bool {
+    state.cronline_builder.map.values().any(|val| *val == CronValue::Every)
+}
+
+/// Date following a "starting" or "until" qualifier, with the words left after it
+fn parse_bound_date<'a>(state: &ParsingState<'a>, words: &'a [&'a str])
+    -> Option<(Date<chrono::Local>, &'a [&'a str])> {
+
+    let mut date_state = ParsingState::new(state.opts, words, state.now);
+    date_state.dates_only = true;
+
+    if date_state.parse_expressions(false).ok()? == 0 {
+        return None;
+    }
+
+    let remaining_words = date_state.remaining_words;
+    Some((date_state.parsed_date()?, remaining_words))
+}
+
+/// Date of an "until" qualifier standing on its own, as in "/pause 3 until march 10"
+pub(crate) fn parse_until_date(
+    opts: &Opts, now: &DateTime<chrono::Local>, words: &[&str]
+) -> Option<Date<chrono::Local>> {
+
+    let state = ParsingState::new(opts, words, *now);
+
+    let words = strip_phrase(words, opts.locale.vocabulary().until)?;
+    match parse_bound_date(&state, words)? {
+        (date, []) => Some(date),
+        _ => None
+    }
+}
+
+/// Comes before the recurrence ("starting monday, every day at 9am") as well as after
+fn try_parse_starting<'a>(state: &ParsingState<'a>) -> Option<ParseUpdate<'a>> {
+
+    let vocab = state.opts.locale.vocabulary();
+
+    let words = strip_phrase(state.remaining_words, vocab.starting)?;
+    let (date, remaining_words) = parse_bound_date(state, words)?;
+
+    let update = ParseUpdate {
+        cron_updates: vec![],
+        remaining_words,
+        bound: Some(Bound::Start(date.and_hms_opt(0, 0, 0)?)),
+        second: None
+    };
+
+    debug!("Parsed: starting");
+
+    Some(update)
+}
+
+fn try_parse_until<'a>(state: &ParsingState<'a>) -> Option<ParseUpdate<'a>> {
+
+    if !is_recurring(state) {
+        return None;
+    }
+
+    let vocab = state.opts.locale.vocabulary();
+
+    let words = strip_phrase(state.remaining_words, vocab.until)?;
+    let (date, remaining_words) = parse_bound_date(state, words)?;
+    let end = date.succ_opt()?.and_hms_opt(0, 0, 0)?;
+
+    let update = ParseUpdate {
+        cron_updates: vec![],
+        remaining_words,
+        bound: Some(Bound::End(end)),
+        second: None
+    };
+
+    debug!("Parsed: until");
+
+    Some(update)
+}
+
+fn try_parse_for<'a>(state: &ParsingState<'a>) -> Option<ParseUpdate<'a>> {
+
+    if !is_recurring(state) {
+        return None;
+    }
+
+    let vocab = state.opts.locale.vocabulary();
+
+    let words = strip_phrase(state.remaining_words, vocab.during)?;
+    let (offset, remaining_words) = parse_offset(vocab, words)?;
+
+    // "starting monday for 2 weeks" lasts 2 weeks from monday
+    let start = state.bounds.start.unwrap_or(state.now);
+
+    let update = ParseUpdate {
+        cron_updates: vec![],
+        remaining_words,
+        bound: Some(Bound::End(offset.apply(start)?)),
+        second: None
+    };
+
+    debug!("Parsed: for");
+
+    Some(update)
+}
+
+fn try_parse_times<'a>(state: &ParsingState<'a>) -> Option<ParseUpdate<'a>> {
+
+    if !is_recurring(state) {
+        return None;
+    }
+
+    let vocab = state.opts.locale.vocabulary();
 
-    let (word, remaining_words) = match state.remaining_words {
-        ["in", word, rem_words @ ..] => (word, rem_words),
-        [word, rem_words @ ..] => (word, rem_words),
+    let (word, words) = state.remaining_words.split_first()?;
+    let count = match split_number(vocab, word)? {
+        (count, "") if count > 0 => count as u64,
         _ => return None
     };
+    let remaining_words = strip_phrase(words, vocab.times)?;
 
-    let reg = Regex::new(r"^[0-9]{4}$").unwrap();
+    let update = ParseUpdate {
+        cron_updates: vec![],
+        remaining_words,
+        bound: Some(Bound::Count(count)),
+        second: None
+    };
+
+    debug!("Parsed: times");
+
+    Some(update)
+}
+
+/// Time to add to a date. Months are kept apart from the fixed part
+/// since their length varies.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Offset {
+    months: i64,
+    duration: Duration
+}
+
+impl Offset {
+
+    fn fixed(duration: Duration) -> Self {
+        Offset { months: 0, duration }
+    }
+
+    fn apply(&self, time: DateTime<chrono::Local>) -> Option<DateTime<chrono::Local>> {
+        add_months(time, self.months)?.checked_add_signed(self.duration)
+    }
+
+    /// `None` when the sum is out of range, numbers come straight from the message
+    fn checked_add(self, other: Offset) -> Option<Offset> {
+        Some(Offset {
+            months: self.months.checked_add(other.months)?,
+            duration: self.duration.checked_add(&other.duration)?
+        })
+    }
+}
+
+/// Parses one or more terms like "3 hours", "3h", "1h30", "ten minutes",
+/// "a year", "half an hour" or "an hour and a half", optionally joined by "and"
+fn parse_offset<'a>(vocab: &Vocabulary, words: &'a [&'a str]) -> Option<(Offset, &'a [&'a str])> {
+
+    let (mut offset, mut remaining_words) = parse_offset_term(vocab, words)?;
+
+    // "2 hours 15 minutes", "2 hours and 15 minutes"
+    loop {
+
+        let (next_words, _) = strip_optional(remaining_words, vocab.and);
+
+        match parse_offset_term(vocab, next_words) {
+            Some((term, rem_words)) => {
+                offset = offset.checked_add(term)?;
+                remaining_words = rem_words;
+            },
+            None => break
+        }
+    }
+
+    Some((offset, remaining_words))
+}
+
+fn parse_offset_term<'a>(vocab: &Vocabulary, words: &'a [&'a str]) -> Option<(Offset, &'a [&'a str])> {
+
+    // Counting in halves makes "half an hour" and "2 and a half days" easy
+    let (nb_halves, unit, remaining_words) = match find_phrase(words, vocab.quantities) {
+
+        // "a few days", "half an hour"
+        Some((nb_halves, rem_words)) => {
+            let (unit, rem_words) = rem_words.split_first()?;
+            (nb_halves, *unit, rem_words)
+        },
+
+        None => {
+
+            let (word, rem_words) = words.split_first()?;
+
+            // "1h30"
+            static HOURS_MINUTES: Lazy<Regex> = Lazy::new(|| {
+                Regex::new(r"^([0-9]+)h([0-9]{1,2})(m(in)?)?$").unwrap()
+            });
+            if let Some(captures) = HOURS_MINUTES.captures(word) {
+                let hours: i64 = captures.get(1)?.as_str().parse().ok()?;
+                let minutes: i64 = captures.get(2)?.as_str().parse().ok()?;
+                let ms = hours.checked_mul(60)?.checked_add(minutes)?.checked_mul(60_000)?;
+                let offset = Offset::fixed(Duration::milliseconds(ms));
+                return Some((offset, rem_words));
+            }
+
+            let (value, suffix) = split_number(vocab, word)?;
+
+            match suffix {
+                "" => {
+                    let (unit, rem_words) = rem_words.split_first()?;
+                    (value.checked_mul(2)?, *unit, rem_words)
+                },
+                suffix => (value.checked_mul(2)?, suffix, rem_words)
+            }
+        }
+    };
+
+    let (nb_halves, remaining_words) = match strip_phrase(remaining_words, vocab.and_a_half) {
+        Some(rem_words) => (nb_halves.checked_add(1)?, rem_words),
+        None => (nb_halves, remaining_words)
+    };
+
+    // "une demi-heure"
+    let unit = unit.to_lowercase();
+    let (nb_halves, unit) = match vocab.half_unit_prefixes.iter().find_map(|prefix| unit.strip_prefix(prefix)) {
+        Some(unit) => (nb_halves / 2, unit),
+        None => (nb_halves, unit.as_str())
+    };
+
+    let offset = get_unit_offset(vocab, unit, nb_halves)?;
+
+    Some((offset, remaining_words))
+}
+
+/// A day of the month in digits, without anything around it
+fn is_day_digits(word: &str) -> bool {
+    static DAY_DIGITS: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[0-9]{1,2}$").unwrap());
+    DAY_DIGITS.is_match(word)
+}
+
+/// Splits "20min" into (20, "min"). Numbers can also be written out
+/// ("ten", "twenty-five"), in which case there is no suffix.
+fn split_number<'a>(vocab: &Vocabulary, word: &'a str) -> Option<(i64, &'a str)> {
+
+    static NUMBER: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[0-9]+").unwrap());
+
+    match NUMBER.find(word) {
+        Some(reg_match) => {
+            let value = reg_match.as_str().parse().ok()?;
+            Some((value, &word[reg_match.end()..]))
+        },
+        None => Some((parse_number_word(vocab, word)?, ""))
+    }
+}
+
+fn parse_number_word(vocab: &Vocabulary, word: &str) -> Option<i64> {
+
+    let word = word.to_lowercase();
+
+    let parse_units = |w: &str| vocab.numbers.iter().position(|&u| u == w).map(|v| v as i64);
+    let parse_tens = |w: &str| vocab.tens.iter().position(|&t| t == w).map(|v| (v as i64 + 2) * 10);
+
+    // Some numbers below twenty have a hyphen too ("dix-sept")
+    parse_units(&word)
+        .or_else(|| parse_tens(&word))
+        .or_else(|| {
+            let (tens, units) = word.split_once('-')?;
+            Some(parse_tens(tens)? + parse_units(units).filter(|&v| v < 10)?)
+        })
+}
+
+fn get_unit_offset(vocab: &Vocabulary, unit: &str, nb_halves: i64) -> Option<Offset> {
+
+    // Any number of milliseconds fits in a Duration, unlike seconds or hours
+    let fixed = |ms_per_half: i64| nb_halves.checked_mul(ms_per_half)
+        .map(|ms| Offset::fixed(Duration::milliseconds(ms)));
+    let months = |nb_months| Some(Offset { months: nb_months, duration: Duration::zero() });
+
+    let units: Vec<(&str, Option<Offset>)> = vec![
+        (vocab.units.second, fixed(500)),
+        (vocab.units.minute, fixed(30 * 1000)),
+        (vocab.units.hour, fixed(30 * 60 * 1000)),
+        (vocab.units.day, fixed(12 * 3600 * 1000)),
+        (vocab.units.week, fixed(84 * 3600 * 1000)),
+        // No such thing as half a month
+        (vocab.units.month, if nb_halves % 2 == 0 { months(nb_halves / 2) } else { None }),
+        (vocab.units.year, nb_halves.checked_mul(6).and_then(months)),
+    ];
+
+    units.into_iter()
+        .find(|(reg, _offset)| vocab_regex(reg).is_match(unit))
+        .and_then(|(_reg, offset)| offset)
+}
+
+/// Calendar-correct: one month after January 31st is the last day of February
+fn add_months(time: DateTime<chrono::Local>, nb_months: i64) -> Option<DateTime<chrono::Local>> {
+
+    let month_0 = (time.year() as i64 * 12 + time.month0() as i64).checked_add(nb_months)?;
+    let year = std::convert::TryFrom::try_from(month_0.div_euclid(12)).ok()?;
+    let month = month_0.rem_euclid(12) as u32 + 1;
+
+    let day = time.day().min(days_in_month(year, month));
 
-    let year: u64 = reg.captures(word)?
+    chrono::Local
+        .ymd_opt(year, month, day)
+        .and_hms_opt(time.hour(), time.minute(), time.second())
+        .single()
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = match month {
+        12 => (year + 1, 1),
+        _ => (year, month + 1)
+    };
+    NaiveDate::from_ymd(next_year, next_month, 1).pred().day()
+}
+
+fn try_parse_year<'a>(state: &ParsingState<'a>) -> Option<ParseUpdate<'a>> {
+
+    let vocab = state.opts.locale.vocabulary();
+
+    let (words, _) = strip_optional(state.remaining_words, vocab.year_prefixes);
+    let (word, remaining_words) = words.split_first()?;
+
+    static YEAR: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[0-9]{4}$").unwrap());
+
+    let year: u64 = YEAR.captures(word)?
         .get(0)?
         .as_str()
         .parse()
@@ -270,7 +739,9 @@ fn try_parse_year<'a, 'b>(state: &'b ParsingState<'a>) -> Option<ParseUpdate<'a>
 
     let update = ParseUpdate {
         cron_updates: vec![(CronColumn::Year, CronValue::On(year))],
-        remaining_words
+        remaining_words,
+        bound: None,
+        second: None
     };
 
     debug!("Parsed: year");
@@ -278,39 +749,29 @@ fn try_parse_year<'a, 'b>(state: &'b ParsingState<'a>) -> Option<ParseUpdate<'a>
     Some(update)
 }
 
-fn try_parse_every<'a, 'b>(state: &'b ParsingState<'a>) -> Option<ParseUpdate<'a>> {
-
-    let remaining_words = match state.remaining_words {
-        ["on", rem_words @ ..] => rem_words,
-        [rem_words @ ..] => rem_words
-    };
-
-    let (&w1, remaining_words) = remaining_words.split_first()?;
+fn try_parse_every<'a>(state: &ParsingState<'a>) -> Option<ParseUpdate<'a>> {
 
-    if w1 != "every" {
-        return None
-    }
+    let vocab = state.opts.locale.vocabulary();
 
-    let (&w2, remaining_words) = remaining_words.split_first()?;
+    let (words, _) = strip_optional(state.remaining_words, vocab.date_prefixes);
+    let words = strip_phrase(words, vocab.every)?;
+    let (cron_col, remaining_words) = find_phrase(words, vocab.every_units)?;
 
-    let cron_updates = match w2 {
+    let cron_updates = match cron_col {
 
-        "hour" => vec![
+        CronColumn::Hour => vec![
             (CronColumn::Hour, CronValue::Every),
             (CronColumn::Minute, CronValue::On(0)),
         ],
 
-        _ => {
-            let &cron_col = CRON_COLUMNS
-                .iter()
-                .find(|col| col.unit() == w2)?;
-            vec![(cron_col, CronValue::Every)]
-        }
+        cron_col => vec![(cron_col, CronValue::Every)]
     };
 
     let update = ParseUpdate {
         cron_updates,
-        remaining_words
+        remaining_words,
+        bound: None,
+        second: None
     };
 
     debug!("Parsed: \"every\"");
@@ -320,49 +781,110 @@ fn try_parse_every<'a, 'b>(state: &'b ParsingState<'a>) -> Option<ParseUpdate<'a
 
 
 
-fn try_parse_date_digits<'a, 'b>(state: &'b ParsingState<'a>) -> Option<ParseUpdate<'a>> {
+fn try_parse_date_digits<'a>(state: &ParsingState<'a>) -> Option<ParseUpdate<'a>> {
 
-    let (word, remaining_words) = match state.remaining_words {
-        ["on", "the", word, rem_words @ ..] => (word, rem_words),
-        ["on", word, rem_words @ ..] => (word, rem_words),
-        [word, rem_words @ ..] => (word, rem_words),
-        _ => return None
-    };
+    let vocab = state.opts.locale.vocabulary();
 
-    let reg = Regex::new(r"^([0-9]{1,2})/([0-9]{1,2})(/([0-9]{4}))?$").unwrap();
-    let captures = reg.captures(word)?;
+    let (words, _) = strip_optional(state.remaining_words, vocab.date_prefixes);
+    let (word, remaining_words) = words.split_first()?;
+
+    // "15/03", "15.03.2024", "15-03-24"
+    static DATE_DIGITS: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"^([0-9]{1,2})([/.-])([0-9]{1,2})(([/.-])([0-9]{4}|[0-9]{2}))?$").unwrap()
+    });
+    let captures = DATE_DIGITS.captures(word)?;
 
     let d1: u64 = captures.get(1)?.as_str().parse().ok()?;
-    let d2: u64 = captures.get(2)?.as_str().parse().ok()?;
+    let d2: u64 = captures.get(3)?.as_str().parse().ok()?;
+
+    // Both separators must be the same
+    if let Some(separator) = captures.get(5) {
+        if separator.as_str() != captures.get(2)?.as_str() {
+            return None;
+        }
+    }
 
     let (day, month) = match state.opts.date_format {
         DateFormat::DMY => (d1, d2),
         DateFormat::MDY => (d2, d1)
     };
 
+    let mut cron_updates = vec![
+        (CronColumn::Day, CronValue::On(day)),
+        (CronColumn::Month, CronValue::On(month))
+    ];
+
+    if let Some(year) = captures.get(6) {
+        cron_updates.push((CronColumn::Year, CronValue::On(full_year(year.as_str())?)));
+    }
+
+    let update = ParseUpdate {
+        cron_updates,
+        remaining_words,
+        bound: None,
+        second: None
+    };
+
+    debug!("Parsed: date digits");
+
+    Some(update)
+}
+
+
+fn try_parse_iso_date<'a>(state: &ParsingState<'a>) -> Option<ParseUpdate<'a>> {
+
+    let vocab = state.opts.locale.vocabulary();
+
+    let (words, _) = strip_optional(state.remaining_words, vocab.date_prefixes);
+    let (word, remaining_words) = words.split_first()?;
+
+    // "2024-03-15", which reads the same whatever the date format
+    static ISO_DATE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"^([0-9]{4})-([0-9]{1,2})-([0-9]{1,2})$").unwrap()
+    });
+    let captures = ISO_DATE.captures(word)?;
+
+    let year: u64 = captures.get(1)?.as_str().parse().ok()?;
+    let month: u64 = captures.get(2)?.as_str().parse().ok()?;
+    let day: u64 = captures.get(3)?.as_str().parse().ok()?;
+
     let update = ParseUpdate {
         cron_updates: vec![
             (CronColumn::Day, CronValue::On(day)),
-            (CronColumn::Month, CronValue::On(month))
+            (CronColumn::Month, CronValue::On(month)),
+            (CronColumn::Year, CronValue::On(year))
         ],
-        remaining_words
+        remaining_words,
+        bound: None,
+        second: None
     };
 
-    debug!("Parsed: date digits");
+    debug!("Parsed: ISO date");
 
     Some(update)
 }
 
+/// Year written with 2 or 4 digits, "24" being 2024
+fn full_year(digits: &str) -> Option<u64> {
+    let year: u64 = digits.parse().ok()?;
+    if digits.len() == 2 { Some(2000 + year) } else { Some(year) }
+}
+
+fn try_parse_relative<'a>(state: &ParsingState<'a>) -> Option<ParseUpdate<'a>> {
 
-fn try_parse_relative<'a, 'b>(state: &'b ParsingState<'a>) -> Option<ParseUpdate<'a>> {
+    let now = state.now;
+    let vocab = state.opts.locale.vocabulary();
 
-    let (word, remaining_words) = state.remaining_words.split_first()?;
+    let (relative_date, remaining_words) = find_phrase(state.remaining_words, vocab.relative_dates)?;
 
-    let time = match *word {
-        "today" => Some(state.now),
-        "tomorrow" => Some(state.now + Duration::days(1)),
-        _ => None
-    }?;
+    let time = match relative_date {
+        RelativeDate::Days(nb_days) => now + Duration::days(nb_days),
+        RelativeDate::Months(nb_months) => add_months(now, nb_months)?,
+        RelativeDate::EndOfMonth => {
+            let last_day = days_in_month(now.year(), now.month());
+            now + Duration::days((last_day - now.day()).into())
+        }
+    };
 
     let cron_updates = get_cron_from_time(
         time,
@@ -375,7 +897,9 @@ fn try_parse_relative<'a, 'b>(state: &'b ParsingState<'a>) -> Option<ParseUpdate
 
     let update = ParseUpdate {
         cron_updates,
-        remaining_words
+        remaining_words,
+        bound: None,
+        second: None
     };
 
     debug!("Parsed: relative");
@@ -383,30 +907,30 @@ fn try_parse_relative<'a, 'b>(state: &'b ParsingState<'a>) -> Option<ParseUpdate
     Some(update)
 }
 
-fn try_parse_weekday<'a, 'b>(state: &'b ParsingState<'a>) -> Option<ParseUpdate<'a>> {
+fn try_parse_weekday<'a>(state: &ParsingState<'a>) -> Option<ParseUpdate<'a>> {
 
-    const DAYS: [&str; 7] = [
-        "monday",
-        "tuesday",
-        "wednesday",
-        "thursday",
-        "friday",
-        "saturday",
-        "sunday"
-    ];
+    let vocab = state.opts.locale.vocabulary();
 
-    let (word, remaining_words) = match state.remaining_words {
-        ["on", word, rem_words @ ..] => (word, rem_words),
-        [word, rem_words @ ..] => (word, rem_words),
-        _ => return None
+    // "this monday" is the closest one, "next monday" the one of next week
+    let (words, next_week) = match strip_phrase(state.remaining_words, vocab.next_weekday_prefixes) {
+        Some(rem_words) => (rem_words, true),
+        None => (strip_optional(state.remaining_words, vocab.weekday_prefixes).0, false)
     };
 
-    let event_offset: u32 = DAYS
-        .iter().position(|&d| d == word.to_lowercase())?
-        .try_into().unwrap();
+    let (event_offset, remaining_words) = find_name(words, &vocab.weekdays)?;
+
+    // "lundi prochain"
+    let (remaining_words, next_week) = match strip_phrase(remaining_words, vocab.next_weekday_suffixes) {
+        Some(rem_words) => (rem_words, true),
+        None => (remaining_words, next_week)
+    };
+
+    let event_offset = event_offset as u32;
     let current_offset = state.now.date().weekday().num_days_from_monday();
 
-    let nb_days = if current_offset < event_offset {
+    let nb_days = if next_week {
+        7 - current_offset + event_offset
+    } else if current_offset < event_offset {
         event_offset - current_offset
     } else {
         7 - (current_offset - event_offset)
@@ -425,7 +949,9 @@ fn try_parse_weekday<'a, 'b>(state: &'b ParsingState<'a>) -> Option<ParseUpdate<
 
     let update = ParseUpdate {
         cron_updates,
-        remaining_words
+        remaining_words,
+        bound: None,
+        second: None
     };
 
     debug!("Parsed: weekday");
@@ -433,14 +959,64 @@ fn try_parse_weekday<'a, 'b>(state: &'b ParsingState<'a>) -> Option<ParseUpdate<
     Some(update)
 }
 
+/// Strips the longest of `phrases` found at the start of `words`, ignoring
+/// a comma after a word
+fn strip_phrase<'a>(words: &'a [&'a str], phrases: &[&str]) -> Option<&'a [&'a str]> {
+
+    phrases.iter()
+        .filter_map(|phrase| {
+            let phrase_words: Vec<&str> = phrase.split_whitespace().collect();
+            let nb_words = phrase_words.len();
+            let matches = words.len() >= nb_words && words.iter()
+                .zip(&phrase_words)
+                .all(|(word, phrase_word)| word.to_lowercase().trim_end_matches(',') == *phrase_word);
+            if matches { Some(nb_words) } else { None }
+        })
+        .max()
+        .map(|nb_words| &words[nb_words..])
+}
+
+/// Like `strip_phrase`, also telling whether a phrase was there
+fn strip_optional<'a>(words: &'a [&'a str], phrases: &[&str]) -> (&'a [&'a str], bool) {
+    match strip_phrase(words, phrases) {
+        Some(rem_words) => (rem_words, true),
+        None => (words, false)
+    }
+}
+
+/// Looks up the longest phrase of a table at the start of `words`
+fn find_phrase<'a, T: Copy>(words: &'a [&'a str], table: &[(&str, T)]) -> Option<(T, &'a [&'a str])> {
+
+    table.iter()
+        .filter_map(|&(phrase, value)| {
+            strip_phrase(words, &[phrase]).map(|rem_words| (value, rem_words))
+        })
+        .min_by_key(|(_value, rem_words)| rem_words.len())
+}
+
+/// Position of the name found at the start of `words`, for months and weekdays.
+/// Names are single words, abbreviations may end with a dot ("Sept."), and
+/// a comma may end the expression ("monday, every day").
+fn find_name<'a>(words: &'a [&'a str], names: &[&[&str]]) -> Option<(usize, &'a [&'a str])> {
+
+    let (word, remaining_words) = words.split_first()?;
+    let word = word.to_lowercase();
+    let word = word.trim_end_matches(',');
+    let bare_word = word.trim_end_matches('.');
+
+    names.iter()
+        .position(|names| names.iter().any(|&name| name == word || name == bare_word))
+        .map(|position| (position, remaining_words))
+}
+
 fn get_cron_from_time(time: DateTime<chrono::Local>, columns: &[CronColumn]) 
     -> Vec<(CronColumn, CronValue)> {
 
     let (minute, hour, day, month, year) = (
-        time.minute().try_into().unwrap(),
-        time.hour().try_into().unwrap(),
-        time.date().day().try_into().unwrap(),
-        time.date().month().try_into().unwrap(),
+        time.minute().into(),
+        time.hour().into(),
+        time.date().day().into(),
+        time.date().month().into(),
         time.date().year().try_into().unwrap()
     );
 
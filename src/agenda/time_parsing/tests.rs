@@ -1,8 +1,10 @@
+#![allow(clippy::zero_prefixed_literal)]
+
 use chrono::{Duration, TimeZone};
 use clap::Clap;
 use crate::Opts;
 
-use super::{Cronline, parse_cronline};
+use super::{Cronline, CronValue, ParseStep, parse_cronline};
 
 #[test]
 fn fixed_durations() {
@@ -22,6 +24,74 @@ fn fixed_durations() {
         Cronline::from_time(&(now + Duration::hours(3))),
         &["test1", "test2"]
     ));
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 45);
+
+    test_parse(&TestParams::new(
+        now,
+        "in 30 seconds test1 test2",
+        Cronline::from_time(&(now + Duration::seconds(30))).with_second(15),
+        &["test1", "test2"]
+    ));
+
+    test_parse(&TestParams::new(
+        now,
+        "in 1 min 30 s test1 test2",
+        Cronline::from_time(&(now + Duration::seconds(90))).with_second(15),
+        &["test1", "test2"]
+    ));
+}
+
+#[test]
+fn compound_durations() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+
+    let cases = [
+        ("in 1h30", Duration::minutes(90)),
+        ("in 2 hours 15 minutes", Duration::minutes(135)),
+        ("in 2 hours and 15 minutes", Duration::minutes(135)),
+        ("in an hour", Duration::hours(1)),
+        ("in a couple of days", Duration::days(2)),
+        ("in ten minutes", Duration::minutes(10)),
+        ("in twenty-five minutes", Duration::minutes(25)),
+        ("in 1 day 2h", Duration::hours(26)),
+    ];
+
+    for (msg, duration) in cases.iter() {
+        test_parse(&TestParams::new(
+            now,
+            &format!("{} test1 test2", msg),
+            Cronline::from_time(&(now + *duration)),
+            &["test1", "test2"]
+        ));
+    }
+}
+
+#[test]
+fn out_of_range_durations() {
+
+    let opts = Opts::parse_from(["placeholder", "placeholder"]);
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+
+    let parse = |msg: &str| {
+        let words: Vec<&str> = msg.split_whitespace().collect();
+        parse_cronline(&opts, &now, &words).map(|res| res.remaining_words.join(" "))
+    };
+
+    // Numbers come from the message, these used to overflow and panic
+    for msg in [
+        "in 99999999999999 days test",
+        "in 4611686018427387904 days test",
+        "in 9223372036854775807 days and a half test",
+        "in 99999999999999h30 test",
+        "in 1536000000000000000 years test",
+        "in 100000000000 weeks 100000000000 weeks test"
+    ].iter() {
+        assert!(parse(msg).is_err(), "{}", msg);
+    }
+
+    // Far, but still a date
+    assert_eq!(parse("in 99999999999 seconds test").unwrap(), "test");
 }
 
 #[test]
@@ -97,6 +167,66 @@ fn today_tomorrow() {
     ));
 }
 
+#[test]
+fn relative_dates() {
+
+    // That date is a Saturday
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+
+    let cases = [
+        ("the day after tomorrow at 8am", chrono::Local.ymd(2000, 01, 03)),
+        ("this sunday at 8am", chrono::Local.ymd(2000, 01, 02)),
+        ("next sunday at 8am", chrono::Local.ymd(2000, 01, 09)),
+        ("next monday at 8am", chrono::Local.ymd(2000, 01, 03)),
+        ("next week at 8am", chrono::Local.ymd(2000, 01, 08)),
+        ("next month at 8am", chrono::Local.ymd(2000, 02, 01)),
+        ("at the end of the month at 8am", chrono::Local.ymd(2000, 01, 31)),
+        ("in 2 weeks on friday at 8am", chrono::Local.ymd(2000, 01, 14)),
+    ];
+
+    for (msg, date) in cases.iter() {
+        test_parse(&TestParams::new(
+            now,
+            &format!("{} test1 test2", msg),
+            Cronline::from_time(&date.and_hms(08, 00, 00)),
+            &["test1", "test2"]
+        ));
+    }
+}
+
+#[test]
+fn calendar_durations() {
+
+    let now = chrono::Local.ymd(2000, 01, 31).and_hms(08, 00, 00);
+
+    let cases = [
+        ("in 1 month", chrono::Local.ymd(2000, 02, 29).and_hms(08, 00, 00)),
+        ("in 3 months", chrono::Local.ymd(2000, 04, 30).and_hms(08, 00, 00)),
+        ("in a year", chrono::Local.ymd(2001, 01, 31).and_hms(08, 00, 00)),
+        ("in half an hour", chrono::Local.ymd(2000, 01, 31).and_hms(08, 30, 00)),
+        ("in an hour and a half", chrono::Local.ymd(2000, 01, 31).and_hms(09, 30, 00)),
+    ];
+
+    for (msg, time) in cases.iter() {
+        test_parse(&TestParams::new(
+            now,
+            &format!("{} test1 test2", msg),
+            Cronline::from_time(time),
+            &["test1", "test2"]
+        ));
+    }
+
+    // The end of the month depends on the month
+    let now = chrono::Local.ymd(2000, 02, 10).and_hms(08, 00, 00);
+
+    test_parse(&TestParams::new(
+        now,
+        "end of the month at 8am test1 test2",
+        Cronline::from_time(&chrono::Local.ymd(2000, 02, 29).and_hms(08, 00, 00)),
+        &["test1", "test2"]
+    ));
+}
+
 #[test]
 fn single_digits() {
 
@@ -197,6 +327,260 @@ fn date_formats() {
     test_parse(&params);
 }
 
+#[test]
+fn date_separators() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+
+    let cases = [
+        ("on 07.08", chrono::Local.ymd(2000, 08, 07)),
+        ("on 07-08", chrono::Local.ymd(2000, 08, 07)),
+        ("on 07/08/2001", chrono::Local.ymd(2001, 08, 07)),
+        ("on 07.08.2001", chrono::Local.ymd(2001, 08, 07)),
+        ("on 07/08/01", chrono::Local.ymd(2001, 08, 07)),
+        ("on 2001-08-07", chrono::Local.ymd(2001, 08, 07)),
+        ("2001-08-07", chrono::Local.ymd(2001, 08, 07)),
+    ];
+
+    for (msg, date) in cases.iter() {
+        test_parse(&TestParams::new(
+            now,
+            &format!("{} at 8am test1 test2", msg),
+            Cronline::from_time(&date.and_hms(08, 00, 00)),
+            &["test1", "test2"]
+        ));
+    }
+
+    // ISO dates do not depend on the date format
+    test_parse(&TestParams::new(
+        now,
+        "on 2001-08-07 at 8am test1 test2",
+        Cronline::from_time(&chrono::Local.ymd(2001, 08, 07).and_hms(08, 00, 00)),
+        &["test1", "test2"]
+    ).with_args(&["--date-format", "mdy"]));
+
+    // Mixed separators are not a date
+    let opts = Opts::parse_from(["placeholder", "placeholder"]);
+    let words = ["on", "07/08-2001", "test1"];
+    assert!(parse_cronline(&opts, &now, &words).is_err());
+}
+
+#[test]
+fn abbreviations() {
+
+    // That date is a Saturday
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+
+    let cases = [
+        ("on the 5th jan", chrono::Local.ymd(2000, 01, 05)),
+        ("on the 5th Sept", chrono::Local.ymd(2000, 09, 05)),
+        ("on the 5th Sept.", chrono::Local.ymd(2000, 09, 05)),
+        ("dec 5th", chrono::Local.ymd(2000, 12, 05)),
+        ("on tue", chrono::Local.ymd(2000, 01, 04)),
+        ("on thurs", chrono::Local.ymd(2000, 01, 06)),
+        ("next Fri.", chrono::Local.ymd(2000, 01, 07)),
+    ];
+
+    for (msg, date) in cases.iter() {
+        test_parse(&TestParams::new(
+            now,
+            &format!("{} at 8am test1 test2", msg),
+            Cronline::from_time(&date.and_hms(08, 00, 00)),
+            &["test1", "test2"]
+        ));
+    }
+}
+
+#[test]
+fn ordinal_words() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+
+    let cases = [
+        ("on the first of may", chrono::Local.ymd(2000, 05, 01)),
+        ("the twenty-third of may", chrono::Local.ymd(2000, 05, 23)),
+        ("may the thirty-first", chrono::Local.ymd(2000, 05, 31)),
+    ];
+
+    for (msg, date) in cases.iter() {
+        test_parse(&TestParams::new(
+            now,
+            &format!("{} at 8am test1 test2", msg),
+            Cronline::from_time(&date.and_hms(08, 00, 00)),
+            &["test1", "test2"]
+        ));
+    }
+
+    // Without "the", an ordinal word is part of the message
+    test_parse(&TestParams::new(
+        now,
+        "tomorrow at 8am first test1",
+        Cronline::from_time(&chrono::Local.ymd(2000, 01, 02).and_hms(08, 00, 00)),
+        &["first", "test1"]
+    ));
+}
+
+
+#[test]
+fn times_of_day() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+
+    test_parse(&TestParams::new(
+        now,
+        "at noon test1 test2",
+        Cronline::from_time(&chrono::Local.ymd(2000, 01, 01).and_hms(12, 00, 00)),
+        &["test1", "test2"]
+    ));
+
+    test_parse(&TestParams::new(
+        now,
+        "tomorrow at midnight test1 test2",
+        Cronline::from_time(&chrono::Local.ymd(2000, 01, 02).and_hms(00, 00, 00)),
+        &["test1", "test2"]
+    ));
+
+    test_parse(&TestParams::new(
+        now,
+        "tonight test1 test2",
+        Cronline::from_time(&chrono::Local.ymd(2000, 01, 01).and_hms(21, 00, 00)),
+        &["test1", "test2"]
+    ));
+
+    test_parse(&TestParams::new(
+        now,
+        "this evening test1 test2",
+        Cronline::from_time(&chrono::Local.ymd(2000, 01, 01).and_hms(19, 00, 00)),
+        &["test1", "test2"]
+    ));
+
+    test_parse(&TestParams::new(
+        now,
+        "tomorrow morning test1 test2",
+        Cronline::from_time(&chrono::Local.ymd(2000, 01, 02).and_hms(09, 00, 00)),
+        &["test1", "test2"]
+    ));
+
+    test_parse(&TestParams::new(
+        now,
+        "on sunday in the afternoon test1 test2",
+        Cronline::from_time(&chrono::Local.ymd(2000, 01, 02).and_hms(14, 00, 00)),
+        &["test1", "test2"]
+    ));
+
+    test_parse(&TestParams::new(
+        now,
+        "every morning test1 test2",
+        Cronline::from_values([
+            CronValue::On(0), CronValue::On(7),
+            CronValue::Every, CronValue::Every, CronValue::Every
+        ]),
+        &["test1", "test2"]
+    ).with_args(&["--morning-time", "7:00"]));
+
+    // A period word on its own is text, the time is given elsewhere
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(06, 00, 00);
+
+    test_parse(&TestParams::new(
+        now,
+        "morning run at 7am",
+        Cronline::from_time(&chrono::Local.ymd(2000, 01, 01).and_hms(07, 00, 00)),
+        &["morning", "run"]
+    ));
+
+    test_parse(&TestParams::new(
+        now,
+        "night shift tomorrow at 10pm",
+        Cronline::from_time(&chrono::Local.ymd(2000, 01, 02).and_hms(22, 00, 00)),
+        &["night", "shift"]
+    ));
+
+    test_parse(&TestParams::new(
+        now,
+        "tomorrow at 8am noon meeting prep",
+        Cronline::from_time(&chrono::Local.ymd(2000, 01, 02).and_hms(08, 00, 00)),
+        &["noon", "meeting", "prep"]
+    ));
+}
+
+#[test]
+fn expressions_anywhere() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+
+    test_parse(&TestParams::new(
+        now,
+        "call mom tomorrow at 6pm",
+        Cronline::from_time(&chrono::Local.ymd(2000, 01, 02).and_hms(18, 00, 00)),
+        &["call", "mom"]
+    ));
+
+    test_parse(&TestParams::new(
+        now,
+        "dentist on monday bring the card at 9am",
+        Cronline::from_time(&chrono::Local.ymd(2000, 01, 03).and_hms(09, 00, 00)),
+        &["dentist", "bring", "the", "card"]
+    ));
+
+    // "may" would conflict with the month from "tomorrow", it's part of the text
+    test_parse(&TestParams::new(
+        now,
+        "call tomorrow at 6pm may be late",
+        Cronline::from_time(&chrono::Local.ymd(2000, 01, 02).and_hms(18, 00, 00)),
+        &["call", "may", "be", "late"]
+    ));
+
+    // A time prefix is used as is, the rest is left untouched
+    test_parse(&TestParams::new(
+        now,
+        "tomorrow at 6pm call mom on monday",
+        Cronline::from_time(&chrono::Local.ymd(2000, 01, 02).and_hms(18, 00, 00)),
+        &["call", "mom", "on", "monday"]
+    ));
+    // In the middle of the text, names and ordinal words need a marker
+    let cases: [(&str, &[&str]); 4] = [
+        ("call mom about the sun lamp at 5pm", &["call", "mom", "about", "the", "sun", "lamp"]),
+        ("fix the sat nav at 5pm", &["fix", "the", "sat", "nav"]),
+        ("print the second draft at 5pm", &["print", "the", "second", "draft"]),
+        ("book the evening train at 5pm", &["book", "the", "evening", "train"]),
+    ];
+
+    for (msg, text) in cases.iter() {
+        test_parse(&TestParams::new(
+            now,
+            msg,
+            Cronline::from_time(&chrono::Local.ymd(2000, 01, 01).and_hms(17, 00, 00)),
+            text
+        ));
+    }
+
+    // A duration too large for a date stays in the text rather than panicking
+    test_parse(&TestParams::new(
+        now,
+        "buy milk in 99999999999999 years at 5pm",
+        Cronline::from_time(&chrono::Local.ymd(2000, 01, 01).and_hms(17, 00, 00)),
+        &["buy", "milk", "in", "99999999999999", "years"]
+    ));
+}
+
+#[test]
+fn parse_steps() {
+
+    let opts = Opts::parse_from(["placeholder", "placeholder"]);
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+    let msg = "in september on the 5th at 8 am test1";
+
+    let words: Vec<&str> = msg.split_whitespace().collect();
+    let res = parse_cronline(&opts, &now, &words).unwrap();
+
+    assert_eq!(res.steps, vec![
+        ParseStep { parser: "month", words: &["in", "september"] },
+        ParseStep { parser: "day", words: &["on", "the", "5th"] },
+        ParseStep { parser: "clock time", words: &["at", "8", "am"] },
+    ]);
+    assert_eq!(res.remaining_words, &["test1"]);
+}
 
 #[test]
 #[should_panic]
@@ -225,6 +609,57 @@ fn fail_pm() {
     parse_cronline(&opts, &now, &words).unwrap();
 }
 
+#[test]
+fn bounds() {
+
+    let now = chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00);
+    let opts = Opts::parse_from(["placeholder", "placeholder"]);
+
+    let every_day = Cronline::from_values([
+        CronValue::On(0), CronValue::On(8),
+        CronValue::Every, CronValue::Every, CronValue::Every
+    ]);
+
+    let cases = [
+        ("every day at 8am until march 10 test1", Some(chrono::Local.ymd(2000, 03, 11).and_hms(00, 00, 00)), None),
+        ("every day until the 10th of march at 8am test1", Some(chrono::Local.ymd(2000, 03, 11).and_hms(00, 00, 00)), None),
+        ("every day at 8am for 2 weeks test1", Some(now + Duration::weeks(2)), None),
+        ("every day at 8am 5 times test1", None, Some(5)),
+        ("test1 every day at 8am for a week three times", Some(now + Duration::weeks(1)), Some(3)),
+    ];
+
+    for (msg, end, remaining) in cases.iter() {
+        let words: Vec<&str> = msg.split_whitespace().collect();
+        let res = parse_cronline(&opts, &now, &words).unwrap();
+        assert_eq!(res.cronline, every_day, "{}", msg);
+        assert_eq!(res.remaining_words, ["test1"], "{}", msg);
+        assert_eq!(res.bounds.end, *end, "{}", msg);
+        assert_eq!(res.bounds.remaining, *remaining, "{}", msg);
+    }
+
+    // A start date can come first, "for" then counts from it
+    let monday = chrono::Local.ymd(2000, 01, 03).and_hms(00, 00, 00);
+    let words = ["starting", "next", "monday,", "every", "day", "at", "8am", "for", "a", "week", "test1"];
+    let res = parse_cronline(&opts, &now, &words).unwrap();
+    assert_eq!(res.cronline, every_day);
+    assert_eq!(res.remaining_words, ["test1"]);
+    assert_eq!(res.bounds.start, Some(monday));
+    assert_eq!(res.bounds.end, Some(monday + Duration::weeks(1)));
+
+    let words = ["test1", "every", "day", "at", "8am", "from", "the", "5th"];
+    let res = parse_cronline(&opts, &now, &words).unwrap();
+    assert_eq!(res.remaining_words, ["test1"]);
+    assert_eq!(res.bounds.start, Some(chrono::Local.ymd(2000, 01, 05).and_hms(00, 00, 00)));
+
+    // One-off events have no bounds, the words are part of the message
+    test_parse(&TestParams::new(
+        now,
+        "tomorrow at 8am book the room for 2 hours",
+        Cronline::from_time(&chrono::Local.ymd(2000, 01, 02).and_hms(08, 00, 00)),
+        &["book", "the", "room", "for", "2", "hours"]
+    ));
+}
+
 #[derive(Clone)]
 struct TestParams<'a> {
 
@@ -0,0 +1,146 @@
+use chrono::{Date, Datelike, Duration, Timelike};
+use crate::{Opts, DateFormat};
+use crate::locale::{Locale, Msg, Vocabulary, fill};
+use super::{AgendaState, Instant, sanitize};
+
+/// Most lines in a reply, the rest is only counted
+const MAX_LINES: usize = 100;
+
+/// Days shown by "/agenda week", today included
+const WEEK_DAYS: i64 = 7;
+
+/// Period shown by `/agenda`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum AgendaPeriod {
+    Today,
+    Tomorrow,
+    Week
+}
+
+impl AgendaPeriod {
+
+    /// The English words work in every language
+    pub(super) fn from_word(vocab: &Vocabulary, word: &str) -> Option<Self> {
+        const PERIODS: [AgendaPeriod; 3] = [AgendaPeriod::Today, AgendaPeriod::Tomorrow, AgendaPeriod::Week];
+        [vocab, Locale::En.vocabulary()].iter()
+            .find_map(|vocab| vocab.agenda_periods.iter().position(|words| words.contains(&word)))
+            .map(|i| PERIODS[i])
+    }
+
+    /// Start and end of the period, the start being excluded
+    fn range(&self, now: &Instant) -> (Instant, Instant) {
+        let today = now.date();
+        match self {
+            Self::Today => (*now, start_of_day(today.succ())),
+            Self::Tomorrow => (
+                start_of_day(today.succ()) - Duration::seconds(1),
+                start_of_day(today.succ().succ())
+            ),
+            Self::Week => (*now, start_of_day(today + Duration::days(WEEK_DAYS)))
+        }
+    }
+}
+
+/// The next `nb` occurrences across all events
+pub(super) fn next_occurences(opts: &Opts, state: &AgendaState, now: &Instant, nb: usize) -> String {
+
+    let locale = opts.locale;
+
+    let mut occurences: Vec<(Instant, u64)> = state.events.iter()
+        .flat_map(|(id, event)| event.upcoming_occurences(now).take(nb).map(move |t| (t, *id)))
+        .collect();
+    occurences.sort();
+    occurences.truncate(nb);
+
+    if occurences.is_empty() {
+        return locale.tr(Msg::NothingComingUp).to_owned();
+    }
+
+    let lines: Vec<String> = occurences.iter()
+        .map(|(t, id)| format!(
+            "<pre>  {} - [{}] {}</pre>",
+            format_occurence(opts, t),
+            id,
+            sanitize(&state.events[id].text)
+        ))
+        .collect();
+
+    [vec![format!("<b>{}</b>", locale.tr(Msg::NextHeader))], lines]
+        .concat()
+        .join("\n")
+}
+
+/// Occurrences of a period across all events, grouped by day
+pub(super) fn agenda(opts: &Opts, state: &AgendaState, now: &Instant, period: AgendaPeriod) -> String {
+
+    let locale = opts.locale;
+    let (start, end) = period.range(now);
+
+    let mut occurences: Vec<(Instant, u64)> = state.events.iter()
+        .flat_map(|(id, event)| event.upcoming_occurences(&start)
+            .take_while(|t| *t < end)
+            .map(move |t| (t, *id)))
+        .collect();
+    occurences.sort();
+
+    if occurences.is_empty() {
+        return locale.tr(Msg::NothingComingUp).to_owned();
+    }
+
+    let date_fmt = match opts.date_format {
+        DateFormat::DMY => "%d/%m",
+        DateFormat::MDY => "%m/%d"
+    };
+
+    let mut lines = vec![];
+    let mut curr_day = None;
+
+    for (t, id) in occurences.iter().take(MAX_LINES) {
+
+        if curr_day != Some(t.date()) {
+            if curr_day.is_some() {
+                lines.push(String::new());
+            }
+            lines.push(format!("<b>{} {}</b>", locale.weekday_name(t.weekday()), t.format(date_fmt)));
+            curr_day = Some(t.date());
+        }
+
+        lines.push(format!(
+            "<pre>  {} - [{}] {}</pre>",
+            format_clock_time(opts, t),
+            id,
+            sanitize(&state.events[id].text)
+        ));
+    }
+
+    if occurences.len() > MAX_LINES {
+        lines.push(fill(locale.tr(Msg::AndMore), &[&(occurences.len() - MAX_LINES)]));
+    }
+
+    lines.join("\n")
+}
+
+/// Day and time of an occurrence, as in "Saturday 01/01/2000 9:00"
+pub(super) fn format_occurence(opts: &Opts, t: &Instant) -> String {
+
+    let date_fmt = match opts.date_format {
+        DateFormat::DMY => "%d/%m/%Y",
+        DateFormat::MDY => "%m/%d/%Y"
+    };
+
+    format!(
+        "{} {} {}",
+        opts.locale.weekday_name(t.weekday()),
+        t.format(date_fmt),
+        format_clock_time(opts, t)
+    )
+}
+
+fn format_clock_time(opts: &Opts, t: &Instant) -> String {
+    opts.time_format.clock(t.hour().into(), t.minute().into(), t.second().into())
+}
+
+pub(super) fn start_of_day(date: Date<chrono::Local>) -> Instant {
+    // Midnight can be skipped by a DST change
+    date.and_hms_opt(0, 0, 0).unwrap_or_else(|| date.and_hms(1, 0, 0))
+}
@@ -0,0 +1,177 @@
+use crossbeam_channel::Sender;
+use simple_server::{Server, Method, StatusCode};
+use log::{info, warn};
+use crate::{Opts, BotUpdate};
+use crate::agenda::{CalendarFeed, SharedClock, explain_time};
+use crate::format_error;
+
+#[cfg(test)]
+mod tests;
+
+#[allow(non_camel_case_types)]
+pub struct HTTP_Notifier {
+    opts: Opts,
+    sender: Sender<BotUpdate>,
+    feed: CalendarFeed,
+    feed_token: Option<String>,
+    clock: SharedClock
+}
+
+
+impl HTTP_Notifier {
+
+    pub fn new(
+        opts: &Opts, sender: &Sender<BotUpdate>, feed: CalendarFeed, clock: &SharedClock
+    ) -> Self {
+
+        let feed_token = std::env::var("NAG_CALENDAR_TOKEN")
+            .ok()
+            .filter(|token| !token.is_empty());
+
+        if feed_token.is_none() {
+            info!("NAG_CALENDAR_TOKEN not set, calendar feed disabled");
+        }
+
+        HTTP_Notifier {
+            opts: opts.clone(),
+            sender: sender.clone(),
+            feed,
+            feed_token,
+            clock: clock.clone()
+        }
+    }
+
+    pub fn get_loop(&self) -> impl FnOnce() {
+
+        let sender = self.sender.clone();
+        let opts = self.opts.clone();
+        let feed = self.feed.clone();
+        let feed_token = self.feed_token.clone();
+        let parse_opts = self.opts.clone();
+        let clock = self.clock.clone();
+
+        let server = Server::new(move |request, mut response| {
+
+            match (request.method(), request.uri().path()) {
+
+                (&Method::GET, "/calendar.ics") => {
+
+                    let expected = match &feed_token {
+                        Some(token) => token,
+                        None => {
+                            response.status(StatusCode::NOT_FOUND);
+                            return Ok(response.body(vec![])?);
+                        }
+                    };
+
+                    let token = request.uri().query()
+                        .and_then(|query| get_query_param(query, "token"));
+
+                    if !token.is_some_and(|token| tokens_match(&token, expected)) {
+                        warn!("Calendar feed requested with an invalid token");
+                        response.status(StatusCode::FORBIDDEN);
+                        return Ok(response.body(vec![])?);
+                    }
+
+                    response.status(StatusCode::OK);
+                    Ok(response
+                        .header("Content-Type", "text/calendar; charset=utf-8")
+                        .body(feed.render().into_bytes())?)
+                },
+
+                (&Method::GET, "/when") => {
+
+                    let (status, text) = when_response(&parse_opts, &clock, request.uri().query());
+
+                    response.status(status);
+                    Ok(response
+                        .header("Content-Type", "text/plain; charset=utf-8")
+                        .body(format!("{}\n", text).into_bytes())?)
+                },
+
+                (&Method::POST, _) => {
+
+                    let text = String::from_utf8_lossy(request.body()).into_owned();
+
+                    sender.send(BotUpdate::MsgOut(text)).unwrap();
+
+                    response.status(StatusCode::OK);
+                    Ok(response.body(vec![])?)
+                },
+
+                (_, path) => {
+                    response.status(StatusCode::METHOD_NOT_ALLOWED);
+                    Ok(response.header("Allow", allowed_methods(path)).body(vec![])?)
+                }
+            }
+        });
+
+        move || {
+            server.listen(
+                &opts.endpoint_host,
+                &format!("{}", opts.endpoint_port)
+            );
+        }
+    }
+}
+
+/// Value of the `Allow` header for a request to `path` with the wrong method.
+/// Messages can be posted to any path.
+fn allowed_methods(path: &str) -> &'static str {
+    match path {
+        "/calendar.ics" | "/when" => "GET, POST",
+        _ => "POST"
+    }
+}
+
+/// Answer to `/when?q=...`, lowercased like the messages sent to the bot
+fn when_response(opts: &Opts, clock: &SharedClock, query: Option<&str>) -> (StatusCode, String) {
+
+    let text = query
+        .and_then(|query| get_query_param(query, "q"))
+        .unwrap_or_default()
+        .to_lowercase();
+
+    match explain_time(opts, &clock.now(), &text) {
+        Ok(text) => (StatusCode::OK, text),
+        Err(err) => (StatusCode::BAD_REQUEST, format!("Error: {}", format_error(err)))
+    }
+}
+
+fn get_query_param(query: &str, name: &str) -> Option<String> {
+    query.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _value)| *key == name)
+        .map(|(_key, value)| percent_decode(value))
+}
+
+/// Decodes a query string value ("+" and "%XX" escapes)
+fn percent_decode(value: &str) -> String {
+
+    let mut bytes = vec![];
+    let mut chars = value.bytes();
+
+    while let Some(b) = chars.next() {
+        match b {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex: Vec<u8> = chars.by_ref().take(2).collect();
+                let decoded = std::str::from_utf8(&hex).ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                match decoded {
+                    Some(b) => bytes.push(b),
+                    None => { bytes.push(b'%'); bytes.extend(hex); }
+                }
+            },
+            b => bytes.push(b)
+        }
+    }
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Constant-time comparison, so the token cannot be guessed from response times
+fn tokens_match(a: &str, b: &str) -> bool {
+    a.len() == b.len() &&
+        a.bytes().zip(b.bytes()).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -0,0 +1,42 @@
+#![allow(clippy::zero_prefixed_literal)]
+
+use std::sync::Arc;
+use chrono::TimeZone;
+use clap::Clap;
+use simple_server::StatusCode;
+use crate::Opts;
+use crate::agenda::{Instant, SharedClock};
+use crate::agenda::clock::Clock;
+use super::{allowed_methods, when_response};
+
+struct FixedClock(Instant);
+
+impl Clock for FixedClock {
+    fn now(&self) -> Instant {
+        self.0
+    }
+}
+
+#[test]
+fn when_route() {
+
+    let opts = Opts::parse_from(["placeholder", "placeholder", "--locale", "de"]);
+    let clock: SharedClock = Arc::new(FixedClock(chrono::Local.ymd(2000, 01, 01).and_hms(08, 00, 00)));
+
+    let (status, text) = when_response(&opts, &clock, Some("q=%C3%9CBERMORGEN+MITTAGS+Test"));
+    assert_eq!(status, StatusCode::OK);
+    assert!(text.contains("\"übermorgen\" -> relative"), "{}", text);
+    assert!(text.contains("Cron-Zeile: 03/01/2000 12:00"), "{}", text);
+    assert!(text.ends_with("Mon 03/01/2000 12:00"), "{}", text);
+
+    let (status, text) = when_response(&opts, &clock, None);
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(text.starts_with("Error: "), "{}", text);
+}
+
+#[test]
+fn allow_header() {
+    assert_eq!(allowed_methods("/calendar.ics"), "GET, POST");
+    assert_eq!(allowed_methods("/when"), "GET, POST");
+    assert_eq!(allowed_methods("/"), "POST");
+}
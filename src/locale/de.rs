@@ -0,0 +1,331 @@
+use crate::agenda::CronColumn;
+use super::{Vocabulary, DurationUnits, Period, RelativeDate, Msg};
+
+pub(super) const VOCABULARY: Vocabulary = Vocabulary {
+
+    day_prefixes: &["am", "den", "der"],
+    ordinal_suffixes: r"\.",
+    ordinal_days: &[
+        "ersten", "zweiten", "dritten", "vierten", "fünften", "sechsten", "siebten",
+        "achten", "neunten", "zehnten", "elften", "zwölften", "dreizehnten",
+        "vierzehnten", "fünfzehnten", "sechzehnten", "siebzehnten", "achtzehnten",
+        "neunzehnten", "zwanzigsten", "einundzwanzigsten", "zweiundzwanzigsten",
+        "dreiundzwanzigsten", "vierundzwanzigsten", "fünfundzwanzigsten",
+        "sechsundzwanzigsten", "siebenundzwanzigsten", "achtundzwanzigsten",
+        "neunundzwanzigsten", "dreißigsten", "einunddreißigsten"
+    ],
+
+    month_prefixes: &["im monat", "im"],
+    months: [
+        &["januar", "jänner", "jan"],
+        &["februar", "feb"],
+        &["märz", "maerz", "mär", "mrz"],
+        &["april", "apr"],
+        &["mai"],
+        &["juni", "jun"],
+        &["juli", "jul"],
+        &["august", "aug"],
+        &["september", "sep", "sept"],
+        &["oktober", "okt"],
+        &["november", "nov"],
+        &["dezember", "dez"]
+    ],
+
+    clock_prefixes: &["um", "gegen"],
+    clock_separators: ":",
+    clock_suffixes: &["uhr"],
+
+    period_prefixes: &["in der", "am", "um"],
+    today_period_prefixes: &["heute"],
+    periods: &[
+        ("mittag", Period::Noon),
+        ("mittags", Period::Noon),
+        ("mitternacht", Period::Midnight),
+        ("morgen", Period::Morning),
+        ("morgens", Period::Morning),
+        ("früh", Period::Morning),
+        ("frueh", Period::Morning),
+        ("vormittag", Period::Morning),
+        ("vormittags", Period::Morning),
+        ("nachmittag", Period::Afternoon),
+        ("nachmittags", Period::Afternoon),
+        ("abend", Period::Evening),
+        ("abends", Period::Evening),
+        ("nacht", Period::Night),
+        ("nachts", Period::Night)
+    ],
+    tonight: &[],
+
+    duration_prefixes: &["in"],
+    quantities: &[
+        ("einer halben", 1),
+        ("einem halben", 1),
+        ("eine halbe", 1),
+        ("anderthalb", 3),
+        ("eineinhalb", 3),
+        ("ein paar", 6),
+        ("einigen", 6),
+        ("einer", 2),
+        ("einem", 2),
+        ("einen", 2),
+        ("eine", 2),
+        ("ein", 2)
+    ],
+    and_a_half: &["und eine halbe", "und einer halben", "und einem halben"],
+    half_unit_prefixes: &[],
+    and: &["und"],
+    numbers: &[
+        "null", "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun",
+        "zehn", "elf", "zwölf", "dreizehn", "vierzehn", "fünfzehn", "sechzehn",
+        "siebzehn", "achtzehn", "neunzehn"
+    ],
+    tens: &["zwanzig", "dreißig", "vierzig", "fünfzig", "sechzig"],
+    units: DurationUnits {
+        second: r"^s(ek(unden?)?)?$",
+        minute: r"^min(uten?)?$",
+        hour: r"^(h|std|stunden?)$",
+        day: r"^(t|tag(e|en)?)$",
+        week: r"^wochen?$",
+        month: r"^monat(e|en)?$",
+        year: r"^jahr(e|en)?$"
+    },
+
+    year_prefixes: &["im jahr", "im"],
+
+    every: &["jeden", "jede", "jedes"],
+    every_units: &[
+        ("jahr", CronColumn::Year),
+        ("monat", CronColumn::Month),
+        ("tag", CronColumn::Day),
+        ("stunde", CronColumn::Hour),
+        ("minute", CronColumn::Minute)
+    ],
+
+    date_prefixes: &["am", "den"],
+
+    starting: &["ab dem", "ab", "beginnend am"],
+    until: &["bis zum", "bis"],
+    during: &["für", "fuer"],
+    times: &["mal"],
+
+    relative_dates: &[
+        ("heute", RelativeDate::Days(0)),
+        ("morgen", RelativeDate::Days(1)),
+        ("übermorgen", RelativeDate::Days(2)),
+        ("uebermorgen", RelativeDate::Days(2)),
+        ("nächste woche", RelativeDate::Days(7)),
+        ("naechste woche", RelativeDate::Days(7)),
+        ("kommende woche", RelativeDate::Days(7)),
+        ("nächsten monat", RelativeDate::Months(1)),
+        ("naechsten monat", RelativeDate::Months(1)),
+        ("nächstes jahr", RelativeDate::Months(12)),
+        ("naechstes jahr", RelativeDate::Months(12)),
+        ("am ende des monats", RelativeDate::EndOfMonth),
+        ("ende des monats", RelativeDate::EndOfMonth),
+        ("am monatsende", RelativeDate::EndOfMonth),
+        ("monatsende", RelativeDate::EndOfMonth)
+    ],
+
+    weekdays: [
+        &["montag", "mo."],
+        &["dienstag", "di."],
+        &["mittwoch", "mi."],
+        &["donnerstag", "do."],
+        &["freitag", "fr."],
+        &["samstag", "sonnabend", "sa."],
+        &["sonntag", "so."]
+    ],
+    weekday_prefixes: &["diesen", "am"],
+    next_weekday_prefixes: &["am nächsten", "am kommenden", "nächsten", "naechsten", "kommenden"],
+    next_weekday_suffixes: &[],
+
+    agenda_periods: [&["heute"], &["morgen"], &["woche"]],
+    order_prefixes: &["nach"],
+    event_orders: [&["termin", "nächstem", "naechstem"], &["nummer"], &["schlagwort"]]
+};
+
+pub(super) const EXAMPLES: &[&str] = &[
+    "um 9 Uhr Einkäufe abholen",
+    "jedes Jahr am 4. April Dans Geburtstag",
+    "am 21/08 Zahnarzttermin",
+    "in 30 Minuten Kuchen im Ofen prüfen",
+    "jeden Tag um 17 Uhr Sport machen",
+    "Mama morgen um 18 Uhr anrufen"
+];
+
+pub(super) fn message(msg: Msg) -> Option<&'static str> {
+
+    let text = match msg {
+
+        Msg::Error => "Fehler: {}",
+        Msg::UnknownCommand => "Unbekannter Befehl",
+
+        Msg::CannotParseTime => "Zeitangabe nicht verstanden",
+        Msg::NoMessage => "keine Nachricht angegeben",
+        Msg::NeverOccurs => "Ungültige Zeit: tritt nie ein",
+        Msg::DefaultingTime => "Standarduhrzeit: {}",
+
+        Msg::NoArguments => "Keine Argumente angegeben",
+        Msg::NoEventNumber => "Keine Terminnummer angegeben",
+        Msg::InvalidSelection => "ungültige Termine \"{}\"",
+        Msg::NothingSelected => "Keine Termine passend zu \"{}\"",
+        Msg::SingleEventOnly => "es kann nur ein Termin auf einmal bearbeitet werden",
+        Msg::NoEventAtNumber => "Kein Termin mit dieser Nummer",
+        Msg::NoTimeAndMessage => "Weder Zeit noch Nachricht angegeben",
+        Msg::NoTagSpecified => "Kein Schlagwort angegeben",
+        Msg::NoTimeExpression => "Keine Zeitangabe angegeben",
+        Msg::NoEventsWithTag => "Keine Termine mit dem Schlagwort \"{}\"",
+
+        Msg::EventAdded => "Neuer Termin hinzugefügt (Nummer {}): {}.\nNächstes Mal in {}.",
+        Msg::EventEdited => "Termin {} geändert: {}.\nNächstes Mal in {}.",
+        Msg::EventRemoved => "Termin \"{}\" gelöscht",
+        Msg::NoEventAt => "Fehler: kein Termin mit der Nummer \"{}\"",
+        Msg::EventTagged => "Termin \"{}\" mit \"{}\" verschlagwortet",
+        Msg::EventUntagged => "Schlagwörter von Termin \"{}\" entfernt",
+        Msg::EventNotTagged => "keiner dieser Termine hat das Schlagwort {}",
+        Msg::EventPaused => "Termin \"{}\" pausiert",
+        Msg::EventPausedUntil => "Termin \"{}\" bis {} pausiert",
+        Msg::EventResumed => "Termin \"{}\" fortgesetzt, nächstes Vorkommen in {}",
+        Msg::EventOver => "Termin \"{}\" war während der Pause fällig und wurde entfernt",
+        Msg::NotPaused => "keiner dieser Termine ist pausiert",
+        Msg::PauseUsage => "Verwendung: /pause &lt;Termine&gt; [bis &lt;Datum&gt;], mit einem Datum in der Zukunft",
+        Msg::ResumeUsage => "Verwendung: /resume &lt;Termine&gt;",
+        Msg::Paused => "pausiert",
+        Msg::PausedUntil => "pausiert bis {}",
+
+        Msg::UndoButton => "↩️ Rückgängig",
+        Msg::Undone => "Rückgängig gemacht: {}",
+        Msg::NothingToUndo => "nichts rückgängig zu machen",
+        Msg::NotLastChange => "das ist nicht mehr die letzte Änderung, bitte /undo verwenden",
+        Msg::UndoStale => "{} kann nicht rückgängig gemacht werden, seitdem wurden Erinnerungen dieser Termine gesendet",
+
+        Msg::AmbiguousDate => "Dieses Datum kann unterschiedlich gelesen werden.",
+        Msg::SaveFor => "\"{}\" speichern für:",
+        Msg::NextOn => "{} (nächstes Mal am {})",
+        Msg::CancelButton => "✖️ Abbrechen",
+        Msg::DeleteButton => "🗑 {} löschen",
+        Msg::EditButton => "✏️ {} bearbeiten",
+        Msg::EditHint => "Um Termin {} zu bearbeiten, sende <code>/edit {} &lt;Zeit&gt; &lt;Nachricht&gt;</code>\nDerzeit: {} - {}",
+        Msg::Cancelled => "Abgebrochen",
+        Msg::ConfirmationExpired => "diese Bestätigung ist abgelaufen",
+        Msg::ButtonExpired => "diese Schaltfläche ist abgelaufen",
+
+        Msg::DoneButton => "✅ Erledigt",
+        Msg::MarkedAsDone => "Als erledigt markiert",
+
+        Msg::NoEvents => "Keine Termine",
+        Msg::UntaggedEvents => "Termine ohne Schlagwort:",
+        Msg::TagHeader => "Schlagwort",
+        Msg::TagCount => "{} Termine",
+        Msg::NoTags => "Keine Schlagwörter",
+        Msg::TagsHeader => "Schlagwörter:",
+        Msg::TagRenamed => "Schlagwort {} in {} umbenannt, bei {} Terminen",
+        Msg::RenameTagUsage => "Verwendung: /renametag &lt;alt&gt; &lt;neu&gt;",
+        Msg::DelTagUsage => "Verwendung: /deltag &lt;Schlagwort&gt;",
+        Msg::DelTagChoice => "{} Termine haben das Schlagwort {}. Schlagwort entfernen oder Termine löschen?",
+        Msg::UntagAllButton => "🏷 Schlagwort entfernen",
+        Msg::DeleteAllButton => "🗑 Termine löschen",
+        Msg::TagRemoved => "Schlagwort {} von {} Terminen entfernt",
+        Msg::TaggedEventsRemoved => "Termine mit dem Schlagwort {} gelöscht ({} Termine)",
+        Msg::EventsUsage => "Verwendung: /events [Schlagwort] [nach termin, nummer oder schlagwort]",
+        Msg::PageOf => "Seite {} von {}",
+        Msg::PreviousPage => "◀️ Zurück",
+        Msg::NextPage => "Weiter ▶️",
+
+        Msg::NextHeader => "Demnächst:",
+        Msg::NothingComingUp => "Nichts geplant",
+        Msg::AndMore => "… und {} weitere",
+        Msg::NextUsage => "Verwendung: /next [n]",
+        Msg::AgendaUsage => "Verwendung: /agenda heute, morgen oder woche",
+        Msg::FindUsage => "Verwendung: /find &lt;Wörter&gt;",
+        Msg::FoundHeader => "Passende Termine:",
+        Msg::NothingFound => "Keine Termine passend zu \"{}\"",
+
+        Msg::NoHistory => "Noch keine Erinnerungen gesendet",
+        Msg::HistoryHeader => "Gesendete Erinnerungen:",
+        Msg::HistoryTagHeader => "Gesendete Erinnerungen mit Schlagwort",
+        Msg::HistorySent => "gesendet um {}",
+        Msg::HistoryFailed => "FEHLGESCHLAGEN: {}",
+        Msg::HistoryDone => ", erledigt am {}",
+
+        Msg::CalendarSent => "Hier ist dein Kalender",
+        Msg::EventsExported => "{} Termine exportiert",
+        Msg::ExportProblems => "Einige Termine konnten nicht exportiert werden:",
+        Msg::EventsImported => "{} Termine importiert",
+        Msg::ImportedNumbers => "{} (Nummern {})",
+        Msg::ImportProblems => "Einige Termine wurden übersprungen:",
+        Msg::OnlyIcsFiles => "nur iCalendar-Dateien (.ics) können importiert werden",
+
+        Msg::WhenInterpretation => "Interpretation:",
+        Msg::WhenMessage => "Nachricht",
+        Msg::WhenCronline => "Cron-Zeile: {}",
+        Msg::WhenNeverOccurs => "Tritt nie ein",
+        Msg::WhenNextOccurences => "Nächste Termine:",
+        Msg::WhenStart => "Beginnt am {}",
+        Msg::WhenEnd => "Endet vor {}",
+        Msg::WhenCount => "{} Termine",
+
+        Msg::RecurOnce => "einmalig am {} um {}",
+        Msg::RecurMinute => "jede Minute",
+        Msg::RecurHour => "jede Stunde zur Minute {}",
+        Msg::RecurDay => "jeden Tag um {}",
+        Msg::RecurMonth => "jeden Monat am {} um {}",
+        Msg::RecurYear => "jedes Jahr am {} um {}",
+        Msg::DuringHour => "von {} bis {}",
+        Msg::OnDay => "am {}",
+        Msg::InMonth => "im {}",
+        Msg::InYear => "im Jahr {}",
+
+        Msg::Weeks => "{} Wochen",
+        Msg::Days => "{} Tagen",
+        Msg::Hours => "{} Stunden",
+        Msg::Minutes => "{} Minuten",
+        Msg::LessThanAMinute => "weniger als einer Minute",
+
+        Msg::DescAdd => "Termin \"{}\" hinzufügen",
+        Msg::DescEdit => "Termin \"{}\" ändern",
+        Msg::DescDelete => "{} löschen",
+        Msg::DescTag => "{} verschlagworten",
+        Msg::DescUntag => "Schlagwörter von {} entfernen",
+        Msg::DescPause => "{} pausieren",
+        Msg::DescResume => "{} fortsetzen",
+        Msg::DescRenameTag => "Schlagwort {} umbenennen",
+        Msg::DescUntagAll => "Schlagwort {} entfernen",
+        Msg::DescDeleteTagged => "Termine mit dem Schlagwort {} löschen",
+        Msg::DescImport => "{} Termine aus {} importieren",
+
+        Msg::CurrentLanguage => "Aktuelle Sprache: {}. Verfügbar: {}",
+        Msg::LanguageSet => "Sprache für diesen Chat: {}",
+        Msg::LanguageReset => "Dieser Chat verwendet die Standardsprache ({})",
+        Msg::UnknownLanguage => "Unbekannte Sprache \"{}\"",
+
+        Msg::HelpHelp => "Zeigt diese Nachricht",
+        Msg::HelpEvents => "Listet anstehende Termine auf, die nächsten zuerst oder nach Nummer oder Schlagwort",
+        Msg::HelpEventsTag => "Listet anstehende Termine mit all diesen Schlagwörtern auf, ohne die mit - davor",
+        Msg::HelpDel => "Löscht Termine",
+        Msg::HelpEdit => "Ersetzt Zeit und Nachricht von Termin Nummer &lt;n&gt;",
+        Msg::HelpWhen => "Zeigt, wie &lt;time&gt; verstanden wird und wann es eintreten würde, ohne einen Termin anzulegen",
+        Msg::HelpUndo => "Macht die letzte Änderung an den Terminen rückgängig",
+        Msg::HelpTag => "Fügt Terminen Schlagwörter hinzu",
+        Msg::HelpUntag => "Entfernt diese oder alle Schlagwörter von Terminen",
+        Msg::HelpTags => "Listet die Schlagwörter und ihre Anzahl an Terminen auf",
+        Msg::HelpRenameTag => "Benennt ein Schlagwort bei allen Terminen um",
+        Msg::HelpDelTag => "Entfernt ein Schlagwort von allen Terminen oder löscht diese",
+        Msg::HelpPause => "Hält die Erinnerungen von Terminen an, bis sie fortgesetzt werden oder bis zum angegebenen Datum",
+        Msg::HelpResume => "Setzt die Erinnerungen pausierter Termine fort",
+        Msg::HelpSelection => "&lt;events&gt; sind Terminnummern (<code>3</code>), Listen (<code>1,4,9</code>), Bereiche (<code>3-7</code>) oder Schlagwörter (<code>tag:arbeit</code>).",
+        Msg::HelpExport => "Lädt alle Termine als iCalendar-Datei herunter (zum Importieren eine .ics-Datei senden)",
+        Msg::HelpHistory => "Zeigt die letzten n gesendeten Erinnerungen, optional nur die mit dem Schlagwort [tag]",
+        Msg::HelpNext => "Zeigt die nächsten n Termine aller Ereignisse (standardmäßig 5)",
+        Msg::HelpAgenda => "Zeigt Tag für Tag, was heute, morgen oder in den nächsten 7 Tagen ansteht",
+        Msg::HelpFind => "Sucht Termine nach Wörtern ihrer Nachricht oder Schlagwörter, auch mit Tippfehlern",
+        Msg::HelpLanguage => "Zeigt oder ändert die Sprache dieses Chats (\"default\" für die globale Sprache)",
+        Msg::HelpAddEvent => "Um einen Termin hinzuzufügen, sende \
+            <code>&lt;Zeit&gt; &lt;Nachricht&gt;</code> \
+            ohne <b>/</b> am Anfang. Die Zeit kann auch \
+            irgendwo in der Nachricht stehen.",
+        Msg::HelpExamples => "Beispiele:"
+    };
+
+    Some(text)
+}
@@ -0,0 +1,311 @@
+use crate::agenda::CronColumn;
+use super::{Vocabulary, DurationUnits, Period, RelativeDate, Msg};
+
+pub(super) const VOCABULARY: Vocabulary = Vocabulary {
+
+    day_prefixes: &["on the", "the"],
+    ordinal_suffixes: "st|nd|rd|th",
+    ordinal_days: &[
+        "first", "second", "third", "fourth", "fifth", "sixth", "seventh", "eighth",
+        "ninth", "tenth", "eleventh", "twelfth", "thirteenth", "fourteenth",
+        "fifteenth", "sixteenth", "seventeenth", "eighteenth", "nineteenth",
+        "twentieth", "twenty-first", "twenty-second", "twenty-third", "twenty-fourth",
+        "twenty-fifth", "twenty-sixth", "twenty-seventh", "twenty-eighth",
+        "twenty-ninth", "thirtieth", "thirty-first"
+    ],
+
+    month_prefixes: &["in", "on", "of"],
+    months: [
+        &["january", "jan"],
+        &["february", "feb"],
+        &["march", "mar"],
+        &["april", "apr"],
+        &["may"],
+        &["june", "jun"],
+        &["july", "jul"],
+        &["august", "aug"],
+        &["september", "sep", "sept"],
+        &["october", "oct"],
+        &["november", "nov"],
+        &["december", "dec"]
+    ],
+
+    clock_prefixes: &["at"],
+    clock_separators: ":",
+    clock_suffixes: &[],
+
+    period_prefixes: &["in the", "at"],
+    today_period_prefixes: &["this"],
+    periods: &[
+        ("noon", Period::Noon),
+        ("midday", Period::Noon),
+        ("midnight", Period::Midnight),
+        ("morning", Period::Morning),
+        ("afternoon", Period::Afternoon),
+        ("evening", Period::Evening),
+        ("night", Period::Night)
+    ],
+    tonight: &["tonight"],
+
+    duration_prefixes: &["in"],
+    quantities: &[
+        ("half a", 1),
+        ("half an", 1),
+        ("a couple of", 4),
+        ("couple of", 4),
+        ("a few", 6),
+        ("few", 6),
+        ("a", 2),
+        ("an", 2)
+    ],
+    and_a_half: &["and a half"],
+    half_unit_prefixes: &[],
+    and: &["and"],
+    numbers: &[
+        "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+        "ten", "eleven", "twelve", "thirteen", "fourteen", "fifteen", "sixteen",
+        "seventeen", "eighteen", "nineteen"
+    ],
+    tens: &["twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety"],
+    units: DurationUnits {
+        second: r"^s(ec(onds?)?)?$",
+        minute: r"^m(in(utes?)?)?$",
+        hour: r"^h(ours?)?$",
+        day: r"^d(ays?)?$",
+        week: r"^w(eeks?)?$",
+        month: r"^mo(nths?)?$",
+        year: r"^y(ears?)?$"
+    },
+
+    year_prefixes: &["in"],
+
+    every: &["every"],
+    every_units: &[
+        ("year", CronColumn::Year),
+        ("month", CronColumn::Month),
+        ("day", CronColumn::Day),
+        ("hour", CronColumn::Hour),
+        ("minute", CronColumn::Minute)
+    ],
+
+    date_prefixes: &["on the", "on"],
+
+    starting: &["starting", "starting on", "starting from", "from", "beginning"],
+    until: &["until", "till"],
+    during: &["for"],
+    times: &["times"],
+
+    relative_dates: &[
+        ("today", RelativeDate::Days(0)),
+        ("tomorrow", RelativeDate::Days(1)),
+        ("the day after tomorrow", RelativeDate::Days(2)),
+        ("day after tomorrow", RelativeDate::Days(2)),
+        ("next week", RelativeDate::Days(7)),
+        ("next month", RelativeDate::Months(1)),
+        ("next year", RelativeDate::Months(12)),
+        ("at the end of the month", RelativeDate::EndOfMonth),
+        ("the end of the month", RelativeDate::EndOfMonth),
+        ("end of the month", RelativeDate::EndOfMonth),
+        ("end of month", RelativeDate::EndOfMonth)
+    ],
+
+    weekdays: [
+        &["monday", "mon"],
+        &["tuesday", "tue", "tues"],
+        &["wednesday", "wed"],
+        &["thursday", "thu", "thur", "thurs"],
+        &["friday", "fri"],
+        &["saturday", "sat"],
+        &["sunday", "sun"]
+    ],
+    weekday_prefixes: &["on this", "this", "on"],
+    next_weekday_prefixes: &["on next", "next"],
+    next_weekday_suffixes: &[],
+
+    agenda_periods: [&["today"], &["tomorrow"], &["week"]],
+    order_prefixes: &["by"],
+    event_orders: [&["next"], &["id"], &["tag"]]
+};
+
+pub(super) const EXAMPLES: &[&str] = &[
+    "at 9am pick up groceries",
+    "every year on April 4th Dan's birthday",
+    "on 21/08 dentist appointment",
+    "in 30 minutes check cake in oven",
+    "every day at 5pm do some exercise",
+    "call mom tomorrow at 6pm"
+];
+
+pub(super) fn message(msg: Msg) -> &'static str {
+    match msg {
+
+        Msg::Error => "Error: {}",
+        Msg::UnknownCommand => "Unknown command",
+
+        Msg::CannotParseTime => "cannot parse time",
+        Msg::NoMessage => "no message specified",
+        Msg::NeverOccurs => "Invalid time: never occurs",
+        Msg::DefaultingTime => "Defaulting time to {}",
+
+        Msg::NoArguments => "No arguments specified",
+        Msg::NoEventNumber => "No event number supplied",
+        Msg::InvalidSelection => "invalid events \"{}\"",
+        Msg::NothingSelected => "No events match \"{}\"",
+        Msg::SingleEventOnly => "only one event can be edited at a time",
+        Msg::NoEventAtNumber => "No event at this number",
+        Msg::NoTimeAndMessage => "No time and message specified",
+        Msg::NoTagSpecified => "No tag specified",
+        Msg::NoTimeExpression => "No time expression supplied",
+        Msg::NoEventsWithTag => "No events with tag \"{}\"",
+
+        Msg::EventAdded => "New event added (number {}): {}.\nNext occurence in {}.",
+        Msg::EventEdited => "Edited event {}: {}.\nNext occurence in {}.",
+        Msg::EventRemoved => "Removed event \"{}\"",
+        Msg::NoEventAt => "Error: no event at number \"{}\"",
+        Msg::EventTagged => "Tagged event \"{}\" with \"{}\"",
+        Msg::EventUntagged => "Untagged event \"{}\"",
+        Msg::EventNotTagged => "none of these events is tagged {}",
+        Msg::EventPaused => "Paused event \"{}\"",
+        Msg::EventPausedUntil => "Paused event \"{}\" until {}",
+        Msg::EventResumed => "Resumed event \"{}\", next occurence in {}",
+        Msg::EventOver => "Event \"{}\" was due while paused and has been removed",
+        Msg::NotPaused => "none of these events is paused",
+        Msg::PauseUsage => "usage: /pause &lt;events&gt; [until &lt;date&gt;], with a date in the future",
+        Msg::ResumeUsage => "usage: /resume &lt;events&gt;",
+        Msg::Paused => "paused",
+        Msg::PausedUntil => "paused until {}",
+
+        Msg::UndoButton => "↩️ Undo",
+        Msg::Undone => "Undone: {}",
+        Msg::NothingToUndo => "nothing to undo",
+        Msg::NotLastChange => "this is not the last change anymore, use /undo instead",
+        Msg::UndoStale => "cannot undo {}, reminders of these events were sent since",
+
+        Msg::AmbiguousDate => "This date can be read in more than one way.",
+        Msg::SaveFor => "Save \"{}\" for:",
+        Msg::NextOn => "{} (next on {})",
+        Msg::CancelButton => "✖️ Cancel",
+        Msg::DeleteButton => "🗑 Delete {}",
+        Msg::EditButton => "✏️ Edit {}",
+        Msg::EditHint => "To edit event {}, send <code>/edit {} &lt;time&gt; &lt;message&gt;</code>\nIt is now: {} - {}",
+        Msg::Cancelled => "Cancelled",
+        Msg::ConfirmationExpired => "this confirmation has expired",
+        Msg::ButtonExpired => "this button has expired",
+
+        Msg::DoneButton => "✅ Done",
+        Msg::MarkedAsDone => "Marked as done",
+
+        Msg::NoEvents => "No events",
+        Msg::UntaggedEvents => "Untagged events:",
+        Msg::TagHeader => "Tag",
+        Msg::TagCount => "{} events",
+        Msg::NoTags => "No tags",
+        Msg::TagsHeader => "Tags:",
+        Msg::TagRenamed => "Renamed tag {} to {} on {} events",
+        Msg::RenameTagUsage => "usage: /renametag &lt;old&gt; &lt;new&gt;",
+        Msg::DelTagUsage => "usage: /deltag &lt;tag&gt;",
+        Msg::DelTagChoice => "{} events are tagged {}. Remove the tag from them, or delete them?",
+        Msg::UntagAllButton => "🏷 Remove the tag",
+        Msg::DeleteAllButton => "🗑 Delete the events",
+        Msg::TagRemoved => "Removed tag {} from {} events",
+        Msg::TaggedEventsRemoved => "Deleted the events tagged {} ({} events)",
+        Msg::EventsUsage => "usage: /events [tag] [by next, id or tag]",
+        Msg::PageOf => "Page {} of {}",
+        Msg::PreviousPage => "◀️ Previous",
+        Msg::NextPage => "Next ▶️",
+
+        Msg::NextHeader => "Coming up:",
+        Msg::NothingComingUp => "Nothing coming up",
+        Msg::AndMore => "… and {} more",
+        Msg::NextUsage => "usage: /next [n]",
+        Msg::AgendaUsage => "usage: /agenda today, tomorrow or week",
+        Msg::FindUsage => "usage: /find &lt;words&gt;",
+        Msg::FoundHeader => "Matching events:",
+        Msg::NothingFound => "No events matching \"{}\"",
+
+        Msg::NoHistory => "No reminders sent yet",
+        Msg::HistoryHeader => "Sent reminders:",
+        Msg::HistoryTagHeader => "Sent reminders tagged",
+        Msg::HistorySent => "sent {}",
+        Msg::HistoryFailed => "FAILED: {}",
+        Msg::HistoryDone => ", done at {}",
+
+        Msg::CalendarSent => "Here is your calendar",
+        Msg::EventsExported => "{} events exported",
+        Msg::ExportProblems => "Some events could not be exported:",
+        Msg::EventsImported => "Imported {} events",
+        Msg::ImportedNumbers => "{} (numbers {})",
+        Msg::ImportProblems => "Some events were skipped:",
+        Msg::OnlyIcsFiles => "only iCalendar (.ics) files can be imported",
+
+        Msg::WhenInterpretation => "Interpretation:",
+        Msg::WhenMessage => "message",
+        Msg::WhenCronline => "Cronline: {}",
+        Msg::WhenNeverOccurs => "Never occurs",
+        Msg::WhenNextOccurences => "Next occurences:",
+        Msg::WhenStart => "Starts on {}",
+        Msg::WhenEnd => "Ends before {}",
+        Msg::WhenCount => "{} occurrences",
+
+        Msg::RecurOnce => "once on {} at {}",
+        Msg::RecurMinute => "every minute",
+        Msg::RecurHour => "every hour at minute {}",
+        Msg::RecurDay => "every day at {}",
+        Msg::RecurMonth => "every month on the {} at {}",
+        Msg::RecurYear => "every year on {} at {}",
+        Msg::DuringHour => "from {} to {}",
+        Msg::OnDay => "on the {}",
+        Msg::InMonth => "in {}",
+        Msg::InYear => "in {}",
+
+        Msg::Weeks => "{} weeks",
+        Msg::Days => "{} days",
+        Msg::Hours => "{} hours",
+        Msg::Minutes => "{} minutes",
+        Msg::LessThanAMinute => "less than a minute",
+
+        Msg::DescAdd => "add event \"{}\"",
+        Msg::DescEdit => "edit event \"{}\"",
+        Msg::DescDelete => "delete {}",
+        Msg::DescTag => "tag {}",
+        Msg::DescUntag => "untag {}",
+        Msg::DescPause => "pause {}",
+        Msg::DescResume => "resume {}",
+        Msg::DescRenameTag => "rename tag {}",
+        Msg::DescUntagAll => "remove tag {}",
+        Msg::DescDeleteTagged => "delete events tagged {}",
+        Msg::DescImport => "import {} events from {}",
+
+        Msg::CurrentLanguage => "Current language: {}. Available: {}",
+        Msg::LanguageSet => "Language set to {} for this chat",
+        Msg::LanguageReset => "Using the default language ({}) for this chat",
+        Msg::UnknownLanguage => "Unknown language \"{}\"",
+
+        Msg::HelpHelp => "Show this message",
+        Msg::HelpEvents => "Lists upcoming events, soonest first or by number or tag",
+        Msg::HelpEventsTag => "Lists upcoming events with all these tags, and none of the ones starting with -",
+        Msg::HelpDel => "Deletes events",
+        Msg::HelpEdit => "Replace the time and message of event number &lt;n&gt;",
+        Msg::HelpWhen => "Show how &lt;time&gt; is understood and when it would occur, without adding an event",
+        Msg::HelpUndo => "Revert the last change made to the events",
+        Msg::HelpTag => "Adds tags to events",
+        Msg::HelpUntag => "Removes these tags, or all tags, from events",
+        Msg::HelpTags => "Lists the tags and their number of events",
+        Msg::HelpRenameTag => "Renames a tag on every event",
+        Msg::HelpDelTag => "Removes a tag from every event, or deletes the events",
+        Msg::HelpPause => "Stops reminders of events, until they are resumed or until the given date",
+        Msg::HelpResume => "Resumes reminders of paused events",
+        Msg::HelpSelection => "&lt;events&gt; are event numbers (<code>3</code>), lists (<code>1,4,9</code>), ranges (<code>3-7</code>) or tags (<code>tag:work</code>).",
+        Msg::HelpExport => "Download all events as an iCalendar file (send an .ics file to import one)",
+        Msg::HelpHistory => "Show the last n sent reminders, optionally only those tagged with [tag]",
+        Msg::HelpNext => "Show the next n occurrences of all events (5 by default)",
+        Msg::HelpAgenda => "Show what is coming up today, tomorrow or in the next 7 days, day by day",
+        Msg::HelpFind => "Searches events by words of their message or tags, typos allowed",
+        Msg::HelpLanguage => "Show or change the language of this chat (\"default\" to use the global one)",
+        Msg::HelpAddEvent => "To add a new event, send \
+            <code>&lt;time&gt; &lt;message&gt;</code> \
+            without a leading <b>/</b>. The time can also be \
+            anywhere in the message.",
+        Msg::HelpExamples => "Examples:"
+    }
+}
@@ -0,0 +1,326 @@
+use crate::agenda::CronColumn;
+use super::{Vocabulary, DurationUnits, Period, RelativeDate, Msg};
+
+pub(super) const VOCABULARY: Vocabulary = Vocabulary {
+
+    day_prefixes: &["el día", "el dia", "el"],
+    ordinal_suffixes: "º|°",
+    ordinal_days: &[
+        "primero", "dos", "tres", "cuatro", "cinco", "seis", "siete", "ocho", "nueve",
+        "diez", "once", "doce", "trece", "catorce", "quince", "dieciséis", "diecisiete",
+        "dieciocho", "diecinueve", "veinte", "veintiuno", "veintidós", "veintitrés",
+        "veinticuatro", "veinticinco", "veintiséis", "veintisiete", "veintiocho",
+        "veintinueve", "treinta", "treinta y uno"
+    ],
+
+    month_prefixes: &["en", "de"],
+    months: [
+        &["enero", "ene"],
+        &["febrero", "feb"],
+        &["marzo", "mar"],
+        &["abril", "abr"],
+        &["mayo"],
+        &["junio", "jun"],
+        &["julio", "jul"],
+        &["agosto", "ago"],
+        &["septiembre", "setiembre", "sep", "sept", "set"],
+        &["octubre", "oct"],
+        &["noviembre", "nov"],
+        &["diciembre", "dic"]
+    ],
+
+    clock_prefixes: &["a las", "a la"],
+    clock_separators: ":",
+    clock_suffixes: &[],
+
+    period_prefixes: &["por la", "en la", "a la", "de la", "a", "al"],
+    today_period_prefixes: &["esta", "este"],
+    periods: &[
+        ("mediodía", Period::Noon),
+        ("mediodia", Period::Noon),
+        ("medianoche", Period::Midnight),
+        ("mañana", Period::Morning),
+        ("manana", Period::Morning),
+        ("tarde", Period::Afternoon),
+        ("noche", Period::Night)
+    ],
+    tonight: &[],
+
+    duration_prefixes: &["dentro de", "en"],
+    quantities: &[
+        ("media", 1),
+        ("medio", 1),
+        ("un par de", 4),
+        ("unos pocos", 6),
+        ("unas pocas", 6),
+        ("un", 2),
+        ("una", 2)
+    ],
+    and_a_half: &["y media", "y medio"],
+    half_unit_prefixes: &[],
+    and: &["y"],
+    numbers: &[
+        "cero", "uno", "dos", "tres", "cuatro", "cinco", "seis", "siete", "ocho", "nueve",
+        "diez", "once", "doce", "trece", "catorce", "quince", "dieciséis",
+        "diecisiete", "dieciocho", "diecinueve"
+    ],
+    tens: &["veinte", "treinta", "cuarenta", "cincuenta", "sesenta"],
+    units: DurationUnits {
+        second: r"^s(eg(undos?)?)?$",
+        minute: r"^min(utos?)?$",
+        hour: r"^h(oras?)?$",
+        day: r"^d(ías?|ias?)$",
+        week: r"^semanas?$",
+        month: r"^mes(es)?$",
+        year: r"^(años?|anos?)$"
+    },
+
+    year_prefixes: &["en el año", "en"],
+
+    every: &["cada", "todos los", "todas las"],
+    every_units: &[
+        ("año", CronColumn::Year),
+        ("años", CronColumn::Year),
+        ("mes", CronColumn::Month),
+        ("meses", CronColumn::Month),
+        ("día", CronColumn::Day),
+        ("días", CronColumn::Day),
+        ("dia", CronColumn::Day),
+        ("dias", CronColumn::Day),
+        ("hora", CronColumn::Hour),
+        ("horas", CronColumn::Hour),
+        ("minuto", CronColumn::Minute),
+        ("minutos", CronColumn::Minute)
+    ],
+
+    date_prefixes: &["el día", "el dia", "el"],
+
+    starting: &["a partir del", "a partir de", "desde el", "desde"],
+    until: &["hasta el", "hasta"],
+    during: &["durante"],
+    times: &["veces"],
+
+    relative_dates: &[
+        ("hoy", RelativeDate::Days(0)),
+        ("mañana", RelativeDate::Days(1)),
+        ("manana", RelativeDate::Days(1)),
+        ("pasado mañana", RelativeDate::Days(2)),
+        ("pasado manana", RelativeDate::Days(2)),
+        ("la semana que viene", RelativeDate::Days(7)),
+        ("la próxima semana", RelativeDate::Days(7)),
+        ("la proxima semana", RelativeDate::Days(7)),
+        ("el mes que viene", RelativeDate::Months(1)),
+        ("el próximo mes", RelativeDate::Months(1)),
+        ("el proximo mes", RelativeDate::Months(1)),
+        ("el año que viene", RelativeDate::Months(12)),
+        ("el próximo año", RelativeDate::Months(12)),
+        ("el proximo año", RelativeDate::Months(12)),
+        ("a fin de mes", RelativeDate::EndOfMonth),
+        ("al final del mes", RelativeDate::EndOfMonth),
+        ("a finales de mes", RelativeDate::EndOfMonth),
+        ("fin de mes", RelativeDate::EndOfMonth)
+    ],
+
+    weekdays: [
+        &["lunes", "lun"],
+        &["martes"],
+        &["miércoles", "miercoles", "mié", "mie"],
+        &["jueves", "jue"],
+        &["viernes", "vie"],
+        &["sábado", "sabado", "sáb", "sab"],
+        &["domingo", "dom"]
+    ],
+    weekday_prefixes: &["este", "el"],
+    next_weekday_prefixes: &["el próximo", "el proximo", "el siguiente", "próximo", "proximo"],
+    next_weekday_suffixes: &["que viene"],
+
+    agenda_periods: [&["hoy"], &["mañana", "manana"], &["semana"]],
+    order_prefixes: &["por"],
+    event_orders: [&["próximo", "proximo"], &["número", "numero"], &["etiqueta"]]
+};
+
+pub(super) const EXAMPLES: &[&str] = &[
+    "a las 9:00 recoger la compra",
+    "cada año el 4 de abril cumpleaños de Dan",
+    "el 21/08 cita con el dentista",
+    "en 30 minutos mirar el pastel del horno",
+    "todos los días a las 17:00 hacer ejercicio",
+    "llamar a mamá mañana a las 18:00"
+];
+
+pub(super) fn message(msg: Msg) -> Option<&'static str> {
+
+    let text = match msg {
+
+        Msg::Error => "Error: {}",
+        Msg::UnknownCommand => "Comando desconocido",
+
+        Msg::CannotParseTime => "no se entiende la fecha",
+        Msg::NoMessage => "no se indicó ningún mensaje",
+        Msg::NeverOccurs => "Fecha no válida: nunca ocurre",
+        Msg::DefaultingTime => "Hora por defecto: {}",
+
+        Msg::NoArguments => "No se indicaron argumentos",
+        Msg::NoEventNumber => "No se indicó ningún número de evento",
+        Msg::InvalidSelection => "eventos \"{}\" no válidos",
+        Msg::NothingSelected => "Ningún evento coincide con \"{}\"",
+        Msg::SingleEventOnly => "solo se puede editar un evento a la vez",
+        Msg::NoEventAtNumber => "No hay ningún evento con este número",
+        Msg::NoTimeAndMessage => "No se indicaron fecha ni mensaje",
+        Msg::NoTagSpecified => "No se indicó ninguna etiqueta",
+        Msg::NoTimeExpression => "No se indicó ninguna expresión de fecha",
+        Msg::NoEventsWithTag => "No hay eventos con la etiqueta \"{}\"",
+
+        Msg::EventAdded => "Nuevo evento añadido (número {}): {}.\nPróxima vez dentro de {}.",
+        Msg::EventEdited => "Evento {} modificado: {}.\nPróxima vez dentro de {}.",
+        Msg::EventRemoved => "Evento \"{}\" eliminado",
+        Msg::NoEventAt => "Error: no hay ningún evento con el número \"{}\"",
+        Msg::EventTagged => "Evento \"{}\" etiquetado con \"{}\"",
+        Msg::EventUntagged => "Etiquetas quitadas del evento \"{}\"",
+        Msg::EventNotTagged => "ninguno de estos eventos tiene la etiqueta {}",
+        Msg::EventPaused => "Evento \"{}\" en pausa",
+        Msg::EventPausedUntil => "Evento \"{}\" en pausa hasta el {}",
+        Msg::EventResumed => "Evento \"{}\" reanudado, próxima ocurrencia en {}",
+        Msg::EventOver => "El evento \"{}\" pasó durante la pausa y ha sido eliminado",
+        Msg::NotPaused => "ninguno de estos eventos está en pausa",
+        Msg::PauseUsage => "uso: /pause &lt;eventos&gt; [hasta &lt;fecha&gt;], con una fecha futura",
+        Msg::ResumeUsage => "uso: /resume &lt;eventos&gt;",
+        Msg::Paused => "en pausa",
+        Msg::PausedUntil => "en pausa hasta el {}",
+
+        Msg::UndoButton => "↩️ Deshacer",
+        Msg::Undone => "Deshecho: {}",
+        Msg::NothingToUndo => "no hay nada que deshacer",
+        Msg::NotLastChange => "ya no es el último cambio, usa /undo",
+        Msg::UndoStale => "no se puede deshacer {}, desde entonces se enviaron recordatorios de estos eventos",
+
+        Msg::AmbiguousDate => "Esta fecha se puede leer de varias maneras.",
+        Msg::SaveFor => "Guardar \"{}\" para:",
+        Msg::NextOn => "{} (próxima vez el {})",
+        Msg::CancelButton => "✖️ Cancelar",
+        Msg::DeleteButton => "🗑 Borrar {}",
+        Msg::EditButton => "✏️ Editar {}",
+        Msg::EditHint => "Para editar el evento {}, envía <code>/edit {} &lt;hora&gt; &lt;mensaje&gt;</code>\nAhora es: {} - {}",
+        Msg::Cancelled => "Cancelado",
+        Msg::ConfirmationExpired => "esta confirmación ha caducado",
+        Msg::ButtonExpired => "este botón ha caducado",
+
+        Msg::DoneButton => "✅ Hecho",
+        Msg::MarkedAsDone => "Marcado como hecho",
+
+        Msg::NoEvents => "No hay eventos",
+        Msg::UntaggedEvents => "Eventos sin etiqueta:",
+        Msg::TagHeader => "Etiqueta",
+        Msg::TagCount => "{} eventos",
+        Msg::NoTags => "No hay etiquetas",
+        Msg::TagsHeader => "Etiquetas:",
+        Msg::TagRenamed => "Etiqueta {} renombrada a {} en {} eventos",
+        Msg::RenameTagUsage => "uso: /renametag &lt;antigua&gt; &lt;nueva&gt;",
+        Msg::DelTagUsage => "uso: /deltag &lt;etiqueta&gt;",
+        Msg::DelTagChoice => "{} eventos tienen la etiqueta {}. ¿Quitar la etiqueta o eliminar los eventos?",
+        Msg::UntagAllButton => "🏷 Quitar la etiqueta",
+        Msg::DeleteAllButton => "🗑 Eliminar los eventos",
+        Msg::TagRemoved => "Etiqueta {} quitada de {} eventos",
+        Msg::TaggedEventsRemoved => "Eventos con la etiqueta {} eliminados ({} eventos)",
+        Msg::EventsUsage => "uso: /events [etiqueta] [por próximo, número o etiqueta]",
+        Msg::PageOf => "Página {} de {}",
+        Msg::PreviousPage => "◀️ Anterior",
+        Msg::NextPage => "Siguiente ▶️",
+
+        Msg::NextHeader => "Próximamente:",
+        Msg::NothingComingUp => "Nada previsto",
+        Msg::AndMore => "… y {} más",
+        Msg::NextUsage => "uso: /next [n]",
+        Msg::AgendaUsage => "uso: /agenda hoy, mañana o semana",
+        Msg::FindUsage => "uso: /find &lt;palabras&gt;",
+        Msg::FoundHeader => "Eventos encontrados:",
+        Msg::NothingFound => "Ningún evento coincide con \"{}\"",
+
+        Msg::NoHistory => "Todavía no se ha enviado ningún recordatorio",
+        Msg::HistoryHeader => "Recordatorios enviados:",
+        Msg::HistoryTagHeader => "Recordatorios enviados con la etiqueta",
+        Msg::HistorySent => "enviado a las {}",
+        Msg::HistoryFailed => "FALLÓ: {}",
+        Msg::HistoryDone => ", hecho el {}",
+
+        Msg::CalendarSent => "Aquí está tu calendario",
+        Msg::EventsExported => "{} eventos exportados",
+        Msg::ExportProblems => "Algunos eventos no se pudieron exportar:",
+        Msg::EventsImported => "{} eventos importados",
+        Msg::ImportedNumbers => "{} (números {})",
+        Msg::ImportProblems => "Algunos eventos se omitieron:",
+        Msg::OnlyIcsFiles => "solo se pueden importar archivos iCalendar (.ics)",
+
+        Msg::WhenInterpretation => "Interpretación:",
+        Msg::WhenMessage => "mensaje",
+        Msg::WhenCronline => "Línea cron: {}",
+        Msg::WhenNeverOccurs => "Nunca ocurre",
+        Msg::WhenNextOccurences => "Próximas veces:",
+        Msg::WhenStart => "Empieza el {}",
+        Msg::WhenEnd => "Termina antes del {}",
+        Msg::WhenCount => "{} veces",
+
+        Msg::RecurOnce => "una vez el {} a las {}",
+        Msg::RecurMinute => "cada minuto",
+        Msg::RecurHour => "cada hora en el minuto {}",
+        Msg::RecurDay => "cada día a las {}",
+        Msg::RecurMonth => "cada mes el día {} a las {}",
+        Msg::RecurYear => "cada año el {} a las {}",
+        Msg::DuringHour => "de {} a {}",
+        Msg::OnDay => "el día {}",
+        Msg::InMonth => "en {}",
+        Msg::InYear => "en {}",
+
+        Msg::Weeks => "{} semanas",
+        Msg::Days => "{} días",
+        Msg::Hours => "{} horas",
+        Msg::Minutes => "{} minutos",
+        Msg::LessThanAMinute => "menos de un minuto",
+
+        Msg::DescAdd => "añadir el evento \"{}\"",
+        Msg::DescEdit => "modificar el evento \"{}\"",
+        Msg::DescDelete => "eliminar {}",
+        Msg::DescTag => "etiquetar {}",
+        Msg::DescUntag => "quitar las etiquetas de {}",
+        Msg::DescPause => "pausar {}",
+        Msg::DescResume => "reanudar {}",
+        Msg::DescRenameTag => "renombrar la etiqueta {}",
+        Msg::DescUntagAll => "quitar la etiqueta {}",
+        Msg::DescDeleteTagged => "eliminar los eventos con la etiqueta {}",
+        Msg::DescImport => "importar {} eventos de {}",
+
+        Msg::CurrentLanguage => "Idioma actual: {}. Disponibles: {}",
+        Msg::LanguageSet => "Idioma de este chat: {}",
+        Msg::LanguageReset => "Este chat usa el idioma por defecto ({})",
+        Msg::UnknownLanguage => "Idioma desconocido \"{}\"",
+
+        Msg::HelpHelp => "Muestra este mensaje",
+        Msg::HelpEvents => "Lista los próximos eventos, los más cercanos primero o por número o etiqueta",
+        Msg::HelpEventsTag => "Lista los próximos eventos con todas estas etiquetas, y ninguna de las precedidas de -",
+        Msg::HelpDel => "Elimina eventos",
+        Msg::HelpEdit => "Sustituye la fecha y el mensaje del evento número &lt;n&gt;",
+        Msg::HelpWhen => "Muestra cómo se entiende &lt;time&gt; y cuándo ocurriría, sin añadir ningún evento",
+        Msg::HelpUndo => "Deshace el último cambio en los eventos",
+        Msg::HelpTag => "Añade etiquetas a eventos",
+        Msg::HelpUntag => "Quita estas etiquetas, o todas, de los eventos",
+        Msg::HelpTags => "Lista las etiquetas y su número de eventos",
+        Msg::HelpRenameTag => "Renombra una etiqueta en todos los eventos",
+        Msg::HelpDelTag => "Quita una etiqueta de todos los eventos, o los elimina",
+        Msg::HelpPause => "Detiene los recordatorios de eventos, hasta reanudarlos o hasta la fecha indicada",
+        Msg::HelpResume => "Reanuda los recordatorios de eventos en pausa",
+        Msg::HelpSelection => "&lt;events&gt; son números de evento (<code>3</code>), listas (<code>1,4,9</code>), intervalos (<code>3-7</code>) o etiquetas (<code>tag:trabajo</code>).",
+        Msg::HelpExport => "Descarga todos los eventos en un archivo iCalendar (envía un archivo .ics para importar uno)",
+        Msg::HelpHistory => "Muestra los últimos n recordatorios enviados, opcionalmente solo los de la etiqueta [tag]",
+        Msg::HelpNext => "Muestra las próximas n veces de todos los eventos (5 por defecto)",
+        Msg::HelpAgenda => "Muestra día a día lo previsto para hoy, mañana o los próximos 7 días",
+        Msg::HelpFind => "Busca eventos por palabras de su mensaje o etiquetas, aunque tengan erratas",
+        Msg::HelpLanguage => "Muestra o cambia el idioma de este chat (\"default\" para el idioma global)",
+        Msg::HelpAddEvent => "Para añadir un evento, envía \
+            <code>&lt;fecha&gt; &lt;mensaje&gt;</code> \
+            sin <b>/</b> al principio. La fecha también puede \
+            estar en cualquier parte del mensaje.",
+        Msg::HelpExamples => "Ejemplos:"
+    };
+
+    Some(text)
+}
@@ -0,0 +1,324 @@
+use crate::agenda::CronColumn;
+use super::{Vocabulary, DurationUnits, Period, RelativeDate, Msg};
+
+pub(super) const VOCABULARY: Vocabulary = Vocabulary {
+
+    day_prefixes: &["le"],
+    ordinal_suffixes: "er|e|ème|eme",
+    ordinal_days: &[
+        "premier", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf",
+        "dix", "onze", "douze", "treize", "quatorze", "quinze", "seize", "dix-sept",
+        "dix-huit", "dix-neuf", "vingt", "vingt-et-un", "vingt-deux", "vingt-trois",
+        "vingt-quatre", "vingt-cinq", "vingt-six", "vingt-sept", "vingt-huit",
+        "vingt-neuf", "trente", "trente-et-un"
+    ],
+
+    month_prefixes: &["au mois de", "en", "de"],
+    months: [
+        &["janvier", "janv"],
+        &["février", "fevrier", "févr", "fevr"],
+        &["mars"],
+        &["avril", "avr"],
+        &["mai"],
+        &["juin"],
+        &["juillet", "juil"],
+        &["août", "aout"],
+        &["septembre", "sept"],
+        &["octobre", "oct"],
+        &["novembre", "nov"],
+        &["décembre", "decembre", "déc", "dec"]
+    ],
+
+    clock_prefixes: &["à", "a", "vers"],
+    clock_separators: ":|h",
+    clock_suffixes: &["heures", "heure"],
+
+    period_prefixes: &["dans la", "dans", "à", "a", "le", "en", "au"],
+    today_period_prefixes: &["ce", "cet", "cette"],
+    periods: &[
+        ("midi", Period::Noon),
+        ("minuit", Period::Midnight),
+        ("matin", Period::Morning),
+        ("matins", Period::Morning),
+        ("matinée", Period::Morning),
+        ("après-midi", Period::Afternoon),
+        ("apres-midi", Period::Afternoon),
+        ("l'après-midi", Period::Afternoon),
+        ("l'apres-midi", Period::Afternoon),
+        ("soir", Period::Evening),
+        ("soirs", Period::Evening),
+        ("soirée", Period::Evening),
+        ("nuit", Period::Night),
+        ("nuits", Period::Night)
+    ],
+    tonight: &[],
+
+    duration_prefixes: &["dans", "d'ici"],
+    quantities: &[
+        ("quelques", 6),
+        ("un", 2),
+        ("une", 2)
+    ],
+    and_a_half: &["et demie", "et demi"],
+    half_unit_prefixes: &["demi-"],
+    and: &["et"],
+    numbers: &[
+        "zéro", "un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf",
+        "dix", "onze", "douze", "treize", "quatorze", "quinze", "seize",
+        "dix-sept", "dix-huit", "dix-neuf"
+    ],
+    tens: &["vingt", "trente", "quarante", "cinquante", "soixante"],
+    units: DurationUnits {
+        second: r"^s(ec(ondes?)?)?$",
+        minute: r"^m(in(utes?)?)?$",
+        hour: r"^h(eures?)?$",
+        day: r"^j(ours?)?$",
+        week: r"^sem(aines?)?$",
+        month: r"^mois$",
+        year: r"^(ans?|années?|annees?)$"
+    },
+
+    year_prefixes: &["en"],
+
+    every: &["chaque", "tous les", "toutes les"],
+    every_units: &[
+        ("an", CronColumn::Year),
+        ("ans", CronColumn::Year),
+        ("année", CronColumn::Year),
+        ("années", CronColumn::Year),
+        ("mois", CronColumn::Month),
+        ("jour", CronColumn::Day),
+        ("jours", CronColumn::Day),
+        ("heure", CronColumn::Hour),
+        ("heures", CronColumn::Hour),
+        ("minute", CronColumn::Minute),
+        ("minutes", CronColumn::Minute)
+    ],
+
+    date_prefixes: &["le"],
+
+    starting: &["à partir de", "à partir du", "a partir de", "a partir du", "dès"],
+    until: &["jusqu'au", "jusqu'à", "jusqu'a", "jusqu'en"],
+    during: &["pendant", "durant"],
+    times: &["fois"],
+
+    relative_dates: &[
+        ("aujourd'hui", RelativeDate::Days(0)),
+        ("demain", RelativeDate::Days(1)),
+        ("après-demain", RelativeDate::Days(2)),
+        ("apres-demain", RelativeDate::Days(2)),
+        ("la semaine prochaine", RelativeDate::Days(7)),
+        ("semaine prochaine", RelativeDate::Days(7)),
+        ("le mois prochain", RelativeDate::Months(1)),
+        ("mois prochain", RelativeDate::Months(1)),
+        ("l'année prochaine", RelativeDate::Months(12)),
+        ("l'an prochain", RelativeDate::Months(12)),
+        ("à la fin du mois", RelativeDate::EndOfMonth),
+        ("a la fin du mois", RelativeDate::EndOfMonth),
+        ("en fin de mois", RelativeDate::EndOfMonth),
+        ("fin du mois", RelativeDate::EndOfMonth)
+    ],
+
+    weekdays: [
+        &["lundi", "lun"],
+        &["mardi", "mar"],
+        &["mercredi", "mer"],
+        &["jeudi", "jeu"],
+        &["vendredi", "ven"],
+        &["samedi", "sam"],
+        &["dimanche", "dim"]
+    ],
+    weekday_prefixes: &["ce", "le"],
+    next_weekday_prefixes: &[],
+    next_weekday_suffixes: &["prochain"],
+
+    agenda_periods: [&["aujourd'hui", "aujourd’hui"], &["demain"], &["semaine"]],
+    order_prefixes: &["par"],
+    event_orders: [&["prochain"], &["numéro", "numero"], &["étiquette", "etiquette"]]
+};
+
+pub(super) const EXAMPLES: &[&str] = &[
+    "à 9h faire les courses",
+    "tous les ans le 4 avril anniversaire de Dan",
+    "le 21/08 rendez-vous chez le dentiste",
+    "dans 30 minutes sortir le gâteau du four",
+    "tous les jours à 17h faire du sport",
+    "appeler maman demain à 18h"
+];
+
+pub(super) fn message(msg: Msg) -> Option<&'static str> {
+
+    let text = match msg {
+
+        Msg::Error => "Erreur : {}",
+        Msg::UnknownCommand => "Commande inconnue",
+
+        Msg::CannotParseTime => "impossible de comprendre la date",
+        Msg::NoMessage => "aucun message indiqué",
+        Msg::NeverOccurs => "Date invalide : ne se produit jamais",
+        Msg::DefaultingTime => "Heure par défaut : {}",
+
+        Msg::NoArguments => "Aucun argument indiqué",
+        Msg::NoEventNumber => "Aucun numéro d'événement indiqué",
+        Msg::InvalidSelection => "événements \"{}\" invalides",
+        Msg::NothingSelected => "Aucun événement ne correspond à \"{}\"",
+        Msg::SingleEventOnly => "un seul événement peut être modifié à la fois",
+        Msg::NoEventAtNumber => "Aucun événement à ce numéro",
+        Msg::NoTimeAndMessage => "Aucune date ni message indiqués",
+        Msg::NoTagSpecified => "Aucune étiquette indiquée",
+        Msg::NoTimeExpression => "Aucune expression de date indiquée",
+        Msg::NoEventsWithTag => "Aucun événement avec l'étiquette \"{}\"",
+
+        Msg::EventAdded => "Nouvel événement ajouté (numéro {}) : {}.\nProchaine occurrence dans {}.",
+        Msg::EventEdited => "Événement {} modifié : {}.\nProchaine occurrence dans {}.",
+        Msg::EventRemoved => "Événement \"{}\" supprimé",
+        Msg::NoEventAt => "Erreur : aucun événement au numéro \"{}\"",
+        Msg::EventTagged => "Événement \"{}\" étiqueté \"{}\"",
+        Msg::EventUntagged => "Étiquettes retirées de l'événement \"{}\"",
+        Msg::EventNotTagged => "aucun de ces événements n'est étiqueté {}",
+        Msg::EventPaused => "Événement \"{}\" mis en pause",
+        Msg::EventPausedUntil => "Événement \"{}\" mis en pause jusqu'au {}",
+        Msg::EventResumed => "Événement \"{}\" repris, prochaine occurrence dans {}",
+        Msg::EventOver => "L'événement \"{}\" est passé pendant la pause et a été supprimé",
+        Msg::NotPaused => "aucun de ces événements n'est en pause",
+        Msg::PauseUsage => "utilisation : /pause &lt;événements&gt; [jusqu'au &lt;date&gt;], avec une date future",
+        Msg::ResumeUsage => "utilisation : /resume &lt;événements&gt;",
+        Msg::Paused => "en pause",
+        Msg::PausedUntil => "en pause jusqu'au {}",
+
+        Msg::UndoButton => "↩️ Annuler",
+        Msg::Undone => "Annulé : {}",
+        Msg::NothingToUndo => "rien à annuler",
+        Msg::NotLastChange => "ce n'est plus la dernière modification, utilisez /undo",
+        Msg::UndoStale => "impossible d'annuler {}, des rappels de ces événements ont été envoyés depuis",
+
+        Msg::AmbiguousDate => "Cette date peut se lire de plusieurs façons.",
+        Msg::SaveFor => "Enregistrer \"{}\" pour :",
+        Msg::NextOn => "{} (prochaine fois le {})",
+        Msg::CancelButton => "✖️ Annuler",
+        Msg::DeleteButton => "🗑 Supprimer {}",
+        Msg::EditButton => "✏️ Modifier {}",
+        Msg::EditHint => "Pour modifier l'événement {}, envoyez <code>/edit {} &lt;heure&gt; &lt;message&gt;</code>\nActuellement : {} - {}",
+        Msg::Cancelled => "Annulé",
+        Msg::ConfirmationExpired => "cette confirmation a expiré",
+        Msg::ButtonExpired => "ce bouton a expiré",
+
+        Msg::DoneButton => "✅ Fait",
+        Msg::MarkedAsDone => "Marqué comme fait",
+
+        Msg::NoEvents => "Aucun événement",
+        Msg::UntaggedEvents => "Événements sans étiquette :",
+        Msg::TagHeader => "Étiquette",
+        Msg::TagCount => "{} événements",
+        Msg::NoTags => "Aucune étiquette",
+        Msg::TagsHeader => "Étiquettes :",
+        Msg::TagRenamed => "Étiquette {} renommée en {} sur {} événements",
+        Msg::RenameTagUsage => "utilisation : /renametag &lt;ancienne&gt; &lt;nouvelle&gt;",
+        Msg::DelTagUsage => "utilisation : /deltag &lt;étiquette&gt;",
+        Msg::DelTagChoice => "{} événements sont étiquetés {}. Retirer l'étiquette, ou supprimer les événements ?",
+        Msg::UntagAllButton => "🏷 Retirer l'étiquette",
+        Msg::DeleteAllButton => "🗑 Supprimer les événements",
+        Msg::TagRemoved => "Étiquette {} retirée de {} événements",
+        Msg::TaggedEventsRemoved => "Événements étiquetés {} supprimés ({} événements)",
+        Msg::EventsUsage => "utilisation : /events [étiquette] [par prochain, numéro ou étiquette]",
+        Msg::PageOf => "Page {} sur {}",
+        Msg::PreviousPage => "◀️ Précédente",
+        Msg::NextPage => "Suivante ▶️",
+
+        Msg::NextHeader => "À venir :",
+        Msg::NothingComingUp => "Rien de prévu",
+        Msg::AndMore => "… et {} de plus",
+        Msg::NextUsage => "utilisation : /next [n]",
+        Msg::AgendaUsage => "utilisation : /agenda aujourd'hui, demain ou semaine",
+        Msg::FindUsage => "utilisation : /find &lt;mots&gt;",
+        Msg::FoundHeader => "Événements correspondants :",
+        Msg::NothingFound => "Aucun événement ne correspond à \"{}\"",
+
+        Msg::NoHistory => "Aucun rappel envoyé pour l'instant",
+        Msg::HistoryHeader => "Rappels envoyés :",
+        Msg::HistoryTagHeader => "Rappels envoyés avec l'étiquette",
+        Msg::HistorySent => "envoyé à {}",
+        Msg::HistoryFailed => "ÉCHEC : {}",
+        Msg::HistoryDone => ", fait le {}",
+
+        Msg::CalendarSent => "Voici votre calendrier",
+        Msg::EventsExported => "{} événements exportés",
+        Msg::ExportProblems => "Certains événements n'ont pas pu être exportés :",
+        Msg::EventsImported => "{} événements importés",
+        Msg::ImportedNumbers => "{} (numéros {})",
+        Msg::ImportProblems => "Certains événements ont été ignorés :",
+        Msg::OnlyIcsFiles => "seuls les fichiers iCalendar (.ics) peuvent être importés",
+
+        Msg::WhenInterpretation => "Interprétation :",
+        Msg::WhenMessage => "message",
+        Msg::WhenCronline => "Ligne cron : {}",
+        Msg::WhenNeverOccurs => "Ne se produit jamais",
+        Msg::WhenNextOccurences => "Prochaines occurrences :",
+        Msg::WhenStart => "Commence le {}",
+        Msg::WhenEnd => "Se termine avant le {}",
+        Msg::WhenCount => "{} occurrences",
+
+        Msg::RecurOnce => "une fois le {} à {}",
+        Msg::RecurMinute => "toutes les minutes",
+        Msg::RecurHour => "toutes les heures à la minute {}",
+        Msg::RecurDay => "tous les jours à {}",
+        Msg::RecurMonth => "tous les mois le {} à {}",
+        Msg::RecurYear => "tous les ans le {} à {}",
+        Msg::DuringHour => "de {} à {}",
+        Msg::OnDay => "le {}",
+        Msg::InMonth => "en {}",
+        Msg::InYear => "en {}",
+
+        Msg::Weeks => "{} semaines",
+        Msg::Days => "{} jours",
+        Msg::Hours => "{} heures",
+        Msg::Minutes => "{} minutes",
+        Msg::LessThanAMinute => "moins d'une minute",
+
+        Msg::DescAdd => "ajout de l'événement \"{}\"",
+        Msg::DescEdit => "modification de l'événement \"{}\"",
+        Msg::DescDelete => "suppression de {}",
+        Msg::DescTag => "étiquetage de {}",
+        Msg::DescUntag => "retrait des étiquettes de {}",
+        Msg::DescPause => "mise en pause de {}",
+        Msg::DescResume => "reprise de {}",
+        Msg::DescRenameTag => "renommage de l'étiquette {}",
+        Msg::DescUntagAll => "retrait de l'étiquette {}",
+        Msg::DescDeleteTagged => "suppression des événements étiquetés {}",
+        Msg::DescImport => "import de {} événements depuis {}",
+
+        Msg::CurrentLanguage => "Langue actuelle : {}. Disponibles : {}",
+        Msg::LanguageSet => "Langue de cette conversation : {}",
+        Msg::LanguageReset => "Cette conversation utilise la langue par défaut ({})",
+        Msg::UnknownLanguage => "Langue inconnue : \"{}\"",
+
+        Msg::HelpHelp => "Affiche ce message",
+        Msg::HelpEvents => "Liste les événements à venir, les plus proches d'abord ou par numéro ou étiquette",
+        Msg::HelpEventsTag => "Liste les événements à venir portant toutes ces étiquettes, et aucune de celles précédées de -",
+        Msg::HelpDel => "Supprime des événements",
+        Msg::HelpEdit => "Remplace la date et le message de l'événement numéro &lt;n&gt;",
+        Msg::HelpWhen => "Montre comment &lt;time&gt; est compris et quand il se produirait, sans ajouter d'événement",
+        Msg::HelpUndo => "Annule la dernière modification des événements",
+        Msg::HelpTag => "Ajoute des étiquettes à des événements",
+        Msg::HelpUntag => "Retire ces étiquettes, ou toutes, de ces événements",
+        Msg::HelpTags => "Liste les étiquettes et leur nombre d'événements",
+        Msg::HelpRenameTag => "Renomme une étiquette sur tous les événements",
+        Msg::HelpDelTag => "Retire une étiquette de tous les événements, ou supprime ceux-ci",
+        Msg::HelpPause => "Suspend les rappels d'événements, jusqu'à leur reprise ou jusqu'à la date donnée",
+        Msg::HelpResume => "Reprend les rappels d'événements en pause",
+        Msg::HelpSelection => "&lt;events&gt; peut être un numéro d'événement (<code>3</code>), une liste (<code>1,4,9</code>), un intervalle (<code>3-7</code>) ou une étiquette (<code>tag:travail</code>).",
+        Msg::HelpExport => "Télécharge tous les événements dans un fichier iCalendar (envoyez un fichier .ics pour en importer un)",
+        Msg::HelpHistory => "Affiche les n derniers rappels envoyés, éventuellement seulement ceux étiquetés [tag]",
+        Msg::HelpNext => "Affiche les n prochaines occurrences de tous les événements (5 par défaut)",
+        Msg::HelpAgenda => "Affiche jour par jour ce qui est prévu aujourd'hui, demain ou dans les 7 prochains jours",
+        Msg::HelpFind => "Cherche des événements par les mots de leur message ou leurs étiquettes, fautes de frappe comprises",
+        Msg::HelpLanguage => "Affiche ou change la langue de cette conversation (\"default\" pour la langue globale)",
+        Msg::HelpAddEvent => "Pour ajouter un événement, envoyez \
+            <code>&lt;date&gt; &lt;message&gt;</code> \
+            sans <b>/</b> au début. La date peut aussi se trouver \
+            n'importe où dans le message.",
+        Msg::HelpExamples => "Exemples :"
+    };
+
+    Some(text)
+}
@@ -0,0 +1,449 @@
+use std::fmt::Display;
+use std::str::FromStr;
+use clap::{Clap, ArgEnum};
+use serde::{Deserialize, Serialize};
+use crate::DateFormat;
+use crate::agenda::CronColumn;
+
+mod en;
+mod fr;
+mod de;
+mod es;
+
+/// Language used to parse times and to write replies
+#[derive(ArgEnum, Clap, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    En,
+    Fr,
+    De,
+    Es
+}
+
+pub const LOCALES: [Locale; 4] = [Locale::En, Locale::Fr, Locale::De, Locale::Es];
+
+impl FromStr for Locale {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        LOCALES.iter()
+            .find(|locale| locale.code() == s.to_lowercase())
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("Cannot parse {}", s))
+    }
+}
+
+impl Locale {
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Fr => "fr",
+            Locale::De => "de",
+            Locale::Es => "es"
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Locale::En => "English",
+            Locale::Fr => "Français",
+            Locale::De => "Deutsch",
+            Locale::Es => "Español"
+        }
+    }
+
+    pub(crate) fn vocabulary(&self) -> &'static Vocabulary {
+        match self {
+            Locale::En => &en::VOCABULARY,
+            Locale::Fr => &fr::VOCABULARY,
+            Locale::De => &de::VOCABULARY,
+            Locale::Es => &es::VOCABULARY
+        }
+    }
+
+    /// Text of a reply, falling back to English when there is no translation
+    pub(crate) fn tr(&self, msg: Msg) -> &'static str {
+        let text = match self {
+            Locale::En => None,
+            Locale::Fr => fr::message(msg),
+            Locale::De => de::message(msg),
+            Locale::Es => es::message(msg)
+        };
+        text.unwrap_or_else(|| en::message(msg))
+    }
+
+    /// Capitalized full name of a day of the week
+    pub(crate) fn weekday_name(&self, weekday: chrono::Weekday) -> String {
+        capitalize(self.vocabulary().weekdays[weekday.num_days_from_monday() as usize][0])
+    }
+
+    /// Full name of a month (1 to 12), as written inside a sentence
+    pub(crate) fn month_name(&self, month: u64) -> String {
+
+        let name = match (month as usize).checked_sub(1).and_then(|i| self.vocabulary().months.get(i)) {
+            Some(names) => names[0],
+            None => return month.to_string()
+        };
+
+        match self {
+            Locale::En | Locale::De => capitalize(name),
+            Locale::Fr | Locale::Es => name.to_owned()
+        }
+    }
+
+    /// Day of the month, as in "on the 5th"
+    pub(crate) fn ordinal_day(&self, day: u64) -> String {
+        match self {
+            Locale::En => {
+                let suffix = match (day % 10, day % 100) {
+                    (_, 11..=13) => "th",
+                    (1, _) => "st",
+                    (2, _) => "nd",
+                    (3, _) => "rd",
+                    _ => "th"
+                };
+                format!("{}{}", day, suffix)
+            },
+            Locale::Fr if day == 1 => "1er".to_owned(),
+            Locale::Fr | Locale::Es => day.to_string(),
+            Locale::De => format!("{}.", day)
+        }
+    }
+
+    /// Day and month written out, with the year if given. Only English has
+    /// a month-first order, picked with the date format.
+    pub(crate) fn written_date(
+        &self, day: u64, month: u64, year: Option<u64>, date_format: &DateFormat
+    ) -> String {
+
+        let month = self.month_name(month);
+
+        match (self, date_format, year) {
+            (Locale::En, DateFormat::MDY, None) => format!("{} {}", month, self.ordinal_day(day)),
+            (Locale::En, DateFormat::MDY, Some(year)) => format!("{} {}, {}", month, self.ordinal_day(day), year),
+            (Locale::Es, _, None) => format!("{} de {}", day, month),
+            (Locale::Es, _, Some(year)) => format!("{} de {} de {}", day, month, year),
+            (_, _, None) => format!("{} {}", self.day_in_date(day), month),
+            (_, _, Some(year)) => format!("{} {} {}", self.day_in_date(day), month, year)
+        }
+    }
+
+    /// Day number before the name of the month ("1er mai", "1. Mai", "1 May")
+    fn day_in_date(&self, day: u64) -> String {
+        match self {
+            Locale::Fr | Locale::De => self.ordinal_day(day),
+            Locale::En | Locale::Es => day.to_string()
+        }
+    }
+
+    /// Example event specifications for the help message
+    pub(crate) fn examples(&self) -> &'static [&'static str] {
+        match self {
+            Locale::En => en::EXAMPLES,
+            Locale::Fr => fr::EXAMPLES,
+            Locale::De => de::EXAMPLES,
+            Locale::Es => es::EXAMPLES
+        }
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new()
+    }
+}
+
+/// Replaces each "{}" of a message template with the next argument
+pub(crate) fn fill(template: &str, args: &[&dyn Display]) -> String {
+
+    let mut parts = template.split("{}");
+    let mut text = parts.next().unwrap_or_default().to_owned();
+
+    for (i, part) in parts.enumerate() {
+        if let Some(arg) = args.get(i) {
+            text.push_str(&arg.to_string());
+        }
+        text.push_str(part);
+    }
+
+    text
+}
+
+/// Words the time parsers and command arguments look for. Phrases are lowercase
+/// and may span several words ("on the"); lists are tried longest phrase first.
+pub(crate) struct Vocabulary {
+
+    /// Before a day of the month ("on the 5th")
+    pub day_prefixes: &'static [&'static str],
+    /// Regex alternatives for what may follow a day number ("st|nd")
+    pub ordinal_suffixes: &'static str,
+    /// Days of the month written out, their value is their position plus one
+    pub ordinal_days: &'static [&'static str],
+
+    pub month_prefixes: &'static [&'static str],
+    pub months: [&'static [&'static str]; 12],
+
+    pub clock_prefixes: &'static [&'static str],
+    /// Regex alternatives between hours and minutes (":")
+    pub clock_separators: &'static str,
+    /// Words after a number marking it as a time of day ("Uhr")
+    pub clock_suffixes: &'static [&'static str],
+
+    /// Before a time of day, which may be a name ("in the evening")
+    pub period_prefixes: &'static [&'static str],
+    /// Meaning "today, at that time of day" ("this evening")
+    pub today_period_prefixes: &'static [&'static str],
+    pub periods: &'static [(&'static str, Period)],
+    /// Words standing for "today at night"
+    pub tonight: &'static [&'static str],
+
+    pub duration_prefixes: &'static [&'static str],
+    /// Quantities before a unit, counted in halves ("a" is 2, "half an" is 1)
+    pub quantities: &'static [(&'static str, i64)],
+    /// After a unit, adds half of it ("and a half")
+    pub and_a_half: &'static [&'static str],
+    /// Glued to a unit to take half of it ("demi-heure")
+    pub half_unit_prefixes: &'static [&'static str],
+    /// Between the terms of a compound duration
+    pub and: &'static [&'static str],
+    /// Numbers written out, their value is their position
+    pub numbers: &'static [&'static str],
+    /// Multiples of ten written out, from twenty
+    pub tens: &'static [&'static str],
+    pub units: DurationUnits,
+
+    pub year_prefixes: &'static [&'static str],
+
+    pub every: &'static [&'static str],
+    /// Unit names after "every"
+    pub every_units: &'static [(&'static str, CronColumn)],
+
+    pub date_prefixes: &'static [&'static str],
+
+    /// Before the first date of a recurring event ("starting monday")
+    pub starting: &'static [&'static str],
+    /// Before the last date of a recurring event ("until march 10")
+    pub until: &'static [&'static str],
+    /// Before how long a recurring event lasts ("for 2 weeks")
+    pub during: &'static [&'static str],
+    /// After a number of occurrences ("5 times")
+    pub times: &'static [&'static str],
+
+    pub relative_dates: &'static [(&'static str, RelativeDate)],
+
+    pub weekdays: [&'static [&'static str]; 7],
+    /// Before a weekday meaning the closest one ("this monday")
+    pub weekday_prefixes: &'static [&'static str],
+    /// Before a weekday meaning the one of next week ("next monday")
+    pub next_weekday_prefixes: &'static [&'static str],
+    /// After a weekday meaning the one of next week ("lundi prochain")
+    pub next_weekday_suffixes: &'static [&'static str],
+
+    /// Arguments of `/agenda`: today, tomorrow and the coming week
+    pub agenda_periods: [&'static [&'static str]; 3],
+    /// Before the order of `/events` ("by")
+    pub order_prefixes: &'static [&'static str],
+    /// Orders of `/events`: by next occurrence, by number and by tag
+    pub event_orders: [&'static [&'static str]; 3]
+}
+
+/// Regexes matching the name of each duration unit
+pub(crate) struct DurationUnits {
+    pub second: &'static str,
+    pub minute: &'static str,
+    pub hour: &'static str,
+    pub day: &'static str,
+    pub week: &'static str,
+    pub month: &'static str,
+    pub year: &'static str
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Period {
+    Noon,
+    Midnight,
+    Morning,
+    Afternoon,
+    Evening,
+    Night
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum RelativeDate {
+    Days(i64),
+    Months(i64),
+    EndOfMonth
+}
+
+/// Replies of the bot. Templates use "{}" for their arguments, see `fill`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Msg {
+
+    Error,
+    UnknownCommand,
+
+    CannotParseTime,
+    NoMessage,
+    NeverOccurs,
+    DefaultingTime,
+
+    NoArguments,
+    NoEventNumber,
+    InvalidSelection,
+    NothingSelected,
+    SingleEventOnly,
+    NoEventAtNumber,
+    NoTimeAndMessage,
+    NoTagSpecified,
+    NoTimeExpression,
+    NoEventsWithTag,
+
+    EventAdded,
+    EventEdited,
+    EventRemoved,
+    NoEventAt,
+    EventTagged,
+    EventUntagged,
+    EventNotTagged,
+    EventPaused,
+    EventPausedUntil,
+    EventResumed,
+    EventOver,
+    NotPaused,
+    PauseUsage,
+    ResumeUsage,
+    Paused,
+    PausedUntil,
+
+    UndoButton,
+    Undone,
+    NothingToUndo,
+    NotLastChange,
+    UndoStale,
+
+    AmbiguousDate,
+    SaveFor,
+    NextOn,
+    CancelButton,
+    DeleteButton,
+    EditButton,
+    EditHint,
+    Cancelled,
+    ConfirmationExpired,
+    ButtonExpired,
+
+    DoneButton,
+    MarkedAsDone,
+
+    NoEvents,
+    UntaggedEvents,
+    TagHeader,
+    TagCount,
+    NoTags,
+    TagsHeader,
+    TagRenamed,
+    RenameTagUsage,
+    DelTagUsage,
+    DelTagChoice,
+    UntagAllButton,
+    DeleteAllButton,
+    TagRemoved,
+    TaggedEventsRemoved,
+    EventsUsage,
+    PageOf,
+    PreviousPage,
+    NextPage,
+
+    NextHeader,
+    NothingComingUp,
+    AndMore,
+    NextUsage,
+    AgendaUsage,
+    FindUsage,
+    FoundHeader,
+    NothingFound,
+
+    NoHistory,
+    HistoryHeader,
+    HistoryTagHeader,
+    HistorySent,
+    HistoryFailed,
+    HistoryDone,
+
+    CalendarSent,
+    EventsExported,
+    ExportProblems,
+    EventsImported,
+    ImportedNumbers,
+    ImportProblems,
+    OnlyIcsFiles,
+
+    WhenInterpretation,
+    WhenMessage,
+    WhenCronline,
+    WhenNeverOccurs,
+    WhenNextOccurences,
+    WhenStart,
+    WhenEnd,
+    WhenCount,
+
+    RecurOnce,
+    RecurMinute,
+    RecurHour,
+    RecurDay,
+    RecurMonth,
+    RecurYear,
+    DuringHour,
+    OnDay,
+    InMonth,
+    InYear,
+
+    Weeks,
+    Days,
+    Hours,
+    Minutes,
+    LessThanAMinute,
+
+    DescAdd,
+    DescEdit,
+    DescDelete,
+    DescTag,
+    DescUntag,
+    DescPause,
+    DescResume,
+    DescRenameTag,
+    DescUntagAll,
+    DescDeleteTagged,
+    DescImport,
+
+    CurrentLanguage,
+    LanguageSet,
+    LanguageReset,
+    UnknownLanguage,
+
+    HelpHelp,
+    HelpEvents,
+    HelpEventsTag,
+    HelpDel,
+    HelpEdit,
+    HelpWhen,
+    HelpUndo,
+    HelpTag,
+    HelpUntag,
+    HelpTags,
+    HelpRenameTag,
+    HelpDelTag,
+    HelpPause,
+    HelpResume,
+    HelpSelection,
+    HelpExport,
+    HelpHistory,
+    HelpNext,
+    HelpAgenda,
+    HelpFind,
+    HelpLanguage,
+    HelpAddEvent,
+    HelpExamples
+}
@@ -1,15 +1,19 @@
 mod telegram;
 mod agenda;
 mod http;
+mod storage;
+mod locale;
 
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::Arc;
 use crossbeam_channel::unbounded;
 use clap::{Clap, AppSettings, ArgEnum};
-use log::debug;
+use log::{debug, error};
 use telegram::Telegram;
-use agenda::Agenda;
+use agenda::{Agenda, Reminder, SharedClock, SystemClock};
 use http::HTTP_Notifier;
+use locale::Locale;
 
 fn main() {
 
@@ -21,16 +25,25 @@ fn main() {
 
     if !opts.data_path.exists() {
         std::fs::create_dir(&opts.data_path)
-            .expect(&format!(
+            .unwrap_or_else(|_| panic!(
                 "Cannot create: {}",
                 opts.data_path.to_string_lossy()));
     }
 
+    if let Some(command) = &opts.command {
+        run_command(&opts, command);
+        return;
+    }
+
+    let storage = storage::open(&opts)
+        .unwrap_or_else(|err| panic!("Cannot open storage: {}", format_error(err)));
+
     let (sender, receiver) = unbounded();
 
-    let mut telegram = Telegram::new(&opts, &sender);
-    let mut agenda = Agenda::new(&opts, &sender);
-    let http_notifier = HTTP_Notifier::new(&opts, &sender);
+    let mut telegram = Telegram::new(&sender, &storage);
+    let clock: SharedClock = Arc::new(SystemClock);
+    let mut agenda = Agenda::new(&opts, &sender, &storage, &clock);
+    let http_notifier = HTTP_Notifier::new(&opts, &sender, agenda.calendar_feed(), &clock);
 
     std::thread::spawn(telegram.get_loop());
     std::thread::spawn(agenda.get_loop());
@@ -45,17 +58,72 @@ fn main() {
         debug!("BotUpdate: {:?}", update);
 
         match update {
-            BotUpdate::MsgIn(msg) => agenda.process(&msg),
-            BotUpdate::MsgOut(msg) => telegram.send(&msg)
+            BotUpdate::MsgIn(chat_id, msg) => agenda.process(chat_id, &msg),
+            BotUpdate::MsgOut(msg) => telegram.send(&msg),
+            BotUpdate::MsgOutButtons(msg, buttons) => telegram
+                .send_with_buttons(&msg, &buttons)
+                .unwrap_or_else(|err| error!(
+                    "Could not send Telegram message: {}",
+                    format_error(err))),
+            BotUpdate::Callback(chat_id, data) => agenda.process_callback(chat_id, &data),
+            BotUpdate::DocumentIn(chat_id, filename, data) => agenda
+                .process_document(chat_id, &filename, &data),
+            BotUpdate::DocumentOut(filename, data, caption) => telegram
+                .send_document(&filename, &data, &caption)
+                .unwrap_or_else(|err| error!(
+                    "Could not send Telegram document: {}",
+                    format_error(err))),
+            BotUpdate::Reminder(reminder) => {
+                let res = telegram.send_with_buttons(
+                    &reminder.notification(&agenda.chat_opts()),
+                    &[vec![reminder.ack_button(agenda.locale())]]
+                );
+                agenda.record_delivery(&reminder, res);
+            },
+            BotUpdate::EventOver(text) => telegram.send(&agenda.event_over_notification(&text))
         }
     }
 
 }
 
+fn run_command(opts: &Opts, command: &Command) {
+
+    let res = match command {
+        Command::MigrateToSqlite => storage::migrate_json_to_sqlite(opts),
+        Command::ExportIcs { file } => storage::open(opts)
+            .and_then(|storage| agenda::export_ics_file(storage.as_ref(), file)),
+        Command::ImportIcs { file } => storage::open(opts)
+            .and_then(|storage| agenda::import_ics_file(storage.as_ref(), file))
+    };
+
+    match res {
+        Ok(msg) => println!("{}", msg),
+        Err(err) => {
+            eprintln!("Error: {}", format_error(err));
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Inline button, as (label, callback data)
+pub type Button = (String, String);
+
 #[derive(Debug)]
 pub enum BotUpdate {
-    MsgIn(String),
-    MsgOut(String)
+    /// Chat ID and text
+    MsgIn(u32, String),
+    MsgOut(String),
+    /// Text and rows of buttons
+    MsgOutButtons(String, Vec<Vec<Button>>),
+    /// Chat ID and button data
+    Callback(u32, String),
+    Reminder(Reminder),
+    /// Text of an event paused past its last occurrence, removed when the pause ended
+    EventOver(String),
+    /// Chat ID, file name and contents
+    DocumentIn(u32, String, Vec<u8>),
+    /// File name, contents and caption
+    DocumentOut(String, Vec<u8>, String)
 }
 
 #[derive(Clap, Debug, Clone)]
@@ -75,6 +143,32 @@ pub struct Opts {
     )]
     date_format: DateFormat,
 
+    #[clap(
+        long, arg_enum, default_value="h24",
+        about="24-hour (17:00) or 12-hour (5:00pm) clock in replies\n"
+    )]
+    time_format: TimeFormat,
+
+    #[clap(
+        long, arg_enum, default_value="en",
+        about=
+            "Language used to read times and to reply.\n\
+            Each chat can pick its own with /language.\n"
+    )]
+    locale: Locale,
+
+    #[clap(long, default_value="9:00", about="Time meant by \"morning\"\n")]
+    morning_time: ClockTime,
+
+    #[clap(long, default_value="14:00", about="Time meant by \"afternoon\"\n")]
+    afternoon_time: ClockTime,
+
+    #[clap(long, default_value="19:00", about="Time meant by \"evening\"\n")]
+    evening_time: ClockTime,
+
+    #[clap(long, default_value="21:00", about="Time meant by \"night\" and \"tonight\"\n")]
+    night_time: ClockTime,
+
     #[clap(long, parse(try_from_str), default_value="true")]
     http_endpoint: bool,
 
@@ -84,8 +178,35 @@ pub struct Opts {
     #[clap(long, parse(try_from_str), default_value="8123")]
     endpoint_port: u16,
 
+    #[clap(
+        long, arg_enum, default_value="json",
+        about=
+            "Where events and the Telegram context are stored.\n\
+            SQLite data goes to DATA_PATH/nag.sqlite3.\n"
+    )]
+    storage: StorageBackend,
+
     #[clap(long, short, arg_enum, default_value="info")]
-    verbosity: Verbosity
+    verbosity: Verbosity,
+
+    #[clap(subcommand)]
+    command: Option<Command>
+}
+
+#[derive(Clap, Debug, Clone)]
+pub enum Command {
+    #[clap(about = "Import the JSON agenda into a new SQLite database, then exit")]
+    MigrateToSqlite,
+
+    #[clap(about = "Write all events to an iCalendar file, then exit")]
+    ExportIcs {
+        file: PathBuf
+    },
+
+    #[clap(about = "Add the events of an iCalendar file to the agenda, then exit")]
+    ImportIcs {
+        file: PathBuf
+    }
 }
 
 #[derive(ArgEnum, Clap, Clone, Debug)]
@@ -105,6 +226,83 @@ impl FromStr for DateFormat {
     }
 }
 
+#[derive(ArgEnum, Clap, Clone, Debug)]
+pub enum TimeFormat {
+    H24,
+    H12
+}
+
+impl FromStr for TimeFormat {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "h24" => Ok(Self::H24),
+            "h12" => Ok(Self::H12),
+            _ => anyhow::bail!("Cannot parse {}", s)
+        }
+    }
+}
+
+impl TimeFormat {
+
+    /// Time of day like "17:00" or "5:00pm", with the seconds only if any
+    pub fn clock(&self, hour: u64, minute: u64, second: u64) -> String {
+
+        let seconds = match second {
+            0 => String::new(),
+            second => format!(":{:02}", second)
+        };
+
+        match self {
+            Self::H24 => format!("{}:{:02}{}", hour, minute, seconds),
+            Self::H12 => {
+                let am_pm = if hour < 12 { "am" } else { "pm" };
+                let hour_12 = match hour % 12 {
+                    0 => 12,
+                    hour => hour
+                };
+                format!("{}:{:02}{}{}", hour_12, minute, seconds, am_pm)
+            }
+        }
+    }
+}
+
+/// Hour and minute of a time of day, written as "HH:MM" or "HH"
+#[derive(Clone, Copy, Debug)]
+pub struct ClockTime {
+    pub hour: u64,
+    pub minute: u64
+}
+
+impl FromStr for ClockTime {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (hour, minute) = s.split_once(':').unwrap_or((s, "0"));
+        let (hour, minute): (u64, u64) = (hour.parse()?, minute.parse()?);
+        if hour >= 24 || minute >= 60 {
+            anyhow::bail!("Invalid time of day {}", s)
+        }
+        Ok(ClockTime { hour, minute })
+    }
+}
+
+#[derive(ArgEnum, Clap, Clone, Debug)]
+pub enum StorageBackend {
+    Json,
+    Sqlite
+}
+
+impl FromStr for StorageBackend {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "sqlite" => Ok(Self::Sqlite),
+            _ => anyhow::bail!("Cannot parse {}", s)
+        }
+    }
+}
+
 #[derive(ArgEnum, Clap, Clone, Debug)]
 enum Verbosity {
     Off, Trace, Debug, Info, Warn, Error
@@ -0,0 +1,190 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::fs::OpenOptions;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use anyhow::Context;
+use log::{debug, info};
+
+use crate::agenda::{AgendaState, HistoryEntry, Instant};
+use crate::telegram::TelegramContext;
+use crate::locale::Locale;
+use super::{Storage, EventChange};
+
+/// Default backend: the whole agenda is kept in a single pretty-printed JSON file
+pub(crate) struct JsonStorage {
+    agenda_path: PathBuf,
+    context_path: PathBuf,
+    history_path: PathBuf,
+    locales_path: PathBuf,
+    // Serializes the read-modify-write cycles of `update_agenda` and `save_chat_locale`
+    lock: Mutex<()>
+}
+
+impl JsonStorage {
+
+    pub(crate) fn new(data_path: &Path) -> Self {
+
+        let agenda_path = data_path.join("agenda.json");
+        let context_path = data_path.join("telegram.json");
+        let history_path = data_path.join("history.jsonl");
+        let locales_path = data_path.join("locales.json");
+
+        debug!("Agenda state path: {}", agenda_path.to_string_lossy());
+        debug!("Telegram context path: {}", context_path.to_string_lossy());
+        debug!("History path: {}", history_path.to_string_lossy());
+
+        JsonStorage { agenda_path, context_path, history_path, locales_path, lock: Mutex::new(()) }
+    }
+
+    fn append_history_record(&self, record: &HistoryRecord) -> anyhow::Result<()> {
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.history_path)
+            .context("cannot open history file")?;
+
+        writeln!(file, "{}", serde_json::to_string(record)?)?;
+
+        Ok(())
+    }
+}
+
+impl Storage for JsonStorage {
+
+    fn load_agenda(&self) -> anyhow::Result<AgendaState> {
+        info!("Attempting to restore agenda from {}", self.agenda_path.to_string_lossy());
+        restore(&self.agenda_path)
+    }
+
+    fn update_agenda(&self, changes: &[EventChange]) -> anyhow::Result<()> {
+
+        let _guard = self.lock.lock().unwrap();
+
+        let mut state = match self.agenda_path.exists() {
+            true => restore(&self.agenda_path)?,
+            false => AgendaState::new()
+        };
+        state.apply(changes);
+
+        info!("Saving agenda to: {}", self.agenda_path.to_string_lossy());
+        save(&self.agenda_path, &state)
+    }
+
+    fn append_history(&self, entry: &HistoryEntry) -> anyhow::Result<()> {
+        self.append_history_record(&HistoryRecord::Fired(entry.clone()))
+    }
+
+    fn acknowledge_history(
+        &self, event_id: u64, scheduled: &Instant, time: &Instant
+    ) -> anyhow::Result<()> {
+        self.append_history_record(&HistoryRecord::Acknowledged {
+            event_id,
+            scheduled: *scheduled,
+            time: *time
+        })
+    }
+
+    fn load_history(&self, limit: usize, tag: Option<&str>) -> anyhow::Result<Vec<HistoryEntry>> {
+
+        if !self.history_path.exists() {
+            return Ok(vec![]);
+        }
+
+        let file = std::fs::File::open(&self.history_path)
+            .context("cannot open history file")?;
+
+        let mut entries: Vec<HistoryEntry> = vec![];
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line).context("invalid history record")? {
+                HistoryRecord::Fired(entry) => entries.push(entry),
+                HistoryRecord::Acknowledged { event_id, scheduled, time } => {
+                    entries
+                        .iter_mut()
+                        .rev()
+                        .find(|e| e.event_id == event_id && e.scheduled == scheduled)
+                        .into_iter()
+                        .for_each(|e| e.acknowledged = Some(time));
+                }
+            }
+        }
+
+        let mut selected: Vec<HistoryEntry> = entries
+            .into_iter()
+            .rev()
+            .filter(|e| tag.is_none_or(|tag| e.tags.iter().any(|t| t == tag)))
+            .take(limit)
+            .collect();
+        selected.reverse();
+
+        Ok(selected)
+    }
+
+    fn load_telegram_context(&self) -> anyhow::Result<TelegramContext> {
+        info!(
+            "Attempting to restore Telegram context from {}",
+            self.context_path.to_string_lossy()
+        );
+        restore(&self.context_path)
+    }
+
+    fn save_telegram_context(&self, context: &TelegramContext) -> anyhow::Result<()> {
+        info!("Saving Telegram context to: {}", self.context_path.to_string_lossy());
+        save(&self.context_path, context)
+    }
+
+    fn load_chat_locales(&self) -> anyhow::Result<HashMap<u32, Locale>> {
+        match self.locales_path.exists() {
+            true => restore(&self.locales_path),
+            false => Ok(HashMap::new())
+        }
+    }
+
+    fn save_chat_locale(&self, chat_id: u32, locale: Option<Locale>) -> anyhow::Result<()> {
+
+        let _guard = self.lock.lock().unwrap();
+
+        let mut locales = self.load_chat_locales()?;
+        match locale {
+            Some(locale) => locales.insert(chat_id, locale),
+            None => locales.remove(&chat_id)
+        };
+
+        info!("Saving chat languages to: {}", self.locales_path.to_string_lossy());
+        save(&self.locales_path, &locales)
+    }
+}
+
+/// The history file is append-only: acknowledgements are recorded as
+/// separate lines and matched to their entry when loading.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type")]
+enum HistoryRecord {
+    Fired(HistoryEntry),
+    Acknowledged { event_id: u64, scheduled: Instant, time: Instant }
+}
+
+fn restore<T: DeserializeOwned>(path: &Path) -> anyhow::Result<T> {
+
+    let data = std::fs::read_to_string(path)?;
+
+    // Refuse to go on with a corrupted file, rather than overwriting it later
+    let value = serde_json::from_str(&data)
+        .unwrap_or_else(|err| panic!(
+            "Error parsing data from {}: {}",
+            path.to_string_lossy(), err));
+
+    Ok(value)
+}
+
+fn save<T: Serialize>(path: &Path, value: &T) -> anyhow::Result<()> {
+    let data = serde_json::to_string_pretty(value)?;
+    std::fs::write(path, data)?;
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use std::sync::Arc;
+use std::collections::HashMap;
+use anyhow::bail;
+use log::info;
+
+use crate::{Opts, StorageBackend};
+use crate::agenda::{AgendaState, AgendaEvent, HistoryEntry, Instant};
+use crate::telegram::TelegramContext;
+use crate::locale::Locale;
+
+mod json;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+pub(crate) use json::JsonStorage;
+#[cfg(feature = "sqlite")]
+pub(crate) use sqlite::SqliteStorage;
+
+pub(crate) type SharedStorage = Arc<dyn Storage>;
+
+/// A single modification of the agenda, as persisted by a storage backend
+#[derive(Debug, Clone)]
+pub(crate) enum EventChange {
+    Upsert(u64, Box<AgendaEvent>),
+    Remove(u64)
+}
+
+impl EventChange {
+    pub(crate) fn id(&self) -> u64 {
+        match self {
+            EventChange::Upsert(id, _) | EventChange::Remove(id) => *id
+        }
+    }
+}
+
+pub(crate) trait Storage: Send + Sync {
+
+    fn load_agenda(&self) -> anyhow::Result<AgendaState>;
+
+    /// Applies all changes at once: either all of them are persisted, or none
+    fn update_agenda(&self, changes: &[EventChange]) -> anyhow::Result<()>;
+
+    /// Records a fired reminder. Entries are never modified afterwards,
+    /// apart from being acknowledged.
+    fn append_history(&self, entry: &HistoryEntry) -> anyhow::Result<()>;
+
+    fn acknowledge_history(
+        &self, event_id: u64, scheduled: &Instant, time: &Instant
+    ) -> anyhow::Result<()>;
+
+    /// Returns the `limit` most recent entries (optionally only those with `tag`),
+    /// oldest first
+    fn load_history(&self, limit: usize, tag: Option<&str>) -> anyhow::Result<Vec<HistoryEntry>>;
+
+    fn load_telegram_context(&self) -> anyhow::Result<TelegramContext>;
+
+    fn save_telegram_context(&self, context: &TelegramContext) -> anyhow::Result<()>;
+
+    /// Languages picked with /language, by chat. Other chats use the global one.
+    fn load_chat_locales(&self) -> anyhow::Result<HashMap<u32, Locale>>;
+
+    /// `None` goes back to the global language
+    fn save_chat_locale(&self, chat_id: u32, locale: Option<Locale>) -> anyhow::Result<()>;
+}
+
+pub(crate) fn open(opts: &Opts) -> anyhow::Result<SharedStorage> {
+
+    let storage: SharedStorage = match opts.storage {
+
+        StorageBackend::Json => {
+            info!("Using JSON storage");
+            Arc::new(JsonStorage::new(&opts.data_path))
+        },
+
+        #[cfg(feature = "sqlite")]
+        StorageBackend::Sqlite => {
+            info!("Using SQLite storage");
+            Arc::new(SqliteStorage::open(&opts.data_path)?)
+        },
+
+        #[cfg(not(feature = "sqlite"))]
+        StorageBackend::Sqlite => bail!("Nag was built without SQLite support")
+    };
+
+    Ok(storage)
+}
+
+/// One-shot import of the JSON agenda and Telegram context into a new SQLite database
+#[cfg(feature = "sqlite")]
+pub(crate) fn migrate_json_to_sqlite(opts: &Opts) -> anyhow::Result<String> {
+
+    let json = JsonStorage::new(&opts.data_path);
+    let sqlite = SqliteStorage::open(&opts.data_path)?;
+
+    if !sqlite.load_agenda()?.events.is_empty() {
+        bail!("the SQLite database already contains events, refusing to overwrite them");
+    }
+
+    let state = json.load_agenda()?;
+    let changes: Vec<EventChange> = state.events
+        .into_iter()
+        .map(|(id, event)| EventChange::Upsert(id, Box::new(event)))
+        .collect();
+
+    sqlite.update_agenda(&changes)?;
+
+    if let Ok(context) = json.load_telegram_context() {
+        sqlite.save_telegram_context(&context)?;
+    }
+
+    for (chat_id, locale) in json.load_chat_locales()? {
+        sqlite.save_chat_locale(chat_id, Some(locale))?;
+    }
+
+    Ok(format!("Migrated {} events to SQLite", changes.len()))
+}
+
+#[cfg(not(feature = "sqlite"))]
+pub(crate) fn migrate_json_to_sqlite(_opts: &Opts) -> anyhow::Result<String> {
+    bail!("Nag was built without SQLite support")
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,383 @@
+use std::path::Path;
+use std::sync::Mutex;
+use std::collections::{BTreeSet, HashMap};
+use anyhow::{anyhow, Context};
+use rusqlite::{Connection, OptionalExtension, Transaction, params};
+use log::{debug, info};
+
+use crate::agenda::{AgendaState, AgendaEvent, HistoryEntry, Instant, normalize_tag};
+use crate::telegram::TelegramContext;
+use crate::locale::Locale;
+use super::{Storage, EventChange};
+
+/// Step of the schema migrations, either plain SQL or Rust code for
+/// changes SQL cannot express
+pub(super) enum Migration {
+    Sql(&'static str),
+    Rust(fn(&Transaction) -> rusqlite::Result<()>)
+}
+
+impl Migration {
+
+    pub(super) fn apply(&self, tx: &Transaction) -> rusqlite::Result<()> {
+        match self {
+            Migration::Sql(sql) => tx.execute_batch(sql),
+            Migration::Rust(step) => step(tx)
+        }
+    }
+}
+
+// Each entry brings the schema from version `i` to version `i + 1`,
+// the current version being tracked with `PRAGMA user_version`.
+pub(super) const MIGRATIONS: &[Migration] = &[
+    Migration::Sql("
+    CREATE TABLE events (
+        id       INTEGER PRIMARY KEY,
+        text     TEXT NOT NULL,
+        cronline TEXT NOT NULL
+    );
+    CREATE TABLE event_tags (
+        event_id INTEGER NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+        tag      TEXT NOT NULL,
+        PRIMARY KEY (event_id, tag)
+    );
+    CREATE TABLE telegram_context (
+        id      INTEGER PRIMARY KEY CHECK (id = 0),
+        chat_id INTEGER
+    );
+    "),
+    Migration::Sql("
+    CREATE TABLE history (
+        id           INTEGER PRIMARY KEY AUTOINCREMENT,
+        event_id     INTEGER NOT NULL,
+        text         TEXT NOT NULL,
+        scheduled    TEXT NOT NULL,
+        sent         TEXT NOT NULL,
+        error        TEXT,
+        acknowledged TEXT
+    );
+    CREATE INDEX history_event ON history (event_id, scheduled);
+    CREATE TABLE history_tags (
+        history_id INTEGER NOT NULL REFERENCES history(id),
+        tag        TEXT NOT NULL
+    );
+    "),
+    Migration::Sql("
+    CREATE TABLE chat_locales (
+        chat_id INTEGER PRIMARY KEY,
+        locale  TEXT NOT NULL
+    );
+    "),
+    Migration::Sql("
+    ALTER TABLE events ADD COLUMN bounds TEXT NOT NULL DEFAULT '{}';
+    "),
+    // Tags became single words when events could have several
+    Migration::Rust(normalize_event_tags),
+    Migration::Sql("
+    ALTER TABLE events ADD COLUMN pause TEXT;
+    ")
+];
+
+fn normalize_event_tags(tx: &Transaction) -> rusqlite::Result<()> {
+
+    let mut tags = HashMap::<u64, BTreeSet<String>>::new();
+    let mut stmt = tx.prepare("SELECT event_id, tag FROM event_tags")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get::<_, String>(1)?)))?;
+    for row in rows {
+        let (id, tag) = row?;
+        let tag = normalize_tag(&tag);
+        if !tag.is_empty() {
+            tags.entry(id).or_default().insert(tag);
+        }
+    }
+
+    tx.execute("DELETE FROM event_tags", [])?;
+    let mut stmt = tx.prepare("INSERT INTO event_tags (event_id, tag) VALUES (?1, ?2)")?;
+    for (id, tags) in tags {
+        for tag in tags {
+            stmt.execute(params![id, tag])?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Optional backend storing everything in `nag.sqlite3`, one row per event
+pub(crate) struct SqliteStorage {
+    conn: Mutex<Connection>
+}
+
+impl SqliteStorage {
+
+    pub(crate) fn open(data_path: &Path) -> anyhow::Result<Self> {
+
+        let db_path = data_path.join("nag.sqlite3");
+        info!("Opening SQLite database {}", db_path.to_string_lossy());
+
+        let mut conn = Connection::open(&db_path)
+            .with_context(|| format!("cannot open {}", db_path.to_string_lossy()))?;
+        conn.pragma_update(None, "foreign_keys", "ON")?;
+        migrate(&mut conn)?;
+
+        Ok(SqliteStorage { conn: Mutex::new(conn) })
+    }
+}
+
+fn migrate(conn: &mut Connection) -> anyhow::Result<()> {
+
+    let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+    for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        debug!("Migrating SQLite schema to version {}", i + 1);
+        let tx = conn.transaction()?;
+        migration.apply(&tx)?;
+        tx.pragma_update(None, "user_version", i + 1)?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
+
+impl Storage for SqliteStorage {
+
+    fn load_agenda(&self) -> anyhow::Result<AgendaState> {
+
+        let conn = self.conn.lock().unwrap();
+
+        let mut tags = HashMap::<u64, BTreeSet<String>>::new();
+        let mut stmt = conn.prepare("SELECT event_id, tag FROM event_tags")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        for row in rows {
+            let (id, tag) = row?;
+            tags.entry(id).or_default().insert(tag);
+        }
+
+        let mut state = AgendaState::new();
+        let mut stmt = conn.prepare("SELECT id, text, cronline, bounds, pause FROM events")?;
+        let rows = stmt.query_map([], |row| Ok((
+            row.get::<_, u64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, String>(3)?,
+            row.get::<_, Option<String>>(4)?
+        )))?;
+        for row in rows {
+            let (id, text, cronline, bounds, pause) = row?;
+            let event = AgendaEvent {
+                cronline: serde_json::from_str(&cronline)
+                    .with_context(|| format!("invalid cronline for event {}", id))?,
+                text,
+                tags: tags.remove(&id).unwrap_or_default(),
+                bounds: serde_json::from_str(&bounds)
+                    .with_context(|| format!("invalid bounds for event {}", id))?,
+                pause: pause.map(|pause| serde_json::from_str(&pause))
+                    .transpose()
+                    .with_context(|| format!("invalid pause for event {}", id))?
+            };
+            state.events.insert(id, event);
+        }
+
+        Ok(state)
+    }
+
+    fn update_agenda(&self, changes: &[EventChange]) -> anyhow::Result<()> {
+
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        for change in changes {
+            match change {
+
+                EventChange::Upsert(id, event) => {
+                    tx.execute(
+                        "INSERT INTO events (id, text, cronline, bounds, pause)
+                         VALUES (?1, ?2, ?3, ?4, ?5)
+                         ON CONFLICT(id) DO UPDATE SET text = excluded.text,
+                             cronline = excluded.cronline, bounds = excluded.bounds,
+                             pause = excluded.pause",
+                        params![
+                            id,
+                            event.text,
+                            serde_json::to_string(&event.cronline)?,
+                            serde_json::to_string(&event.bounds)?,
+                            event.pause.as_ref().map(serde_json::to_string).transpose()?
+                        ]
+                    )?;
+                    tx.execute("DELETE FROM event_tags WHERE event_id = ?1", params![id])?;
+                    for tag in event.tags.iter() {
+                        tx.execute(
+                            "INSERT INTO event_tags (event_id, tag) VALUES (?1, ?2)",
+                            params![id, tag]
+                        )?;
+                    }
+                },
+
+                EventChange::Remove(id) => {
+                    tx.execute("DELETE FROM events WHERE id = ?1", params![id])?;
+                }
+            }
+        }
+
+        tx.commit()?;
+        debug!("Committed {} agenda changes to SQLite", changes.len());
+
+        Ok(())
+    }
+
+    fn append_history(&self, entry: &HistoryEntry) -> anyhow::Result<()> {
+
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            "INSERT INTO history (event_id, text, scheduled, sent, error, acknowledged)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                entry.event_id,
+                entry.text,
+                entry.scheduled.to_rfc3339(),
+                entry.sent.to_rfc3339(),
+                entry.error,
+                entry.acknowledged.map(|t| t.to_rfc3339())
+            ]
+        )?;
+        let history_id = tx.last_insert_rowid();
+
+        for tag in entry.tags.iter() {
+            tx.execute(
+                "INSERT INTO history_tags (history_id, tag) VALUES (?1, ?2)",
+                params![history_id, tag]
+            )?;
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn acknowledge_history(
+        &self, event_id: u64, scheduled: &Instant, time: &Instant
+    ) -> anyhow::Result<()> {
+
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute(
+            "UPDATE history SET acknowledged = ?3 WHERE id = (
+                SELECT MAX(id) FROM history WHERE event_id = ?1 AND scheduled = ?2
+            )",
+            params![event_id, scheduled.to_rfc3339(), time.to_rfc3339()]
+        )?;
+
+        Ok(())
+    }
+
+    fn load_history(&self, limit: usize, tag: Option<&str>) -> anyhow::Result<Vec<HistoryEntry>> {
+
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT id, event_id, text, scheduled, sent, error, acknowledged FROM history
+             WHERE ?1 IS NULL OR id IN (SELECT history_id FROM history_tags WHERE tag = ?1)
+             ORDER BY id DESC LIMIT ?2"
+        )?;
+        let mut tags_stmt = conn.prepare("SELECT tag FROM history_tags WHERE history_id = ?1")?;
+
+        let rows = stmt.query_map(params![tag, limit as i64], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, u64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, Option<String>>(5)?,
+                row.get::<_, Option<String>>(6)?
+            ))
+        })?;
+
+        let mut entries = vec![];
+        for row in rows {
+            let (history_id, event_id, text, scheduled, sent, error, acknowledged) = row?;
+            let tags = tags_stmt
+                .query_map(params![history_id], |row| row.get(0))?
+                .collect::<Result<Vec<String>, _>>()?;
+            entries.push(HistoryEntry {
+                event_id,
+                text,
+                tags,
+                scheduled: parse_time(&scheduled)?,
+                sent: parse_time(&sent)?,
+                error,
+                acknowledged: acknowledged.as_deref().map(parse_time).transpose()?
+            });
+        }
+        entries.reverse();
+
+        Ok(entries)
+    }
+
+    fn load_telegram_context(&self) -> anyhow::Result<TelegramContext> {
+
+        let conn = self.conn.lock().unwrap();
+
+        let chat_id = conn
+            .query_row(
+                "SELECT chat_id FROM telegram_context WHERE id = 0", [],
+                |row| row.get(0))
+            .optional()?
+            .ok_or_else(|| anyhow!("no Telegram context stored"))?;
+
+        Ok(TelegramContext { chat_id })
+    }
+
+    fn save_telegram_context(&self, context: &TelegramContext) -> anyhow::Result<()> {
+
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute(
+            "INSERT OR REPLACE INTO telegram_context (id, chat_id) VALUES (0, ?1)",
+            params![context.chat_id]
+        )?;
+
+        Ok(())
+    }
+
+    fn load_chat_locales(&self) -> anyhow::Result<HashMap<u32, Locale>> {
+
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare("SELECT chat_id, locale FROM chat_locales")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, u32>(0)?, row.get::<_, String>(1)?)))?;
+
+        let mut locales = HashMap::new();
+        for row in rows {
+            let (chat_id, code) = row?;
+            locales.insert(chat_id, code.parse()?);
+        }
+
+        Ok(locales)
+    }
+
+    fn save_chat_locale(&self, chat_id: u32, locale: Option<Locale>) -> anyhow::Result<()> {
+
+        let conn = self.conn.lock().unwrap();
+
+        match locale {
+            Some(locale) => conn.execute(
+                "INSERT OR REPLACE INTO chat_locales (chat_id, locale) VALUES (?1, ?2)",
+                params![chat_id, locale.code()]
+            )?,
+            None => conn.execute(
+                "DELETE FROM chat_locales WHERE chat_id = ?1",
+                params![chat_id]
+            )?
+        };
+
+        Ok(())
+    }
+}
+
+fn parse_time(text: &str) -> anyhow::Result<Instant> {
+    let t = chrono::DateTime::parse_from_rfc3339(text)
+        .with_context(|| format!("invalid time {}", text))?;
+    Ok(t.with_timezone(&chrono::Local))
+}
@@ -0,0 +1,180 @@
+use std::path::PathBuf;
+use chrono::TimeZone;
+use crate::agenda::{AgendaEvent, Bounds, HistoryEntry, Pause};
+use crate::locale::Locale;
+use super::{Storage, EventChange, JsonStorage};
+
+fn temp_data_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("nag-test-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir(&path).unwrap();
+    path
+}
+
+fn make_event(text: &str, tags: &[&str]) -> AgendaEvent {
+    let mut event: AgendaEvent = serde_json::from_str(r#"{
+        "cronline": {"line": [{"On": 0}, {"On": 17}, "Every", "Every", "Every"]},
+        "text": ""
+    }"#).unwrap();
+    event.text = text.to_owned();
+    event.tags = tags.iter().map(|tag| tag.to_string()).collect();
+    event
+}
+
+fn check_roundtrip(storage: &dyn Storage) {
+
+    storage.update_agenda(&[
+        EventChange::Upsert(0, Box::new(make_event("test1", &[]))),
+        EventChange::Upsert(1, Box::new(make_event("test2", &["work", "urgent"]))),
+        EventChange::Upsert(2, Box::new(make_event("test3", &[])))
+    ]).unwrap();
+
+    storage.update_agenda(&[
+        EventChange::Remove(0),
+        EventChange::Upsert(2, Box::new(AgendaEvent {
+            pause: Some(Pause { until: None }),
+            ..make_event("test3", &["home"])
+        })),
+        EventChange::Upsert(3, Box::new(AgendaEvent {
+            bounds: Bounds {
+                start: Some(chrono::Local.ymd(2000, 2, 1).and_hms(0, 0, 0)),
+                end: Some(chrono::Local.ymd(2000, 3, 11).and_hms(0, 0, 0)),
+                remaining: Some(4)
+            },
+            pause: Some(Pause { until: Some(chrono::Local.ymd(2000, 2, 15).and_hms(0, 0, 0)) }),
+            ..make_event("test4", &[])
+        }))
+    ]).unwrap();
+
+    let state = storage.load_agenda().unwrap();
+
+    let mut ids: Vec<&u64> = state.events.keys().collect();
+    ids.sort();
+    assert_eq!(ids, [&1, &2, &3]);
+    assert_eq!(state.events[&1].text, "test2");
+    assert_eq!(state.events[&1].tags.iter().collect::<Vec<_>>(), ["urgent", "work"]);
+    assert_eq!(state.events[&2].tags.iter().collect::<Vec<_>>(), ["home"]);
+    assert_eq!(state.events[&2].bounds, Bounds::default());
+    assert_eq!(state.events[&3].bounds.remaining, Some(4));
+    assert!(state.events[&3].bounds.start.is_some());
+    assert!(state.events[&3].bounds.end.is_some());
+    assert_eq!(state.events[&1].pause, None);
+    assert_eq!(state.events[&2].pause, Some(Pause { until: None }));
+    assert!(state.events[&3].pause.as_ref().is_some_and(|pause| pause.until.is_some()));
+}
+
+fn check_history(storage: &dyn Storage) {
+
+    let t0 = chrono::Local.ymd(2000, 1, 1).and_hms(8, 0, 0);
+
+    let make_entry = |event_id, minutes, tag: Option<&str>| HistoryEntry {
+        event_id,
+        text: format!("test{}", event_id),
+        tags: tag.iter().map(|t| t.to_string()).collect(),
+        scheduled: t0 + chrono::Duration::minutes(minutes),
+        sent: t0 + chrono::Duration::minutes(minutes),
+        error: None,
+        acknowledged: None
+    };
+
+    storage.append_history(&make_entry(0, 0, Some("work"))).unwrap();
+    storage.append_history(&make_entry(1, 1, None)).unwrap();
+    storage.append_history(&make_entry(0, 2, Some("work"))).unwrap();
+
+    let ack_t = t0 + chrono::Duration::minutes(5);
+    storage.acknowledge_history(0, &(t0 + chrono::Duration::minutes(2)), &ack_t).unwrap();
+
+    let entries = storage.load_history(2, None).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].event_id, 1);
+    assert_eq!(entries[1].event_id, 0);
+    assert_eq!(entries[1].acknowledged, Some(ack_t));
+
+    let entries = storage.load_history(10, Some("work")).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert!(entries.iter().all(|e| e.event_id == 0));
+    assert_eq!(entries[0].acknowledged, None);
+}
+
+fn check_chat_locales(storage: &dyn Storage) {
+
+    assert!(storage.load_chat_locales().unwrap().is_empty());
+
+    storage.save_chat_locale(1, Some(Locale::Fr)).unwrap();
+    storage.save_chat_locale(2, Some(Locale::De)).unwrap();
+    storage.save_chat_locale(2, Some(Locale::Es)).unwrap();
+    storage.save_chat_locale(1, None).unwrap();
+
+    let locales = storage.load_chat_locales().unwrap();
+    assert_eq!(locales.len(), 1);
+    assert_eq!(locales[&2], Locale::Es);
+}
+
+#[test]
+fn single_tag_migration() {
+
+    let event: AgendaEvent = serde_json::from_str(r#"{
+        "cronline": {"line": [{"On": 0}, {"On": 17}, "Every", "Every", "Every"]},
+        "text": "test",
+        "tag": "side project"
+    }"#).unwrap();
+    assert_eq!(event.tags.iter().collect::<Vec<_>>(), ["side-project"]);
+
+    let event: AgendaEvent = serde_json::from_str(r#"{
+        "cronline": {"line": [{"On": 0}, {"On": 17}, "Every", "Every", "Every"]},
+        "text": "test",
+        "tag": null
+    }"#).unwrap();
+    assert!(event.tags.is_empty());
+}
+
+#[test]
+fn json_roundtrip() {
+    let path = temp_data_path("json");
+    check_roundtrip(&JsonStorage::new(&path));
+    check_history(&JsonStorage::new(&path));
+    check_chat_locales(&JsonStorage::new(&path));
+    std::fs::remove_dir_all(&path).unwrap();
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_single_tag_migration() {
+
+    let path = temp_data_path("sqlite-tags");
+
+    let mut conn = rusqlite::Connection::open(path.join("nag.sqlite3")).unwrap();
+    let tx = conn.transaction().unwrap();
+    for (i, migration) in super::sqlite::MIGRATIONS.iter().take(4).enumerate() {
+        migration.apply(&tx).unwrap();
+        tx.pragma_update(None, "user_version", i + 1).unwrap();
+    }
+    tx.execute_batch(r#"
+        INSERT INTO events (id, text, cronline) VALUES
+            (0, 'test', '{"line": [{"On": 0}, {"On": 17}, "Every", "Every", "Every"]}'),
+            (1, 'test', '{"line": [{"On": 0}, {"On": 17}, "Every", "Every", "Every"]}');
+        INSERT INTO event_tags (event_id, tag) VALUES
+            (0, 'side project'),
+            (1, '#Éte  Camp#'),
+            (1, 'éte camp#'),
+            (1, '#');
+    "#).unwrap();
+    tx.commit().unwrap();
+    drop(conn);
+
+    let state = super::SqliteStorage::open(&path).unwrap().load_agenda().unwrap();
+    assert_eq!(state.events[&0].tags.iter().collect::<Vec<_>>(), ["side-project"]);
+    assert_eq!(state.events[&1].tags.iter().collect::<Vec<_>>(), ["éte-camp#"]);
+
+    std::fs::remove_dir_all(&path).unwrap();
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_roundtrip() {
+    let path = temp_data_path("sqlite");
+    check_roundtrip(&super::SqliteStorage::open(&path).unwrap());
+    check_history(&super::SqliteStorage::open(&path).unwrap());
+    check_chat_locales(&super::SqliteStorage::open(&path).unwrap());
+    std::fs::remove_dir_all(&path).unwrap();
+}
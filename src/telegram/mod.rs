@@ -0,0 +1,374 @@
+use std::sync::{Arc, Mutex};
+use std::io::Read;
+use anyhow::{anyhow, Context};
+use crossbeam_channel::Sender;
+use serde::{Deserialize, Serialize};
+use log::{debug, info, warn, error};
+use crate::{BotUpdate, Button, format_error};
+use crate::storage::SharedStorage;
+
+#[cfg(test)]
+mod tests;
+
+const POLL_TIMEOUT: u32 = 120;
+
+/// Longest text Telegram accepts in a message
+const MAX_MESSAGE_CHARS: usize = 4096;
+
+pub struct Telegram {
+    api_url: String,
+    file_url: String,
+    context: Arc<Mutex<TelegramContext>>,
+    storage: SharedStorage,
+    sender: Sender<BotUpdate>
+}
+
+impl Telegram {
+
+    pub fn new(sender: &Sender<BotUpdate>, storage: &SharedStorage) -> Self {
+
+        let token = std::env::var("NAG_TELEGRAM_TOKEN")
+            .expect("Environment variable NAG_TELEGRAM_TOKEN not set");
+        let api_url = format!("https://api.telegram.org/bot{}", token);
+        let file_url = format!("https://api.telegram.org/file/bot{}", token);
+
+        let context = storage.load_telegram_context()
+            .unwrap_or_else(|err| {
+                warn!("No Telegram context restored: {}", err);
+                info!("Creating new context");
+                TelegramContext::new()
+            });
+        let context = Arc::new(Mutex::new(context));
+
+        Telegram { 
+            api_url,
+            file_url,
+            context,
+            storage: storage.clone(),
+            sender: sender.clone()
+        }
+    }
+
+    pub fn send(&mut self, text: &str) {
+        self.send_with_buttons(text, &[])
+            .unwrap_or_else(|err| error!(
+                "Could not send Telegram message: {}",
+                format_error(err)));
+    }
+
+    /// Sends a message with rows of inline buttons, given as (label, callback data)
+    pub fn send_with_buttons(
+        &mut self, text: &str, buttons: &[Vec<Button>]
+    ) -> anyhow::Result<()> {
+
+        let context = self.context.lock().unwrap();
+
+        let chat_id = context.chat_id
+            .ok_or_else(|| anyhow!("no known ChatID stored"))?;
+
+        let url = format!("{}/sendMessage", self.api_url);
+        let chunks = split_message(text);
+        let nb_chunks = chunks.len();
+
+        for (i, chunk) in chunks.into_iter().enumerate() {
+
+            let mut json = ureq::json!({
+                "chat_id": chat_id,
+                "text": chunk,
+                "parse_mode": "HTML"
+            });
+
+            // The buttons go under the last part
+            if !buttons.is_empty() && i + 1 == nb_chunks {
+                let keyboard: Vec<Vec<_>> = buttons.iter()
+                    .map(|row| row.iter()
+                        .map(|(label, data)| ureq::json!({
+                            "text": label,
+                            "callback_data": data
+                        }))
+                        .collect())
+                    .collect();
+                json["reply_markup"] = ureq::json!({ "inline_keyboard": keyboard });
+            }
+
+            ureq::post(&url)
+                .send_json(json)
+                .context("call to Telegram API failed")?;
+        }
+
+        Ok(())
+    }
+
+    pub fn send_document(
+        &mut self, filename: &str, data: &[u8], caption: &str
+    ) -> anyhow::Result<()> {
+
+        const BOUNDARY: &str = "nag-document-boundary";
+
+        let context = self.context.lock().unwrap();
+
+        let chat_id = context.chat_id
+            .ok_or_else(|| anyhow!("no known ChatID stored"))?;
+
+        let mut body = vec![];
+        for (name, value) in [("chat_id", chat_id.to_string()), ("caption", caption.to_owned())] {
+            body.extend_from_slice(format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                BOUNDARY, name, value
+            ).as_bytes());
+        }
+        body.extend_from_slice(format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"document\"; filename=\"{}\"\r\n\
+            Content-Type: application/octet-stream\r\n\r\n",
+            BOUNDARY, filename
+        ).as_bytes());
+        body.extend_from_slice(data);
+        body.extend_from_slice(format!("\r\n--{}--\r\n", BOUNDARY).as_bytes());
+
+        let url = format!("{}/sendDocument", self.api_url);
+        ureq::post(&url)
+            .set("Content-Type", &format!("multipart/form-data; boundary={}", BOUNDARY))
+            .send_bytes(&body)
+            .context("call to Telegram API failed")?;
+
+        Ok(())
+    }
+
+    pub fn get_loop(&self) -> impl FnOnce() {
+
+        let api_url = self.api_url.clone();
+        let file_url = self.file_url.clone();
+        let context = self.context.clone();
+        let sender = self.sender.clone();
+        let storage = self.storage.clone();
+
+        move || {
+
+            let mut offset = 0u32;
+    
+            let mut relay_updates = move || -> anyhow::Result<()> {
+    
+                let poll_url = format!(
+                    "{}/getUpdates?offset={}&timeout={}&allowed_updates=[\"message\",\"callback_query\"]",
+                    api_url, offset, POLL_TIMEOUT
+                );
+            
+                let api_res: ReturnedUpdates = ureq::get(&poll_url)
+                    .call()?
+                    .into_json()?;
+    
+                let mut updates = api_res.result;
+                updates.sort_by_key(|update| update.update_id);
+    
+                if let Some(latest_update) = updates.last() {
+                    offset = latest_update.update_id + 1;
+                }
+
+                let latest_chat = updates.iter()
+                    .rev()
+                    .find_map(|update| update.chat_id());
+
+                if let Some(chat_id) = latest_chat {
+                    let mut context = context.lock().unwrap();
+                    let update = context.update_chat_id(chat_id);
+                    if update {
+                        storage.save_telegram_context(&context)
+                            .unwrap_or_else(|err| panic!(
+                                "Cannot save Telegram context: {}",
+                                format_error(err)));
+                    }
+                }
+    
+                for update in updates.iter() {
+
+                    debug!("Telegram update: {:?}", update);
+
+                    let chat_id = match update.chat_id() {
+                        Some(chat_id) => chat_id,
+                        None => continue
+                    };
+
+                    if let Some(text) = update.message.as_ref().and_then(|m| m.text.clone()) {
+                        info!("Received Telegram message: {}", text);
+                        sender.send(BotUpdate::MsgIn(chat_id, text)).unwrap();
+                    }
+
+                    if let Some(document) = update.message.as_ref().and_then(|m| m.document.as_ref()) {
+                        info!("Received Telegram document: {:?}", document.file_name);
+                        match download_file(&api_url, &file_url, &document.file_id) {
+                            Ok(data) => {
+                                let filename = document.file_name.clone().unwrap_or_default();
+                                sender.send(BotUpdate::DocumentIn(chat_id, filename, data)).unwrap();
+                            },
+                            Err(err) => error!(
+                                "Cannot download Telegram document: {}",
+                                format_error(err))
+                        }
+                    }
+
+                    if let Some(query) = &update.callback_query {
+                        info!("Received Telegram callback: {:?}", query.data);
+                        answer_callback(&api_url, &query.id)
+                            .unwrap_or_else(|err| warn!(
+                                "Cannot answer callback query: {}",
+                                format_error(err)));
+                        if let Some(data) = query.data.clone() {
+                            sender.send(BotUpdate::Callback(chat_id, data)).unwrap();
+                        }
+                    }
+                }
+    
+                Ok(())
+            };
+    
+            info!("Starting Telegram polling loop");
+            loop {
+                relay_updates()
+                    .unwrap_or_else(|err| error!(
+                        "Telegram: error retrieving updates: {}",
+                        format_error(err)
+                    ))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ReturnedUpdates {
+    result: Vec<Update>
+}
+
+fn answer_callback(api_url: &str, query_id: &str) -> anyhow::Result<()> {
+    let url = format!("{}/answerCallbackQuery", api_url);
+    ureq::post(&url).send_json(ureq::json!({ "callback_query_id": query_id }))?;
+    Ok(())
+}
+
+fn download_file(api_url: &str, file_url: &str, file_id: &str) -> anyhow::Result<Vec<u8>> {
+
+    let url = format!("{}/getFile?file_id={}", api_url, file_id);
+    let res: ReturnedFile = ureq::get(&url).call()?.into_json()?;
+
+    let url = format!("{}/{}", file_url, res.result.file_path);
+    let mut data = vec![];
+    ureq::get(&url).call()?
+        .into_reader()
+        .read_to_end(&mut data)?;
+
+    Ok(data)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ReturnedFile {
+    result: File
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct File {
+    file_path: String
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Update {
+    update_id: u32,
+    message: Option<Message>,
+    callback_query: Option<CallbackQuery>
+}
+
+impl Update {
+    fn chat_id(&self) -> Option<u32> {
+        self.message.as_ref()
+            .or_else(|| self.callback_query.as_ref()?.message.as_ref())
+            .map(|message| message.chat.id)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Message {
+    text: Option<String>,
+    document: Option<Document>,
+    chat: Chat
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Document {
+    file_id: String,
+    file_name: Option<String>
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct CallbackQuery {
+    id: String,
+    data: Option<String>,
+    message: Option<Message>
+}
+#[derive(Debug, Clone, Deserialize)]
+struct Chat {
+    id: u32
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct TelegramContext {
+    pub(crate) chat_id: Option<u32>
+}
+
+impl TelegramContext {
+
+    fn new() -> Self {
+        TelegramContext { chat_id: None }
+    }
+
+    fn update_chat_id(&mut self, new_id: u32) -> bool{
+
+        let update = self.chat_id != Some(new_id);
+        if update {
+            info!("Active ChatID changed to {}", new_id);
+            self.chat_id = Some(new_id);
+        }
+        update
+    }
+}
+
+/// Splits a text too long for one message between lines, so that the HTML
+/// tags of the replies, which never span lines, stay balanced. A single
+/// line too long is cut anywhere.
+fn split_message(text: &str) -> Vec<String> {
+
+    let mut chunks = vec![];
+    let mut chunk: Option<String> = None;
+    let mut chunk_chars = 0;
+
+    for mut line in text.split('\n') {
+
+        let line_chars = line.chars().count();
+
+        chunk = match chunk {
+            Some(mut curr) if chunk_chars + 1 + line_chars <= MAX_MESSAGE_CHARS => {
+                curr.push('\n');
+                chunk_chars += 1;
+                Some(curr)
+            },
+            Some(curr) => {
+                chunks.push(curr);
+                chunk_chars = 0;
+                None
+            },
+            None => None
+        };
+        let curr = chunk.get_or_insert_with(String::new);
+
+        while chunk_chars + line.chars().count() > MAX_MESSAGE_CHARS {
+            let (cut, _c) = line.char_indices().nth(MAX_MESSAGE_CHARS - chunk_chars).unwrap();
+            curr.push_str(&line[..cut]);
+            chunks.push(std::mem::take(curr));
+            chunk_chars = 0;
+            line = &line[cut..];
+        }
+
+        curr.push_str(line);
+        chunk_chars += line.chars().count();
+    }
+
+    chunks.extend(chunk);
+    chunks
+}
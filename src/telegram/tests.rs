@@ -0,0 +1,35 @@
+use super::{split_message, MAX_MESSAGE_CHARS};
+
+#[test]
+fn short_message() {
+    assert_eq!(split_message("hello\n\nworld"), ["hello\n\nworld"]);
+    assert_eq!(split_message(""), [""]);
+}
+
+#[test]
+fn split_between_lines() {
+
+    let line = "é".repeat(1000);
+    let text = [line.as_str(); 9].join("\n");
+
+    let chunks = split_message(&text);
+
+    assert_eq!(chunks, [
+        [line.as_str(); 4].join("\n"),
+        [line.as_str(); 4].join("\n"),
+        line.clone()
+    ]);
+}
+
+#[test]
+fn split_long_line() {
+
+    let text = format!("start\n{}", "a".repeat(2 * MAX_MESSAGE_CHARS + 10));
+
+    let chunks = split_message(&text);
+
+    assert_eq!(chunks.len(), 4);
+    assert_eq!(chunks[0], "start");
+    assert!(chunks.iter().all(|c| c.chars().count() <= MAX_MESSAGE_CHARS));
+    assert_eq!(chunks.concat(), text.replace('\n', ""));
+}